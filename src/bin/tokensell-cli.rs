@@ -0,0 +1,1352 @@
+use std::{error::Error, process::exit, str::FromStr};
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+  client_error::ClientError,
+  rpc_client::RpcClient,
+  rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+  rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::{
+  borsh::try_from_slice_unchecked,
+  commitment_config::CommitmentConfig,
+  instruction::{Instruction, InstructionError},
+  pubkey::Pubkey,
+  signature::{read_keypair_file, Keypair, Signature, Signer},
+  transaction::{Transaction, TransactionError},
+};
+
+use spl_associated_token_account::create_associated_token_account;
+
+use live_tokensell::{
+  client::{self, Voucher},
+  instructions::init_sell::InitSellArgs,
+  state::{
+    ComplianceGate,
+    Key,
+    ParticipantData,
+    PriceOracle,
+    RefundPolicy,
+    SaleStatus,
+    SellDataV1,
+    StakingTier,
+    StakingTiers,
+    TgePolicy,
+    DIRECTORY_PAGE_CAPACITY,
+    PARTICIPANT_SIZE,
+  },
+  utils::associated_token_address,
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+struct Config {
+  rpc: RpcClient,
+  payer: Keypair,
+  program_id: Pubkey,
+}
+
+fn pubkey_arg(matches: &ArgMatches, name: &str) -> CliResult<Pubkey> {
+  let value = matches.value_of(name).ok_or(format!("--{} is required", name))?;
+  Pubkey::from_str(value).map_err(|e| format!("invalid --{} {}: {}", name, value, e).into())
+}
+
+fn value_arg<T: FromStr>(matches: &ArgMatches, name: &str) -> CliResult<T>
+where
+  T::Err: std::fmt::Display,
+{
+  let value = matches.value_of(name).ok_or(format!("--{} is required", name))?;
+  value.parse().map_err(|e| format!("invalid --{} {}: {}", name, value, e).into())
+}
+
+/// Parses `MIN_STAKED:MAX_AMOUNT` staking tiers.
+fn tiers_arg(matches: &ArgMatches, name: &str) -> CliResult<Vec<StakingTier>> {
+  let values = matches.values_of(name).ok_or(format!("--{} is required", name))?;
+
+  values
+    .map(|value| {
+      let (min_staked, max_amount) = value.split_once(':').ok_or(format!("invalid --{} {}", name, value))?;
+
+      Ok(StakingTier {
+        min_staked: min_staked.parse().map_err(|e| format!("invalid --{} {}: {}", name, value, e))?,
+        max_amount: max_amount.parse().map_err(|e| format!("invalid --{} {}: {}", name, value, e))?,
+      })
+    })
+    .collect()
+}
+
+/// Reads `wallet,amount` rows, amounts in target token base units. Blank lines and a `wallet`
+/// header row are skipped.
+fn allocations_csv(path: &str) -> CliResult<Vec<(Pubkey, u64)>> {
+  let contents = std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+
+  contents
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with("wallet"))
+    .map(|(index, line)| {
+      let invalid = |e: &dyn std::fmt::Display| format!("{} line {}: {}", path, index + 1, e);
+      let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+
+      if columns.len() != 2 {
+        return Err(invalid(&"expected wallet,amount").into());
+      }
+
+      Ok((
+        columns[0].parse().map_err(|e| invalid(&e))?,
+        columns[1].parse().map_err(|e| invalid(&e))?,
+      ))
+    })
+    .collect()
+}
+
+/// Parses a decimal price such as `0.035` into a numerator and a power of ten denominator.
+fn price_arg(matches: &ArgMatches, name: &str) -> CliResult<(u64, u64)> {
+  let value = matches.value_of(name).ok_or(format!("--{} is required", name))?;
+  let invalid = || format!("invalid --{} {}", name, value);
+
+  let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+  if whole.is_empty() && fraction.is_empty() {
+    return Err(invalid().into());
+  }
+
+  let denominator = 10u64.checked_pow(fraction.len() as u32).ok_or_else(invalid)?;
+  let numerator = format!("{}{}", whole, fraction).parse::<u64>().map_err(|_| invalid())?;
+
+  Ok((numerator, denominator))
+}
+
+/// Spells out tokensell errors, which the RPC only reports as custom error codes.
+fn describe_error(error: ClientError) -> Box<dyn Error> {
+  if let Some(TransactionError::InstructionError(index, InstructionError::Custom(code))) = error.get_transaction_error() {
+    if let Some(tokensell_error) = client::decode_error(code) {
+      return format!("instruction {} failed with error {}: {}", index, code, tokensell_error).into();
+    }
+  }
+
+  error.into()
+}
+
+fn send(config: &Config, instructions: &[Instruction], extra_signers: &[&Keypair]) -> CliResult<()> {
+  let mut signers: Vec<&Keypair> = vec![&config.payer];
+  signers.extend_from_slice(extra_signers);
+
+  let blockhash = config.rpc.get_latest_blockhash()?;
+  let transaction = Transaction::new_signed_with_payer(
+    instructions,
+    Some(&config.payer.pubkey()),
+    &signers,
+    blockhash,
+  );
+
+  let signature = config.rpc.send_and_confirm_transaction(&transaction).map_err(describe_error)?;
+  println!("Signature: {}", signature);
+
+  Ok(())
+}
+
+/// Token program owning `mint`, classic Token or Token-2022.
+fn mint_program(config: &Config, mint: &Pubkey) -> CliResult<Pubkey> {
+  Ok(config.rpc.get_account(mint)?.owner)
+}
+
+/// Associated token account of `owner`, created in `instructions` when it's missing.
+fn ensure_associated_account(
+  config: &Config,
+  owner: &Pubkey,
+  mint: &Pubkey,
+  token_program: &Pubkey,
+  instructions: &mut Vec<Instruction>,
+) -> CliResult<Pubkey> {
+  let address = associated_token_address(owner, mint, token_program);
+
+  if config.rpc.get_account_with_commitment(&address, config.rpc.commitment())?.value.is_none() {
+    // like ClaimFor, only classic associated token accounts are created on the fly
+    if *token_program == spl_token_2022::id() {
+      return Err(format!(
+        "associated token account {} of {} has to exist for Token-2022 mint {}",
+        address, owner, mint,
+      ).into());
+    }
+
+    instructions.push(create_associated_token_account(&config.payer.pubkey(), owner, mint));
+  }
+
+  Ok(address)
+}
+
+fn account_data(config: &Config, address: &Pubkey) -> CliResult<Option<Vec<u8>>> {
+  let account = config.rpc.get_account_with_commitment(address, config.rpc.commitment())?.value;
+  Ok(account.map(|account| account.data))
+}
+
+/// Sales created by `owner` so far, the next sale of `owner` takes this number as sale id.
+fn owner_sales(config: &Config, owner: &Pubkey) -> CliResult<u64> {
+  let (counter, _) = client::find_sale_counter_address(&config.program_id, owner);
+
+  match account_data(config, &counter)? {
+    Some(data) => Ok(client::decode_sale_counter(&data)?.sales),
+    None => Ok(0),
+  }
+}
+
+/// Sales listed in the directory so far.
+fn directory_sales(config: &Config) -> CliResult<u64> {
+  let (directory, _) = client::find_directory_address(&config.program_id);
+
+  match account_data(config, &directory)? {
+    Some(data) => Ok(client::decode_directory(&data)?.sales),
+    None => Ok(0),
+  }
+}
+
+fn command_init_sell(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let source_mint = pubkey_arg(matches, "source_mint")?;
+  let target_mint = pubkey_arg(matches, "target_mint")?;
+  let amount: u64 = value_arg(matches, "amount")?;
+  let owner = config.payer.pubkey();
+
+  let target_program = mint_program(config, &target_mint)?;
+  let source_program = mint_program(config, &source_mint)?;
+
+  let sale_id = owner_sales(config, &owner)?;
+  let (sell, _) = client::find_sell_address(&config.program_id, &owner, sale_id);
+  let (vault, _) = client::find_vault_address(&config.program_id, &sell);
+  let funding = match matches.value_of("funding_account") {
+    Some(_) => pubkey_arg(matches, "funding_account")?,
+    None => associated_token_address(&owner, &target_mint, &target_program),
+  };
+
+  let mut instructions = vec![];
+
+  let (price_numerator, price_denominator) = price_arg(matches, "price")?;
+  let price_oracle = match matches.value_of("price_feed") {
+    Some(_) => Some(PriceOracle {
+      feed: pubkey_arg(matches, "price_feed")?,
+      max_age: value_arg(matches, "max_price_age")?,
+      max_confidence_bps: value_arg(matches, "max_confidence_bps")?,
+    }),
+    None => None,
+  };
+
+  let refund_policy = if matches.is_present("refund_until_end") {
+    RefundPolicy::UntilEnd
+  } else if matches.is_present("refund_window") {
+    RefundPolicy::AfterDeposit(value_arg(matches, "refund_window")?)
+  } else {
+    RefundPolicy::None
+  };
+
+  let compliance = match matches.value_of("compliance_authority") {
+    Some(_) => Some(ComplianceGate {
+      authority: pubkey_arg(matches, "compliance_authority")?,
+      gate_claims: matches.is_present("gate_claims"),
+    }),
+    None => None,
+  };
+
+  let voucher_signer = match matches.value_of("voucher_signer") {
+    Some(_) => Some(pubkey_arg(matches, "voucher_signer")?),
+    None => None,
+  };
+
+  let staking = match matches.value_of("staking_mint") {
+    Some(_) => Some(StakingTiers {
+      mint: pubkey_arg(matches, "staking_mint")?,
+      tiers: tiers_arg(matches, "tier")?,
+    }),
+    None => None,
+  };
+
+  let tge_policy = match matches.value_of("tge_delay") {
+    Some(_) => Some(TgePolicy {
+      delay: value_arg(matches, "tge_delay")?,
+      earliest: value_arg(matches, "tge_earliest")?,
+      latest: value_arg(matches, "tge_latest")?,
+    }),
+    None => None,
+  };
+
+  let target_acc = match matches.value_of("proceeds_account") {
+    Some(_) => pubkey_arg(matches, "proceeds_account")?,
+    None => ensure_associated_account(config, &owner, &source_mint, &source_program, &mut instructions)?,
+  };
+
+  instructions.push(client::init_sell(
+    &config.program_id,
+    &owner,
+    &target_mint,
+    &source_mint,
+    &target_acc,
+    &target_program,
+    &source_program,
+    directory_sales(config)?,
+    InitSellArgs {
+      instruction: 1,
+      price_numerator,
+      price_denominator,
+      start_time: value_arg(matches, "start_time")?,
+      end_time: value_arg(matches, "end_time")?,
+      initial_perc: value_arg(matches, "initial_perc")?,
+      total_months: value_arg(matches, "total_months")?,
+      min_deposit: value_arg(matches, "min_deposit")?,
+      total_amount: amount,
+      transfers_disabled: matches.is_present("non_transferable"),
+      sale_id,
+      price_oracle,
+      refund_policy,
+      compliance,
+      voucher_signer,
+      staking,
+      tge_policy,
+      price_oracle_program: match matches.value_of("price_oracle_program") {
+        Some(_) => Some(pubkey_arg(matches, "price_oracle_program")?),
+        None => None,
+      },
+    },
+  ));
+
+  instructions.push(client::fund_vault(
+    &config.program_id,
+    &owner,
+    &sell,
+    &funding,
+    &target_mint,
+    &target_program,
+    amount,
+  ));
+
+  send(config, &instructions, &[])?;
+
+  println!("Sell: {}", sell);
+  println!("Vault: {}", vault);
+
+  Ok(())
+}
+
+fn command_fund_vault(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let owner = config.payer.pubkey();
+
+  let target_mint = sell.target_mint.ok_or("sale has no recorded target mint")?;
+  let token_program = mint_program(config, &target_mint)?;
+  let funding = match matches.value_of("funding_account") {
+    Some(_) => pubkey_arg(matches, "funding_account")?,
+    None => associated_token_address(&owner, &target_mint, &token_program),
+  };
+
+  let instruction = client::fund_vault(
+    &config.program_id,
+    &owner,
+    &sell_key,
+    &funding,
+    &target_mint,
+    &token_program,
+    value_arg(matches, "amount")?,
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_deposit(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let payer = config.payer.pubkey();
+  let token_program = mint_program(config, &sell.source_mint)?;
+
+  let source_token = match matches.value_of("source_account") {
+    Some(_) => pubkey_arg(matches, "source_account")?,
+    None => associated_token_address(&payer, &sell.source_mint, &token_program),
+  };
+
+  let destination = match sell.refund_policy {
+    RefundPolicy::None => sell.target_acc,
+    _ => client::find_escrow_address(&config.program_id, &sell_key).0,
+  };
+
+  let mut instructions = vec![];
+
+  let voucher_nonce = match sell.voucher_signer {
+    Some(signer) => {
+      let voucher = Voucher {
+        wallet: payer,
+        sell: sell_key,
+        max_amount: value_arg(matches, "voucher_amount")?,
+        expires_at: value_arg(matches, "voucher_expires")?,
+        nonce: value_arg(matches, "voucher_nonce")?,
+      };
+      let signature: Signature = value_arg(matches, "voucher_signature")?;
+      let signature = <[u8; 64]>::try_from(signature.as_ref()).map_err(|_| "invalid voucher signature")?;
+
+      instructions.push(client::voucher_instruction(&signer, &signature, &voucher));
+      Some(voucher.nonce)
+    },
+    None => None,
+  };
+
+  instructions.push(client::make_deposit(
+    &config.program_id,
+    &payer,
+    &sell_key,
+    &source_token,
+    &destination,
+    &payer,
+    &sell.source_mint,
+    &token_program,
+    sell.price_oracle.as_ref().map(|oracle| &oracle.feed),
+    sell.compliance.as_ref().map(|gate| &gate.authority),
+    voucher_nonce,
+    sell.staking.as_ref().map(|staking| &staking.mint),
+    value_arg(matches, "amount")?,
+  ));
+
+  send(config, &instructions, &[])
+}
+
+fn command_sign_voucher(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let voucher = Voucher {
+    wallet: pubkey_arg(matches, "wallet")?,
+    sell: pubkey_arg(matches, "sell")?,
+    max_amount: value_arg(matches, "max_amount")?,
+    expires_at: value_arg(matches, "expires_at")?,
+    nonce: value_arg(matches, "nonce")?,
+  };
+
+  let signature = config.payer.sign_message(&client::voucher_message(&voucher));
+  println!("Voucher signature: {}", signature);
+
+  Ok(())
+}
+
+fn command_set_tge(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let instruction = set_tge_instruction(config, matches, &config.payer.pubkey())?;
+
+  send(config, &[instruction], &[])?;
+
+  let sell = client::decode_sell(&config.rpc.get_account_data(&pubkey_arg(matches, "sell")?)?)?;
+  if let Some(pending) = sell.pending_tge {
+    println!("TGE {} can be applied from {}", pending.tge, pending.eta);
+  }
+
+  Ok(())
+}
+
+/// Set TGE instruction of the sale in `matches`, signed by its `owner`.
+fn set_tge_instruction(config: &Config, matches: &ArgMatches, owner: &Pubkey) -> CliResult<Instruction> {
+  let tge = value_arg(matches, "tge")?;
+
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+
+  let target_mint = match sell.target_mint {
+    Some(mint) => mint,
+    None => pubkey_arg(matches, "target_mint")?,
+  };
+  let vault = match sell.source_vault {
+    Some(vault) => vault,
+    None => pubkey_arg(matches, "vault")?,
+  };
+
+  Ok(client::set_tge(
+    &config.program_id,
+    owner,
+    &sell_key,
+    &target_mint,
+    &vault,
+    tge,
+  ))
+}
+
+fn command_apply_tge(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let instruction = client::apply_tge(&config.program_id, &sell_key, sell.directory_index);
+
+  send(config, &[instruction], &[])
+}
+
+fn command_claim(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let payer = config.payer.pubkey();
+
+  let target_mint = match sell.target_mint {
+    Some(mint) => mint,
+    None => pubkey_arg(matches, "target_mint")?,
+  };
+  let vault = match sell.source_vault {
+    Some(vault) => vault,
+    None => pubkey_arg(matches, "vault")?,
+  };
+
+  let token_program = mint_program(config, &target_mint)?;
+  let mut instructions = vec![];
+  let target_token = ensure_associated_account(config, &payer, &target_mint, &token_program, &mut instructions)?;
+
+  instructions.push(client::claim(
+    &config.program_id,
+    &payer,
+    &sell_key,
+    &target_mint,
+    &vault,
+    &target_token,
+    &token_program,
+    client::claim_compliance_authority(&sell),
+  ));
+
+  send(config, &instructions, &[])
+}
+
+fn command_claim_for(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let owner = pubkey_arg(matches, "owner")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+
+  let target_mint = match sell.target_mint {
+    Some(mint) => mint,
+    None => pubkey_arg(matches, "target_mint")?,
+  };
+  let vault = match sell.source_vault {
+    Some(vault) => vault,
+    None => pubkey_arg(matches, "vault")?,
+  };
+
+  let instruction = client::claim_for(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &owner,
+    &sell_key,
+    &target_mint,
+    &vault,
+    &mint_program(config, &target_mint)?,
+    client::claim_compliance_authority(&sell),
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_transfer_position(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let recipient = pubkey_arg(matches, "recipient")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let instruction = client::transfer_position(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &recipient,
+    &sell_key,
+    sell.compliance.as_ref().map(|gate| &gate.authority),
+    sell.staking.as_ref().map(|staking| &staking.mint),
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_close_participant(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let instruction = client::close_participant(&config.program_id, &config.payer.pubkey(), &sell_key);
+
+  send(config, &[instruction], &[])
+}
+
+fn command_refund(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let owner = config.payer.pubkey();
+  let token_program = mint_program(config, &sell.source_mint)?;
+
+  let destination = match matches.value_of("destination") {
+    Some(_) => pubkey_arg(matches, "destination")?,
+    None => associated_token_address(&owner, &sell.source_mint, &token_program),
+  };
+
+  let instruction = client::refund(&config.program_id, &owner, &sell_key, &destination, &sell.source_mint, &token_program);
+
+  send(config, &[instruction], &[])
+}
+
+fn command_release_escrow(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let token_program = mint_program(config, &sell.source_mint)?;
+
+  let instruction = client::release_escrow(&config.program_id, &sell_key, &sell.target_acc, &sell.source_mint, &token_program);
+
+  send(config, &[instruction], &[])
+}
+
+fn command_set_revocable(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let keypair_path = matches.value_of("participant_keypair").unwrap();
+  let participant = read_keypair_file(keypair_path)
+    .map_err(|e| format!("failed to read keypair {}: {}", keypair_path, e))?;
+
+  let instruction = client::set_revocable(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &participant.pubkey(),
+    &sell_key,
+    !matches.is_present("clear"),
+  );
+
+  send(config, &[instruction], &[&participant])
+}
+
+fn command_revoke(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let wallet = pubkey_arg(matches, "wallet")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let owner = config.payer.pubkey();
+
+  let target_mint = sell.target_mint.ok_or("sale has no recorded target mint")?;
+  let vault = sell.source_vault.ok_or("sale has no recorded vault")?;
+  let token_program = mint_program(config, &target_mint)?;
+
+  let mut instructions = vec![];
+  let destination = match matches.value_of("destination") {
+    Some(_) => pubkey_arg(matches, "destination")?,
+    None => ensure_associated_account(config, &owner, &target_mint, &token_program, &mut instructions)?,
+  };
+
+  instructions.push(client::revoke(
+    &config.program_id,
+    &owner,
+    &wallet,
+    &sell_key,
+    &target_mint,
+    &vault,
+    &destination,
+    &token_program,
+  ));
+
+  send(config, &instructions, &[])
+}
+
+fn command_add_allocations(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let batch_size: usize = value_arg(matches, "batch_size")?;
+  let allocations = allocations_csv(matches.value_of("csv").unwrap())?;
+  let payer = config.payer.pubkey();
+
+  let owner = match matches.value_of("owner_keypair") {
+    Some(path) => Some(read_keypair_file(path).map_err(|e| format!("failed to read keypair {}: {}", path, e))?),
+    None => None,
+  };
+  let owner_key = owner.as_ref().map_or(payer, |owner| owner.pubkey());
+  let extra_signers: Vec<&Keypair> = owner.iter().collect();
+
+  for batch in allocations.chunks(batch_size.max(1)) {
+    let instructions: Vec<Instruction> = batch
+      .iter()
+      .map(|(wallet, amount)| client::add_allocation(&config.program_id, &payer, &owner_key, &sell_key, wallet, *amount, false))
+      .collect();
+
+    send(config, &instructions, &extra_signers)?;
+  }
+
+  println!("Added {} allocations", allocations.len());
+
+  Ok(())
+}
+
+fn command_stake(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let mint = pubkey_arg(matches, "mint")?;
+  let owner = config.payer.pubkey();
+  let token_program = mint_program(config, &mint)?;
+
+  let source_token = match matches.value_of("source_account") {
+    Some(_) => pubkey_arg(matches, "source_account")?,
+    None => associated_token_address(&owner, &mint, &token_program),
+  };
+
+  let instruction = client::stake(
+    &config.program_id,
+    &owner,
+    &mint,
+    &source_token,
+    &token_program,
+    value_arg(matches, "amount")?,
+    value_arg(matches, "lock_until")?,
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_unstake(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let mint = pubkey_arg(matches, "mint")?;
+  let owner = config.payer.pubkey();
+  let token_program = mint_program(config, &mint)?;
+
+  let destination = match matches.value_of("destination") {
+    Some(_) => pubkey_arg(matches, "destination")?,
+    None => associated_token_address(&owner, &mint, &token_program),
+  };
+
+  let instruction = client::unstake(
+    &config.program_id,
+    &owner,
+    &mint,
+    &destination,
+    &token_program,
+    value_arg(matches, "amount")?,
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_issue_attestation(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let jurisdiction = match matches.value_of("jurisdiction") {
+    Some(code) => Some(
+      <[u8; 2]>::try_from(code.to_ascii_uppercase().as_bytes())
+        .map_err(|_| format!("invalid jurisdiction {}, expected a two letter code", code))?,
+    ),
+    None => None,
+  };
+
+  let instruction = client::issue_attestation(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &pubkey_arg(matches, "wallet")?,
+    value_arg(matches, "expires_at")?,
+    jurisdiction,
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_revoke_attestation(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let instruction = client::revoke_attestation(&config.program_id, &config.payer.pubkey(), &pubkey_arg(matches, "wallet")?);
+
+  send(config, &[instruction], &[])
+}
+
+fn command_set_owner(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let instruction = client::set_owner(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &pubkey_arg(matches, "sell")?,
+    &pubkey_arg(matches, "new_owner")?,
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_create_multisig(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let signers = matches
+    .values_of("signer")
+    .ok_or("--signer is required")?
+    .map(|value| Pubkey::from_str(value).map_err(|e| format!("invalid --signer {}: {}", value, e)))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let create_key = Keypair::new();
+  let (multisig, _) = client::find_multisig_address(&config.program_id, &create_key.pubkey());
+  let (authority, _) = client::find_multisig_authority(&config.program_id, &multisig);
+
+  let instruction = client::create_multisig(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &create_key.pubkey(),
+    value_arg(matches, "threshold")?,
+    &signers,
+  );
+
+  send(config, &[instruction], &[&create_key])?;
+
+  println!("Multisig: {}", multisig);
+  println!("Authority: {}", authority);
+
+  Ok(())
+}
+
+/// Proposes setting the TGE of a sale owned by the multisig authority.
+fn command_propose_set_tge(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let multisig = pubkey_arg(matches, "multisig")?;
+  let index = client::decode_multisig(&config.rpc.get_account_data(&multisig)?)?.proposals;
+  let (authority, _) = client::find_multisig_authority(&config.program_id, &multisig);
+
+  let proposed = set_tge_instruction(config, matches, &authority)?;
+  let instruction = client::create_proposal(&config.program_id, &config.payer.pubkey(), &multisig, index, &proposed);
+
+  send(config, &[instruction], &[])?;
+
+  println!("Proposal: {}", index);
+
+  Ok(())
+}
+
+fn command_approve_proposal(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let instruction = client::approve_proposal(
+    &config.program_id,
+    &config.payer.pubkey(),
+    &pubkey_arg(matches, "multisig")?,
+    value_arg(matches, "index")?,
+  );
+
+  send(config, &[instruction], &[])
+}
+
+fn command_execute_proposal(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let multisig = pubkey_arg(matches, "multisig")?;
+  let index = value_arg(matches, "index")?;
+  let (proposal, _) = client::find_proposal_address(&config.program_id, &multisig, index);
+  let proposal = client::decode_proposal(&config.rpc.get_account_data(&proposal)?)?;
+
+  let proposed = client::proposed_instruction(&config.program_id, &proposal);
+  let instruction = client::execute_proposal(&config.program_id, &multisig, index, &proposed);
+
+  send(config, &[instruction], &[])
+}
+
+fn command_show_sell(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let (sell_authority, _) = client::find_sell_authority(&config.program_id, &sell_key);
+
+  println!("Sell: {}", sell_key);
+  println!("Sell authority: {}", sell_authority);
+  println!("{:#?}", sell);
+
+  Ok(())
+}
+
+fn command_list_sales(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  println!("{:<44} {:<44} {:<8} {:>12} {:>12}", "Sell", "Owner", "Status", "Start", "End");
+
+  if matches.value_of("owner").is_some() {
+    let owner = pubkey_arg(matches, "owner")?;
+
+    for sale_id in 0..owner_sales(config, &owner)? {
+      let (sell_key, _) = client::find_sell_address(&config.program_id, &owner, sale_id);
+      let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+      let status = if sell.tge.is_some() { SaleStatus::TgeSet } else { SaleStatus::Pending };
+
+      print_sale(&sell_key, &owner, status, sell.start_time, sell.end_time);
+    }
+
+    return Ok(());
+  }
+
+  let sales = directory_sales(config)?;
+  let pages = sales.div_ceil(DIRECTORY_PAGE_CAPACITY as u64);
+
+  for page in 0..pages {
+    let (page_key, _) = client::find_directory_page_address(&config.program_id, page);
+    let directory_page = client::decode_directory_page(&config.rpc.get_account_data(&page_key)?)?;
+
+    for entry in directory_page.entries {
+      print_sale(&entry.sell, &entry.owner, entry.status, entry.start_time, entry.end_time);
+    }
+  }
+
+  Ok(())
+}
+
+fn print_sale(sell: &Pubkey, owner: &Pubkey, status: SaleStatus, start_time: i64, end_time: i64) {
+  println!(
+    "{:<44} {:<44} {:<8} {:>12} {:>12}",
+    sell.to_string(),
+    owner.to_string(),
+    format!("{:?}", status),
+    start_time,
+    end_time
+  );
+}
+
+fn command_migrate(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let account = pubkey_arg(matches, "account")?;
+  let data = config.rpc.get_account_data(&account)?;
+  let payer = config.payer.pubkey();
+  let mut participant_wallet = None;
+
+  let instruction = if Key::from_data(&data) == Key::SellDataV1 {
+    let legacy: SellDataV1 = try_from_slice_unchecked(&data)?;
+
+    let target_mint = match legacy.target_mint {
+      Some(mint) => mint,
+      None => pubkey_arg(matches, "target_mint")?,
+    };
+    let vault = match legacy.source_vault {
+      Some(vault) => vault,
+      None => pubkey_arg(matches, "vault")?,
+    };
+
+    client::migrate_legacy_sell(&config.program_id, &payer, &account, &legacy.source_mint, &target_mint, &vault)
+  } else if Key::from_data(&data) == Key::ParticipantDataV1 {
+    let mut data = data.clone();
+    data.resize(data.len().max(PARTICIPANT_SIZE), 0);
+    let legacy: ParticipantData = try_from_slice_unchecked(&data)?;
+
+    let keypair_path = matches.value_of("participant_keypair").ok_or("--participant-keypair is required")?;
+    let wallet = read_keypair_file(keypair_path)
+      .map_err(|e| format!("failed to read keypair {}: {}", keypair_path, e))?;
+    let instruction = client::migrate_legacy_participant(
+      &config.program_id,
+      &payer,
+      &account,
+      &legacy.sell,
+      &wallet.pubkey(),
+      value_arg(matches, "claimed")?,
+    );
+
+    participant_wallet = Some(wallet);
+    instruction
+  } else {
+    client::migrate(&config.program_id, &payer, &account)
+  };
+
+  let signers: Vec<&Keypair> = participant_wallet.iter().collect();
+  send(config, &[instruction], &signers)
+}
+
+fn fetch_participants(config: &Config, sell_key: &Pubkey) -> CliResult<Vec<ParticipantData>> {
+  let accounts = config.rpc.get_program_accounts_with_config(
+    &config.program_id,
+    RpcProgramAccountsConfig {
+      filters: Some(vec![
+        RpcFilterType::DataSize(PARTICIPANT_SIZE as u64),
+        // key (1) + owner (32) precede the sell field
+        RpcFilterType::Memcmp(Memcmp {
+          offset: 1 + 32,
+          bytes: MemcmpEncodedBytes::Base58(sell_key.to_string()),
+          encoding: None,
+        }),
+      ]),
+      account_config: RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        ..RpcAccountInfoConfig::default()
+      },
+      with_context: None,
+    },
+  )?;
+
+  Ok(
+    accounts
+      .into_iter()
+      .filter_map(|(_, account)| client::decode_participant(&account.data).ok())
+      .collect(),
+  )
+}
+
+fn command_list_participants(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let now = config.rpc.get_block_time(config.rpc.get_slot()?)?;
+
+  println!("{:<44} {:>20} {:>20} {:>20}", "Owner", "Amount", "Claimed", "Claimable");
+
+  for participant in fetch_participants(config, &sell_key)? {
+    let claimable = client::claimable_amount(&sell, &participant, now);
+
+    println!(
+      "{:<44} {:>20} {:>20} {:>20}",
+      participant.owner.to_string(),
+      participant.amount,
+      participant.claimed,
+      claimable
+    );
+  }
+
+  Ok(())
+}
+
+fn command_distribute(config: &Config, matches: &ArgMatches) -> CliResult<()> {
+  let sell_key = pubkey_arg(matches, "sell")?;
+  let batch_size: usize = value_arg(matches, "batch_size")?;
+  let sell = client::decode_sell(&config.rpc.get_account_data(&sell_key)?)?;
+  let now = config.rpc.get_block_time(config.rpc.get_slot()?)?;
+
+  let (target_mint, vault) = match (sell.target_mint, sell.source_vault) {
+    (Some(mint), Some(vault)) => (mint, vault),
+    _ => return Err("sale has no recorded target mint and vault".into()),
+  };
+
+  let token_program = mint_program(config, &target_mint)?;
+
+  let recipients: Vec<Pubkey> = fetch_participants(config, &sell_key)?
+    .into_iter()
+    .filter(|participant| client::claimable_amount(&sell, participant, now) > 0)
+    .map(|participant| participant.owner)
+    .collect();
+
+  for batch in recipients.chunks(batch_size.max(1)) {
+    let mut instructions = vec![];
+    let mut pairs = vec![];
+
+    for wallet in batch {
+      let destination = ensure_associated_account(config, wallet, &target_mint, &token_program, &mut instructions)?;
+      pairs.push((*wallet, destination));
+    }
+
+    instructions.push(client::batch_distribute(
+      &config.program_id,
+      &config.payer.pubkey(),
+      &sell_key,
+      &target_mint,
+      &vault,
+      &token_program,
+      &pairs,
+      client::claim_compliance_authority(&sell),
+    ));
+
+    send(config, &instructions, &[])?;
+  }
+
+  println!("Distributed to {} participants", recipients.len());
+
+  Ok(())
+}
+
+fn sell_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("sell")
+    .long("sell")
+    .value_name("PUBKEY")
+    .takes_value(true)
+    .required(true)
+    .help("Sell account")
+}
+
+fn value<'a, 'b>(name: &'a str, long: &'a str, help: &'a str) -> Arg<'a, 'b> {
+  Arg::with_name(name)
+    .long(long)
+    .takes_value(true)
+    .help(help)
+}
+
+fn main() {
+  let default_keypair = dirs_keypair_path();
+
+  let matches = App::new("tokensell-cli")
+    .about("Operate token sales")
+    .setting(AppSettings::SubcommandRequiredElseHelp)
+    .arg(
+      Arg::with_name("keypair")
+        .long("keypair")
+        .short("k")
+        .value_name("PATH")
+        .takes_value(true)
+        .global(true)
+        .default_value(&default_keypair)
+        .help("Fee payer and signer keypair file"),
+    )
+    .arg(
+      Arg::with_name("url")
+        .long("url")
+        .short("u")
+        .value_name("URL")
+        .takes_value(true)
+        .global(true)
+        .default_value("http://localhost:8899")
+        .help("JSON RPC URL of the cluster"),
+    )
+    .arg(
+      Arg::with_name("program_id")
+        .long("program-id")
+        .value_name("PUBKEY")
+        .takes_value(true)
+        .global(true)
+        .required(true)
+        .help("Deployed tokensell program"),
+    )
+    .subcommand(
+      SubCommand::with_name("init-sell")
+        .about("Create a sale, fund its vault and initialize it")
+        .arg(value("source_mint", "source-mint", "Mint participants pay with").required(true))
+        .arg(value("target_mint", "target-mint", "Mint being sold").required(true))
+        .arg(value("amount", "amount", "Tokens moved into the vault and offered").required(true))
+        .arg(value("price", "price", "Payment tokens per sold token, e.g. 0.035, or USD with --price-feed").required(true))
+        .arg(value("price_feed", "price-feed", "Pyth price feed of the payment token, prices the sale in USD"))
+        .arg(value("price_oracle_program", "price-oracle-program", "Program owning --price-feed, Pyth by default"))
+        .arg(value("max_price_age", "max-price-age", "Oldest price feed update accepted, in seconds").default_value("60"))
+        .arg(value("max_confidence_bps", "max-confidence-bps", "Widest price confidence accepted, in basis points").default_value("100"))
+        .arg(value("start_time", "start", "Sale start, unix timestamp").required(true))
+        .arg(value("end_time", "end", "Sale end, unix timestamp").required(true))
+        .arg(value("initial_perc", "initial-perc", "Percent unlocked at TGE").default_value("0"))
+        .arg(value("total_months", "months", "Vesting duration in months").default_value("0"))
+        .arg(value("min_deposit", "min-deposit", "Minimal first payment").default_value("0"))
+        .arg(Arg::with_name("non_transferable").long("non-transferable").help("Forbid moving positions to other wallets"))
+        .arg(value("refund_window", "refund-window", "Seconds each deposit can be refunded for, payments wait in an escrow"))
+        .arg(
+          Arg::with_name("refund_until_end")
+            .long("refund-until-end")
+            .conflicts_with("refund_window")
+            .help("Allow refunds until the sale ends, payments wait in an escrow"),
+        )
+        .arg(value("compliance_authority", "compliance-authority", "Authority whose attestations depositing wallets need"))
+        .arg(
+          Arg::with_name("gate_claims")
+            .long("gate-claims")
+            .requires("compliance_authority")
+            .help("Require attestations to claim as well"),
+        )
+        .arg(value("voucher_signer", "voucher-signer", "Key whose signed vouchers depositing wallets need"))
+        .arg(value("staking_mint", "staking-mint", "Mint participants stake to reach an allocation tier").requires("tier"))
+        .arg(
+          value("tier", "tier", "MIN_STAKED:MAX_AMOUNT allocation tier, repeated in ascending order")
+            .multiple(true)
+            .number_of_values(1)
+            .requires("staking_mint"),
+        )
+        .arg(value("tge_delay", "tge-delay", "Seconds TGE changes wait before they apply, by default 2 days with the TGE within 90 days after the end").requires_all(&["tge_earliest", "tge_latest"]))
+        .arg(value("tge_earliest", "tge-earliest", "Earliest TGE, unix timestamp").requires("tge_delay"))
+        .arg(value("tge_latest", "tge-latest", "Latest TGE, unix timestamp").requires("tge_delay"))
+        .arg(value("funding_account", "funding-account", "Token account funding the vault, defaults to the payer ATA"))
+        .arg(value("proceeds_account", "proceeds-account", "Token account receiving payments, defaults to the payer ATA")),
+    )
+    .subcommand(
+      SubCommand::with_name("fund-vault")
+        .about("Move more sold tokens into a sale vault")
+        .arg(sell_arg())
+        .arg(value("amount", "amount", "Tokens moved into the vault").required(true))
+        .arg(value("funding_account", "funding-account", "Token account funding the vault, defaults to the payer ATA")),
+    )
+    .subcommand(
+      SubCommand::with_name("deposit")
+        .about("Buy tokens in a sale")
+        .arg(sell_arg())
+        .arg(value("amount", "amount", "Tokens to buy").required(true))
+        .arg(value("source_account", "source-account", "Token account paying, defaults to the payer ATA"))
+        .arg(value("voucher_amount", "voucher-amount", "Tokens the payer's voucher allows"))
+        .arg(value("voucher_expires", "voucher-expires", "Voucher expiry, unix timestamp"))
+        .arg(value("voucher_nonce", "voucher-nonce", "Voucher nonce"))
+        .arg(value("voucher_signature", "voucher-signature", "Voucher signature of the sale's voucher signer")),
+    )
+    .subcommand(
+      SubCommand::with_name("sign-voucher")
+        .about("Sign a voucher as the voucher signer of a sale, with the payer keypair")
+        .arg(sell_arg())
+        .arg(value("wallet", "wallet", "Wallet the voucher is for").required(true))
+        .arg(value("max_amount", "max-amount", "Tokens the wallet can buy with it").required(true))
+        .arg(value("expires_at", "expires", "Voucher expiry, unix timestamp").required(true))
+        .arg(value("nonce", "nonce", "Nonce, each nonce is a separate allocation").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("set-tge")
+        .about("Queue a change of the token generation event of a sale, apply-tge makes it after the delay")
+        .arg(sell_arg())
+        .arg(value("tge", "tge", "TGE unix timestamp").required(true))
+        .arg(value("target_mint", "target-mint", "Mint being sold, when not recorded on the sale"))
+        .arg(value("vault", "vault", "Sale vault, when not recorded on the sale")),
+    )
+    .subcommand(
+      SubCommand::with_name("apply-tge")
+        .about("Apply the queued TGE change of a sale once its delay is over")
+        .arg(sell_arg()),
+    )
+    .subcommand(
+      SubCommand::with_name("claim")
+        .about("Claim unlocked tokens to the payer ATA")
+        .arg(sell_arg())
+        .arg(value("target_mint", "target-mint", "Mint being sold, when not recorded on the sale"))
+        .arg(value("vault", "vault", "Sale vault, when not recorded on the sale")),
+    )
+    .subcommand(
+      SubCommand::with_name("claim-for")
+        .about("Claim unlocked tokens to a participant's ATA, paying for the ATA if missing")
+        .arg(sell_arg())
+        .arg(value("owner", "owner", "Participant wallet").required(true))
+        .arg(value("target_mint", "target-mint", "Mint being sold, when not recorded on the sale"))
+        .arg(value("vault", "vault", "Sale vault, when not recorded on the sale")),
+    )
+    .subcommand(
+      SubCommand::with_name("distribute")
+        .about("Push vested tokens to every participant's ATA, as the sale owner")
+        .arg(sell_arg())
+        .arg(value("batch_size", "batch-size", "Participants per transaction").default_value("6")),
+    )
+    .subcommand(
+      SubCommand::with_name("transfer-position")
+        .about("Move the payer's position, claimed part included, to another wallet")
+        .arg(sell_arg())
+        .arg(value("recipient", "recipient", "Wallet receiving the position").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("close-participant")
+        .about("Close the payer's fully claimed participant account and reclaim its rent")
+        .arg(sell_arg()),
+    )
+    .subcommand(
+      SubCommand::with_name("refund")
+        .about("Take back the payer's payment while the sale's refund window is open")
+        .arg(sell_arg())
+        .arg(value("destination", "destination", "Token account receiving the refund, defaults to the payer ATA")),
+    )
+    .subcommand(
+      SubCommand::with_name("release-escrow")
+        .about("Pay the escrow of a sale out to its proceeds account once refunds are over")
+        .arg(sell_arg()),
+    )
+    .subcommand(
+      SubCommand::with_name("add-allocations")
+        .about("Grant irrevocable allocations from a wallet,amount CSV file, as the sale owner")
+        .arg(sell_arg())
+        .arg(value("csv", "csv", "CSV file of allocations, amounts in target base units").required(true))
+        .arg(value("owner_keypair", "owner-keypair", "Keypair file of the sale owner, when not the payer"))
+        .arg(value("batch_size", "batch-size", "Allocations per transaction").default_value("8")),
+    )
+    .subcommand(
+      SubCommand::with_name("set-revocable")
+        .about("Make a position revocable, as the sale owner together with the participant")
+        .arg(sell_arg())
+        .arg(value("participant_keypair", "participant-keypair", "Keypair file of the participant").required(true))
+        .arg(Arg::with_name("clear").long("clear").help("Make the position irrevocable again")),
+    )
+    .subcommand(
+      SubCommand::with_name("revoke")
+        .about("Stop vesting of a revocable position and take back the unvested tokens")
+        .arg(sell_arg())
+        .arg(value("wallet", "wallet", "Participant wallet").required(true))
+        .arg(value("destination", "destination", "Token account receiving the unvested tokens, defaults to the payer ATA")),
+    )
+    .subcommand(
+      SubCommand::with_name("stake")
+        .about("Lock tokens of a mint in the payer's stake, extending its lock")
+        .arg(value("mint", "mint", "Staked mint").required(true))
+        .arg(value("amount", "amount", "Tokens to lock").required(true))
+        .arg(value("lock_until", "lock-until", "End of the lock, unix timestamp").required(true))
+        .arg(value("source_account", "source-account", "Token account paying, defaults to the payer ATA")),
+    )
+    .subcommand(
+      SubCommand::with_name("unstake")
+        .about("Take tokens out of the payer's stake once its lock ended")
+        .arg(value("mint", "mint", "Staked mint").required(true))
+        .arg(value("amount", "amount", "Tokens to take out").required(true))
+        .arg(value("destination", "destination", "Token account receiving them, defaults to the payer ATA")),
+    )
+    .subcommand(
+      SubCommand::with_name("issue-attestation")
+        .about("Attest a wallet as the payer, renewing any previous attestation")
+        .arg(value("wallet", "wallet", "Attested wallet").required(true))
+        .arg(value("expires_at", "expires", "Attestation expiry, unix timestamp").required(true))
+        .arg(value("jurisdiction", "jurisdiction", "Two letter country code of the wallet")),
+    )
+    .subcommand(
+      SubCommand::with_name("revoke-attestation")
+        .about("Revoke an attestation the payer issued")
+        .arg(value("wallet", "wallet", "Attested wallet").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("set-owner")
+        .about("Hand a sale of the payer to another owner, such as a multisig authority")
+        .arg(sell_arg())
+        .arg(value("new_owner", "new-owner", "New owner of the sale").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("create-multisig")
+        .about("Create an M-of-N multisig and print the authority to hand sales to")
+        .arg(value("signer", "signer", "Multisig signer, repeated for each").multiple(true).number_of_values(1).required(true))
+        .arg(value("threshold", "threshold", "Approvals needed to execute a proposal").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("propose-set-tge")
+        .about("Propose setting the TGE of a multisig owned sale, as one of its signers")
+        .arg(value("multisig", "multisig", "Multisig owning the sale").required(true))
+        .arg(sell_arg())
+        .arg(value("tge", "tge", "TGE unix timestamp").required(true))
+        .arg(value("target_mint", "target-mint", "Mint being sold, when not recorded on the sale"))
+        .arg(value("vault", "vault", "Sale vault, when not recorded on the sale")),
+    )
+    .subcommand(
+      SubCommand::with_name("approve-proposal")
+        .about("Approve a multisig proposal as one of its signers")
+        .arg(value("multisig", "multisig", "Multisig").required(true))
+        .arg(value("index", "index", "Proposal index").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("execute-proposal")
+        .about("Execute a multisig proposal that reached its threshold")
+        .arg(value("multisig", "multisig", "Multisig").required(true))
+        .arg(value("index", "index", "Proposal index").required(true)),
+    )
+    .subcommand(
+      SubCommand::with_name("show-sell")
+        .about("Print a sale account")
+        .arg(sell_arg()),
+    )
+    .subcommand(
+      SubCommand::with_name("list-sales")
+        .about("Print the sales listed in the sale directory")
+        .arg(value("owner", "owner", "Only print the sales created by this wallet")),
+    )
+    .subcommand(
+      SubCommand::with_name("migrate")
+        .about("Rewrite a sell or participant account in the latest layout")
+        .arg(value("account", "account", "Sell or participant account").required(true))
+        .arg(value("target_mint", "target-mint", "Mint being sold, for sales created before versioning"))
+        .arg(value("vault", "vault", "Sale vault, for sales created before versioning"))
+        .arg(value("claimed", "claimed", "Tokens already paid out, for participants created before versioning"))
+        .arg(value("participant_keypair", "participant-keypair", "Keypair file of the participant agreeing to --claimed")),
+    )
+    .subcommand(
+      SubCommand::with_name("list-participants")
+        .about("Print all participants of a sale")
+        .arg(sell_arg()),
+    )
+    .get_matches();
+
+  let config = match load_config(&matches) {
+    Ok(config) => config,
+    Err(e) => {
+      eprintln!("error: {}", e);
+      exit(1);
+    }
+  };
+
+  let result = match matches.subcommand() {
+    ("init-sell", Some(m)) => command_init_sell(&config, m),
+    ("fund-vault", Some(m)) => command_fund_vault(&config, m),
+    ("deposit", Some(m)) => command_deposit(&config, m),
+    ("sign-voucher", Some(m)) => command_sign_voucher(&config, m),
+    ("set-tge", Some(m)) => command_set_tge(&config, m),
+    ("apply-tge", Some(m)) => command_apply_tge(&config, m),
+    ("claim", Some(m)) => command_claim(&config, m),
+    ("claim-for", Some(m)) => command_claim_for(&config, m),
+    ("distribute", Some(m)) => command_distribute(&config, m),
+    ("transfer-position", Some(m)) => command_transfer_position(&config, m),
+    ("close-participant", Some(m)) => command_close_participant(&config, m),
+    ("refund", Some(m)) => command_refund(&config, m),
+    ("release-escrow", Some(m)) => command_release_escrow(&config, m),
+    ("add-allocations", Some(m)) => command_add_allocations(&config, m),
+    ("set-revocable", Some(m)) => command_set_revocable(&config, m),
+    ("revoke", Some(m)) => command_revoke(&config, m),
+    ("stake", Some(m)) => command_stake(&config, m),
+    ("unstake", Some(m)) => command_unstake(&config, m),
+    ("issue-attestation", Some(m)) => command_issue_attestation(&config, m),
+    ("revoke-attestation", Some(m)) => command_revoke_attestation(&config, m),
+    ("set-owner", Some(m)) => command_set_owner(&config, m),
+    ("create-multisig", Some(m)) => command_create_multisig(&config, m),
+    ("propose-set-tge", Some(m)) => command_propose_set_tge(&config, m),
+    ("approve-proposal", Some(m)) => command_approve_proposal(&config, m),
+    ("execute-proposal", Some(m)) => command_execute_proposal(&config, m),
+    ("show-sell", Some(m)) => command_show_sell(&config, m),
+    ("list-sales", Some(m)) => command_list_sales(&config, m),
+    ("migrate", Some(m)) => command_migrate(&config, m),
+    ("list-participants", Some(m)) => command_list_participants(&config, m),
+    _ => unreachable!(),
+  };
+
+  if let Err(e) = result {
+    eprintln!("error: {}", e);
+    exit(1);
+  }
+}
+
+fn load_config(matches: &ArgMatches) -> CliResult<Config> {
+  let keypair_path = matches.value_of("keypair").unwrap();
+  let payer = read_keypair_file(keypair_path)
+    .map_err(|e| format!("failed to read keypair {}: {}", keypair_path, e))?;
+
+  Ok(Config {
+    rpc: RpcClient::new_with_commitment(matches.value_of("url").unwrap().to_string(), CommitmentConfig::confirmed()),
+    payer,
+    program_id: pubkey_arg(matches, "program_id")?,
+  })
+}
+
+fn dirs_keypair_path() -> String {
+  match std::env::var("HOME") {
+    Ok(home) => format!("{}/.config/solana/id.json", home),
+    Err(_) => "id.json".to_string(),
+  }
+}
@@ -0,0 +1,1071 @@
+//! Off-chain helpers for integrators, enabled with the `client` feature.
+
+use solana_program::{
+  decode_error::DecodeError,
+  ed25519_program,
+  pubkey::Pubkey,
+  clock::UnixTimestamp,
+  instruction::{AccountMeta, Instruction},
+  program_error::ProgramError,
+  borsh::try_from_slice_unchecked,
+  system_program,
+  sysvar,
+};
+
+use borsh::BorshSerialize;
+
+use crate::{
+  state::{
+    assert_latest_layout,
+    Attestation,
+    Directory,
+    DirectoryPage,
+    Key,
+    Multisig,
+    ParticipantData,
+    Proposal,
+    ProposalAccount,
+    RefundPolicy,
+    SaleCounter,
+    SellData,
+    Stake,
+    COUNTER_SEED,
+    DIRECTORY_SEED,
+    ESCROW_SEED,
+    PARTICIPANT_SIZE,
+    PREFIX,
+    TOKENSELL_SELL_SIZE,
+    VAULT_SEED,
+  },
+  directory::directory_page_of,
+  error::TokensellError,
+  instructions::{
+    make_deposit::MakeDepositArgs,
+    init_sell::InitSellArgs,
+    fund_vault::FundVaultArgs,
+    issue_attestation::IssueAttestationArgs,
+    set_tge::SetTgeArgs,
+    migrate::MigrateParticipantArgs,
+    stake::StakeArgs,
+    unstake::UnstakeArgs,
+    set_revocable::SetRevocableArgs,
+    add_allocation::AddAllocationArgs,
+    set_owner::SetOwnerArgs,
+    create_multisig::CreateMultisigArgs,
+    create_proposal::CreateProposalArgs,
+  },
+  utils::associated_token_address,
+  vesting::vested_amount,
+  voucher::ed25519_instruction_data,
+};
+
+pub use crate::compliance::find_attestation_address;
+pub use crate::voucher::{find_voucher_receipt_address, Voucher};
+pub use crate::staking::{find_stake_address, find_stake_authority, find_stake_vault_address};
+pub use crate::multisig::{find_multisig_address, find_multisig_authority, find_proposal_address};
+
+/// Bytes the voucher signer of a sale signs for `voucher`.
+pub fn voucher_message(voucher: &Voucher) -> Vec<u8> {
+  voucher.try_to_vec().unwrap()
+}
+
+/// Ed25519 program instruction presenting `voucher` with the `signature` of `signer`,
+/// placed right before the deposit it allows.
+pub fn voucher_instruction(signer: &Pubkey, signature: &[u8; 64], voucher: &Voucher) -> Instruction {
+  Instruction {
+    program_id: ed25519_program::id(),
+    accounts: vec![],
+    data: ed25519_instruction_data(signer, signature, &voucher_message(voucher)),
+  }
+}
+
+/// Sale account `init_sell` creates for `owner` and `sale_id`.
+pub fn find_sell_address(program_id: &Pubkey, owner: &Pubkey, sale_id: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    owner.as_ref(),
+    &sale_id.to_le_bytes(),
+  ], program_id)
+}
+
+/// `SaleCounter` of `owner`, the next sale of `owner` uses its `sales` as sale id.
+pub fn find_sale_counter_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    owner.as_ref(),
+    COUNTER_SEED.as_bytes(),
+  ], program_id)
+}
+
+/// `Directory` counting every sale listed so far.
+pub fn find_directory_address(program_id: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    DIRECTORY_SEED.as_bytes(),
+  ], program_id)
+}
+
+/// `DirectoryPage` number `page`, listing up to `DIRECTORY_PAGE_CAPACITY` sales.
+pub fn find_directory_page_address(program_id: &Pubkey, page: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    DIRECTORY_SEED.as_bytes(),
+    &page.to_le_bytes(),
+  ], program_id)
+}
+
+/// PDA that owns the sale vault and signs claim transfers.
+pub fn find_sell_authority(program_id: &Pubkey, sell: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell.as_ref(),
+  ], program_id)
+}
+
+/// PDA of the token account `init_sell` creates to hold the sold tokens.
+pub fn find_vault_address(program_id: &Pubkey, sell: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell.as_ref(),
+    VAULT_SEED.as_bytes(),
+  ], program_id)
+}
+
+/// PDA of the token account of the source mint holding the payments of a sale with a
+/// refund policy.
+pub fn find_escrow_address(program_id: &Pubkey, sell: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell.as_ref(),
+    ESCROW_SEED.as_bytes(),
+  ], program_id)
+}
+
+/// PDA holding the `ParticipantData` of `owner` in `sell`.
+pub fn find_participant_address(program_id: &Pubkey, sell: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell.as_ref(),
+    owner.as_ref(),
+  ], program_id)
+}
+
+pub fn decode_sell(data: &[u8]) -> Result<SellData, ProgramError> {
+  assert_latest_layout(data, Key::SellDataV2, TOKENSELL_SELL_SIZE)?;
+
+  let sell: SellData = try_from_slice_unchecked(data)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  Ok(sell)
+}
+
+pub fn decode_participant(data: &[u8]) -> Result<ParticipantData, ProgramError> {
+  assert_latest_layout(data, Key::ParticipantDataV2, PARTICIPANT_SIZE)?;
+
+  let participant: ParticipantData = try_from_slice_unchecked(data)?;
+
+  if participant.key != Key::ParticipantDataV2 {
+    return Err(TokensellError::NotParticipantAccount.into());
+  }
+
+  Ok(participant)
+}
+
+pub fn decode_attestation(data: &[u8]) -> Result<Attestation, ProgramError> {
+  let attestation: Attestation = try_from_slice_unchecked(data)?;
+
+  if attestation.key != Key::Attestation {
+    return Err(TokensellError::NotAttestationAccount.into());
+  }
+
+  Ok(attestation)
+}
+
+pub fn decode_stake(data: &[u8]) -> Result<Stake, ProgramError> {
+  let stake: Stake = try_from_slice_unchecked(data)?;
+
+  if stake.key != Key::Stake {
+    return Err(TokensellError::InvalidStakeAccount.into());
+  }
+
+  Ok(stake)
+}
+
+pub fn decode_multisig(data: &[u8]) -> Result<Multisig, ProgramError> {
+  let multisig: Multisig = try_from_slice_unchecked(data)?;
+
+  if multisig.key != Key::Multisig {
+    return Err(TokensellError::NotMultisigAccount.into());
+  }
+
+  Ok(multisig)
+}
+
+pub fn decode_proposal(data: &[u8]) -> Result<Proposal, ProgramError> {
+  let proposal: Proposal = try_from_slice_unchecked(data)?;
+
+  if proposal.key != Key::Proposal {
+    return Err(TokensellError::InvalidProposal.into());
+  }
+
+  Ok(proposal)
+}
+
+/// Instruction stored in `proposal`.
+pub fn proposed_instruction(program_id: &Pubkey, proposal: &Proposal) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: proposal.accounts
+      .iter()
+      .map(|account| AccountMeta {
+        pubkey: account.pubkey,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+      })
+      .collect(),
+    data: proposal.data.clone(),
+  }
+}
+
+pub fn decode_sale_counter(data: &[u8]) -> Result<SaleCounter, ProgramError> {
+  let counter: SaleCounter = try_from_slice_unchecked(data)?;
+
+  if counter.key != Key::SaleCounter {
+    return Err(TokensellError::NotDirectoryAccount.into());
+  }
+
+  Ok(counter)
+}
+
+pub fn decode_directory(data: &[u8]) -> Result<Directory, ProgramError> {
+  let directory: Directory = try_from_slice_unchecked(data)?;
+
+  if directory.key != Key::Directory {
+    return Err(TokensellError::NotDirectoryAccount.into());
+  }
+
+  Ok(directory)
+}
+
+pub fn decode_directory_page(data: &[u8]) -> Result<DirectoryPage, ProgramError> {
+  let page: DirectoryPage = try_from_slice_unchecked(data)?;
+
+  if page.key != Key::DirectoryPage {
+    return Err(TokensellError::NotDirectoryAccount.into());
+  }
+
+  Ok(page)
+}
+
+/// Tokensell error behind the custom error code of a failed instruction.
+///
+/// Transfers pass token program errors through, and those share the same code range, the
+/// program logs tell them apart.
+pub fn decode_error(code: u32) -> Option<TokensellError> {
+  TokensellError::decode_custom_error_to_enum(code)
+}
+
+/// Amount `process_claim` would transfer to `participant` at `now`.
+pub fn claimable_amount(sell: &SellData, participant: &ParticipantData, now: UnixTimestamp) -> u64 {
+  let tge = match sell.tge {
+    Some(tge) => tge,
+    None => return 0,
+  };
+
+  if participant.revoked_at.is_some() {
+    return participant.amount.saturating_sub(participant.claimed);
+  }
+
+  vested_amount(participant.amount, sell.initial_perc, sell.total_months, tge, now)
+    .map(|vested| vested.saturating_sub(participant.claimed))
+    .unwrap_or(0)
+}
+
+/// Compliance authority whose attestations claims from `sell` need, if it gates claims.
+pub fn claim_compliance_authority(sell: &SellData) -> Option<&Pubkey> {
+  sell.compliance
+    .as_ref()
+    .filter(|gate| gate.gate_claims)
+    .map(|gate| &gate.authority)
+}
+
+/// `target_acc` receives the payment, the sale's target account, or `find_escrow_address`
+/// for sales with a refund policy.
+/// `token_program` is the program owning `source_mint`, classic Token or Token-2022.
+/// `price_feed` is the feed of the sale's `price_oracle`, for sales priced in USD.
+/// Sales with a compliance gate pass its `compliance_authority`, and the payer's attestation.
+/// `voucher_nonce` is the nonce of the payer's voucher, for sales with a voucher signer, the
+/// deposit then follows the `voucher_instruction` of that voucher.
+/// `staking_mint` is the mint of the sale's staking tiers, when it has some.
+#[allow(clippy::too_many_arguments)]
+pub fn make_deposit(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  sell: &Pubkey,
+  source_token: &Pubkey,
+  target_acc: &Pubkey,
+  transfer_authority: &Pubkey,
+  source_mint: &Pubkey,
+  token_program: &Pubkey,
+  price_feed: Option<&Pubkey>,
+  compliance_authority: Option<&Pubkey>,
+  voucher_nonce: Option<u64>,
+  staking_mint: Option<&Pubkey>,
+  amount: u64,
+) -> Instruction {
+  let (participant, _) = find_participant_address(program_id, sell, payer);
+
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new(*source_token, false),
+    AccountMeta::new(*sell, false),
+    AccountMeta::new(*target_acc, false),
+    AccountMeta::new_readonly(*transfer_authority, true),
+    AccountMeta::new_readonly(*token_program, false),
+    AccountMeta::new(participant, false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(*source_mint, false),
+  ];
+
+  if let Some(price_feed) = price_feed {
+    accounts.push(AccountMeta::new_readonly(*price_feed, false));
+  }
+
+  if let Some(authority) = compliance_authority {
+    accounts.push(AccountMeta::new_readonly(find_attestation_address(program_id, authority, payer).0, false));
+  }
+
+  if let Some(nonce) = voucher_nonce {
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    accounts.push(AccountMeta::new(find_voucher_receipt_address(program_id, sell, payer, nonce).0, false));
+  }
+
+  if let Some(mint) = staking_mint {
+    accounts.push(AccountMeta::new_readonly(find_stake_address(program_id, mint, payer).0, false));
+  }
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &MakeDepositArgs {
+      instruction: 0,
+      amount,
+    },
+    accounts,
+  )
+}
+
+/// Creates the sale of `payer` numbered `args.sale_id`, at `find_sell_address`.
+///
+/// `token_program` is the program owning `target_mint`, the vault is created under it.
+/// `source_token_program` owns `source_mint`, sales with a refund policy create their
+/// escrow under it.
+/// `directory_sales` is the `sales` count of the directory, the sale is listed after them.
+/// Sales priced in USD pass the feed of `args.price_oracle`.
+#[allow(clippy::too_many_arguments)]
+pub fn init_sell(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  target_mint: &Pubkey,
+  source_mint: &Pubkey,
+  target_acc: &Pubkey,
+  token_program: &Pubkey,
+  source_token_program: &Pubkey,
+  directory_sales: u64,
+  args: InitSellArgs,
+) -> Instruction {
+  let (sell, _) = find_sell_address(program_id, payer, args.sale_id);
+  let (sell_authority, _) = find_sell_authority(program_id, &sell);
+  let (token_vault, _) = find_vault_address(program_id, &sell);
+  let (sale_counter, _) = find_sale_counter_address(program_id, payer);
+  let (directory, _) = find_directory_address(program_id);
+  let (directory_page, _) = find_directory_page_address(program_id, directory_page_of(directory_sales));
+  let price_feed = args.price_oracle.as_ref().map(|oracle| oracle.feed);
+
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new(sell, false),
+    AccountMeta::new(token_vault, false),
+    AccountMeta::new_readonly(*target_mint, false),
+    AccountMeta::new_readonly(*source_mint, false),
+    AccountMeta::new_readonly(*target_acc, false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(sell_authority, false),
+    AccountMeta::new_readonly(*token_program, false),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new(sale_counter, false),
+    AccountMeta::new(directory, false),
+    AccountMeta::new(directory_page, false),
+  ];
+
+  if let Some(price_feed) = price_feed {
+    accounts.push(AccountMeta::new_readonly(price_feed, false));
+  }
+
+  if args.refund_policy != RefundPolicy::None {
+    let (escrow, _) = find_escrow_address(program_id, &sell);
+    accounts.push(AccountMeta::new(escrow, false));
+    accounts.push(AccountMeta::new_readonly(*source_token_program, false));
+  }
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &InitSellArgs {
+      instruction: 1,
+      ..args
+    },
+    accounts,
+  )
+}
+
+/// Claims what `payer` has vested into `target_token`, with the attestation of the
+/// `claim_compliance_authority` of the sale.
+#[allow(clippy::too_many_arguments)]
+pub fn claim(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  sell: &Pubkey,
+  target_mint: &Pubkey,
+  sell_vault: &Pubkey,
+  target_token: &Pubkey,
+  token_program: &Pubkey,
+  compliance_authority: Option<&Pubkey>,
+) -> Instruction {
+  let (sell_authority, _) = find_sell_authority(program_id, sell);
+  let (participant, _) = find_participant_address(program_id, sell, payer);
+
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*sell, false),
+    AccountMeta::new_readonly(*target_mint, false),
+    AccountMeta::new_readonly(sell_authority, false),
+    AccountMeta::new(*sell_vault, false),
+    AccountMeta::new_readonly(*token_program, false),
+    AccountMeta::new(participant, false),
+    AccountMeta::new(*target_token, false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+
+  if let Some(authority) = compliance_authority {
+    accounts.push(AccountMeta::new_readonly(find_attestation_address(program_id, authority, payer).0, false));
+  }
+
+  Instruction::new_with_bytes(*program_id, &[2], accounts)
+}
+
+/// Queues `tge` on `sell`, `apply_tge` makes it once the delay of the sale's TGE policy is over.
+pub fn set_tge(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  sell: &Pubkey,
+  target_mint: &Pubkey,
+  source_vault: &Pubkey,
+  tge: UnixTimestamp,
+) -> Instruction {
+  Instruction::new_with_borsh(
+    *program_id,
+    &SetTgeArgs {
+      instruction: 3,
+      tge: Some(tge),
+    },
+    vec![
+      AccountMeta::new(*payer, true),
+      AccountMeta::new(*sell, false),
+      AccountMeta::new_readonly(*target_mint, false),
+      AccountMeta::new_readonly(*source_vault, false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ],
+  )
+}
+
+/// Rewrites a participant account created after versioning, or a sale, in the latest layout.
+pub fn migrate(program_id: &Pubkey, payer: &Pubkey, account: &Pubkey) -> Instruction {
+  Instruction::new_with_bytes(
+    *program_id,
+    &[4],
+    vec![
+      AccountMeta::new(*payer, true),
+      AccountMeta::new(*account, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(system_program::id(), false),
+    ],
+  )
+}
+
+/// Migrates a sale created before versioning, `owner` has to be the sale owner.
+pub fn migrate_legacy_sell(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  sell: &Pubkey,
+  source_mint: &Pubkey,
+  target_mint: &Pubkey,
+  token_vault: &Pubkey,
+) -> Instruction {
+  let mut instruction = migrate(program_id, owner, sell);
+
+  instruction.accounts.extend([
+    AccountMeta::new_readonly(*source_mint, false),
+    AccountMeta::new_readonly(*target_mint, false),
+    AccountMeta::new_readonly(*token_vault, false),
+  ]);
+
+  instruction
+}
+
+/// Migrates a participant created before versioning, signed by `owner`, the sale owner, and by
+/// `wallet`, the participant. `claimed` is what the participant was already paid.
+pub fn migrate_legacy_participant(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  participant: &Pubkey,
+  sell: &Pubkey,
+  wallet: &Pubkey,
+  claimed: u64,
+) -> Instruction {
+  let mut instruction = migrate(program_id, owner, participant);
+
+  instruction.accounts.push(AccountMeta::new_readonly(*sell, false));
+  instruction.accounts.push(AccountMeta::new_readonly(*wallet, true));
+  instruction.data = MigrateParticipantArgs {
+    instruction: 4,
+    claimed,
+  }.try_to_vec().unwrap();
+
+  instruction
+}
+
+pub fn close_participant(program_id: &Pubkey, owner: &Pubkey, sell: &Pubkey) -> Instruction {
+  let (participant, _) = find_participant_address(program_id, sell, owner);
+
+  Instruction::new_with_bytes(
+    *program_id,
+    &[5],
+    vec![
+      AccountMeta::new(*owner, true),
+      AccountMeta::new_readonly(*sell, false),
+      AccountMeta::new(participant, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ],
+  )
+}
+
+/// Claims for `owner` into their associated token account, creating it at `cranker`'s expense,
+/// like `claim` does for the participant.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_for(
+  program_id: &Pubkey,
+  cranker: &Pubkey,
+  owner: &Pubkey,
+  sell: &Pubkey,
+  target_mint: &Pubkey,
+  sell_vault: &Pubkey,
+  token_program: &Pubkey,
+  compliance_authority: Option<&Pubkey>,
+) -> Instruction {
+  let (sell_authority, _) = find_sell_authority(program_id, sell);
+  let (participant, _) = find_participant_address(program_id, sell, owner);
+
+  let mut accounts = vec![
+    AccountMeta::new(*cranker, true),
+    AccountMeta::new_readonly(*owner, false),
+    AccountMeta::new_readonly(*sell, false),
+    AccountMeta::new_readonly(*target_mint, false),
+    AccountMeta::new_readonly(sell_authority, false),
+    AccountMeta::new(*sell_vault, false),
+    AccountMeta::new_readonly(*token_program, false),
+    AccountMeta::new(participant, false),
+    AccountMeta::new(associated_token_address(owner, target_mint, token_program), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+  ];
+
+  if let Some(authority) = compliance_authority {
+    accounts.push(AccountMeta::new_readonly(find_attestation_address(program_id, authority, owner).0, false));
+  }
+
+  Instruction::new_with_bytes(*program_id, &[6], accounts)
+}
+
+/// Distributes vested tokens to each `(participant wallet, destination token account)` pair,
+/// each with its attestation under `compliance_authority` as in `claim`.
+#[allow(clippy::too_many_arguments)]
+pub fn batch_distribute(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  sell: &Pubkey,
+  target_mint: &Pubkey,
+  sell_vault: &Pubkey,
+  token_program: &Pubkey,
+  recipients: &[(Pubkey, Pubkey)],
+  compliance_authority: Option<&Pubkey>,
+) -> Instruction {
+  let (sell_authority, _) = find_sell_authority(program_id, sell);
+
+  let mut accounts = vec![
+    AccountMeta::new_readonly(*owner, true),
+    AccountMeta::new_readonly(*sell, false),
+    AccountMeta::new_readonly(*target_mint, false),
+    AccountMeta::new_readonly(sell_authority, false),
+    AccountMeta::new(*sell_vault, false),
+    AccountMeta::new_readonly(*token_program, false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+
+  for (wallet, destination) in recipients {
+    let (participant, _) = find_participant_address(program_id, sell, wallet);
+    accounts.push(AccountMeta::new(participant, false));
+    accounts.push(AccountMeta::new(*destination, false));
+
+    if let Some(authority) = compliance_authority {
+      accounts.push(AccountMeta::new_readonly(find_attestation_address(program_id, authority, wallet).0, false));
+    }
+  }
+
+  Instruction::new_with_bytes(*program_id, &[7], accounts)
+}
+
+/// Moves `owner`'s position in `sell` to `recipient`.
+/// `compliance_authority` and `staking_mint` are those of the sale's compliance gate and staking
+/// tiers, when it has them, the recipient's attestation and stake are checked.
+pub fn transfer_position(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  recipient: &Pubkey,
+  sell: &Pubkey,
+  compliance_authority: Option<&Pubkey>,
+  staking_mint: Option<&Pubkey>,
+) -> Instruction {
+  let (participant, _) = find_participant_address(program_id, sell, owner);
+  let (recipient_participant, _) = find_participant_address(program_id, sell, recipient);
+
+  let mut accounts = vec![
+    AccountMeta::new(*owner, true),
+    AccountMeta::new_readonly(*recipient, false),
+    AccountMeta::new_readonly(*sell, false),
+    AccountMeta::new(participant, false),
+    AccountMeta::new(recipient_participant, false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+    AccountMeta::new_readonly(system_program::id(), false),
+  ];
+
+  if let Some(authority) = compliance_authority {
+    accounts.push(AccountMeta::new_readonly(find_attestation_address(program_id, authority, recipient).0, false));
+  }
+
+  if let Some(mint) = staking_mint {
+    accounts.push(AccountMeta::new_readonly(find_stake_address(program_id, mint, recipient).0, false));
+  }
+
+  Instruction::new_with_bytes(*program_id, &[8], accounts)
+}
+
+/// Moves `amount` of the sold token from `owner`'s `funding` account into the sale vault.
+pub fn fund_vault(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  sell: &Pubkey,
+  funding: &Pubkey,
+  target_mint: &Pubkey,
+  token_program: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  let (token_vault, _) = find_vault_address(program_id, sell);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &FundVaultArgs {
+      instruction: 9,
+      amount,
+    },
+    vec![
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new_readonly(*sell, false),
+      AccountMeta::new(*funding, false),
+      AccountMeta::new(token_vault, false),
+      AccountMeta::new_readonly(*target_mint, false),
+      AccountMeta::new_readonly(*token_program, false),
+    ],
+  )
+}
+
+/// Refunds the position of `owner` into their `destination` account of the source mint.
+pub fn refund(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  sell: &Pubkey,
+  destination: &Pubkey,
+  source_mint: &Pubkey,
+  token_program: &Pubkey,
+) -> Instruction {
+  let (participant, _) = find_participant_address(program_id, sell, owner);
+  let (escrow, _) = find_escrow_address(program_id, sell);
+  let (sell_authority, _) = find_sell_authority(program_id, sell);
+
+  Instruction::new_with_bytes(
+    *program_id,
+    &[10],
+    vec![
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new(*sell, false),
+      AccountMeta::new(participant, false),
+      AccountMeta::new(escrow, false),
+      AccountMeta::new_readonly(sell_authority, false),
+      AccountMeta::new(*destination, false),
+      AccountMeta::new_readonly(*source_mint, false),
+      AccountMeta::new_readonly(*token_program, false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ],
+  )
+}
+
+/// Pays the escrow of `sell` out to its `target_acc` once refunds are over.
+pub fn release_escrow(
+  program_id: &Pubkey,
+  sell: &Pubkey,
+  target_acc: &Pubkey,
+  source_mint: &Pubkey,
+  token_program: &Pubkey,
+) -> Instruction {
+  let (escrow, _) = find_escrow_address(program_id, sell);
+  let (sell_authority, _) = find_sell_authority(program_id, sell);
+
+  Instruction::new_with_bytes(
+    *program_id,
+    &[11],
+    vec![
+      AccountMeta::new_readonly(*sell, false),
+      AccountMeta::new(escrow, false),
+      AccountMeta::new_readonly(sell_authority, false),
+      AccountMeta::new(*target_acc, false),
+      AccountMeta::new_readonly(*source_mint, false),
+      AccountMeta::new_readonly(*token_program, false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ],
+  )
+}
+
+/// Attests `wallet` until `expires_at` as compliance `authority`, which pays for the account.
+pub fn issue_attestation(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  wallet: &Pubkey,
+  expires_at: UnixTimestamp,
+  jurisdiction: Option<[u8; 2]>,
+) -> Instruction {
+  let (attestation, _) = find_attestation_address(program_id, authority, wallet);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &IssueAttestationArgs {
+      instruction: 12,
+      expires_at,
+      jurisdiction,
+    },
+    vec![
+      AccountMeta::new(*authority, true),
+      AccountMeta::new_readonly(*wallet, false),
+      AccountMeta::new(attestation, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(system_program::id(), false),
+    ],
+  )
+}
+
+pub fn revoke_attestation(program_id: &Pubkey, authority: &Pubkey, wallet: &Pubkey) -> Instruction {
+  let (attestation, _) = find_attestation_address(program_id, authority, wallet);
+
+  Instruction::new_with_bytes(
+    *program_id,
+    &[13],
+    vec![
+      AccountMeta::new_readonly(*authority, true),
+      AccountMeta::new(attestation, false),
+    ],
+  )
+}
+
+/// Locks `amount` of `mint` from `source_token` until at least `lock_until`, in the stake of
+/// `owner`. `token_program` owns `mint`.
+pub fn stake(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  mint: &Pubkey,
+  source_token: &Pubkey,
+  token_program: &Pubkey,
+  amount: u64,
+  lock_until: UnixTimestamp,
+) -> Instruction {
+  let (stake, _) = find_stake_address(program_id, mint, owner);
+  let (stake_vault, _) = find_stake_vault_address(program_id, &stake);
+  let (stake_authority, _) = find_stake_authority(program_id, &stake);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &StakeArgs {
+      instruction: 14,
+      amount,
+      lock_until,
+    },
+    vec![
+      AccountMeta::new(*owner, true),
+      AccountMeta::new(stake, false),
+      AccountMeta::new(stake_vault, false),
+      AccountMeta::new_readonly(stake_authority, false),
+      AccountMeta::new_readonly(*mint, false),
+      AccountMeta::new(*source_token, false),
+      AccountMeta::new_readonly(*token_program, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+      AccountMeta::new_readonly(system_program::id(), false),
+    ],
+  )
+}
+
+pub fn unstake(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  mint: &Pubkey,
+  destination: &Pubkey,
+  token_program: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  let (stake, _) = find_stake_address(program_id, mint, owner);
+  let (stake_vault, _) = find_stake_vault_address(program_id, &stake);
+  let (stake_authority, _) = find_stake_authority(program_id, &stake);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &UnstakeArgs {
+      instruction: 15,
+      amount,
+    },
+    vec![
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new(stake, false),
+      AccountMeta::new(stake_vault, false),
+      AccountMeta::new_readonly(stake_authority, false),
+      AccountMeta::new_readonly(*mint, false),
+      AccountMeta::new(*destination, false),
+      AccountMeta::new_readonly(*token_program, false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ],
+  )
+}
+
+/// Needs the signatures of both the sale `owner` and the participant `wallet`.
+pub fn set_revocable(program_id: &Pubkey, owner: &Pubkey, wallet: &Pubkey, sell: &Pubkey, revocable: bool) -> Instruction {
+  let (participant, _) = find_participant_address(program_id, sell, wallet);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &SetRevocableArgs {
+      instruction: 16,
+      revocable,
+    },
+    vec![
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new_readonly(*wallet, true),
+      AccountMeta::new_readonly(*sell, false),
+      AccountMeta::new(participant, false),
+    ],
+  )
+}
+
+/// Revokes the position of `wallet`, `destination` receives the unvested tokens.
+/// `token_program` owns `target_mint`.
+#[allow(clippy::too_many_arguments)]
+pub fn revoke(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  wallet: &Pubkey,
+  sell: &Pubkey,
+  target_mint: &Pubkey,
+  sell_vault: &Pubkey,
+  destination: &Pubkey,
+  token_program: &Pubkey,
+) -> Instruction {
+  let (sell_authority, _) = find_sell_authority(program_id, sell);
+  let (participant, _) = find_participant_address(program_id, sell, wallet);
+
+  Instruction::new_with_bytes(
+    *program_id,
+    &[17],
+    vec![
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new(*sell, false),
+      AccountMeta::new(participant, false),
+      AccountMeta::new_readonly(*target_mint, false),
+      AccountMeta::new_readonly(sell_authority, false),
+      AccountMeta::new(*sell_vault, false),
+      AccountMeta::new(*destination, false),
+      AccountMeta::new_readonly(*token_program, false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ],
+  )
+}
+
+/// Grants `amount` to `wallet`, signed by the sale `owner`. `payer` pays for a new position.
+/// Revocable grants are signed by `wallet` as well.
+pub fn add_allocation(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  owner: &Pubkey,
+  sell: &Pubkey,
+  wallet: &Pubkey,
+  amount: u64,
+  revocable: bool,
+) -> Instruction {
+  let (participant, _) = find_participant_address(program_id, sell, wallet);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &AddAllocationArgs {
+      instruction: 18,
+      amount,
+      revocable,
+    },
+    vec![
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new(*sell, false),
+      AccountMeta::new_readonly(*wallet, revocable),
+      AccountMeta::new(participant, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(sysvar::clock::id(), false),
+      AccountMeta::new_readonly(system_program::id(), false),
+    ],
+  )
+}
+
+/// Hands the sale to `new_owner`, such as `find_multisig_authority` of a multisig.
+pub fn set_owner(program_id: &Pubkey, owner: &Pubkey, sell: &Pubkey, new_owner: &Pubkey) -> Instruction {
+  Instruction::new_with_borsh(
+    *program_id,
+    &SetOwnerArgs {
+      instruction: 19,
+      owner: *new_owner,
+    },
+    vec![
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new(*sell, false),
+    ],
+  )
+}
+
+/// `create_key` is any new keypair signing the creation, it only makes the multisig address unique.
+pub fn create_multisig(program_id: &Pubkey, payer: &Pubkey, create_key: &Pubkey, threshold: u8, signers: &[Pubkey]) -> Instruction {
+  let (multisig, _) = find_multisig_address(program_id, create_key);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &CreateMultisigArgs {
+      instruction: 20,
+      threshold,
+      signers: signers.to_vec(),
+    },
+    vec![
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*create_key, true),
+      AccountMeta::new(multisig, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(system_program::id(), false),
+    ],
+  )
+}
+
+/// Proposes `instruction`, an instruction of this program signed by the multisig authority,
+/// as proposal `index`, the multisig's proposal count.
+pub fn create_proposal(program_id: &Pubkey, proposer: &Pubkey, multisig: &Pubkey, index: u64, instruction: &Instruction) -> Instruction {
+  let (proposal, _) = find_proposal_address(program_id, multisig, index);
+
+  Instruction::new_with_borsh(
+    *program_id,
+    &CreateProposalArgs {
+      instruction: 21,
+      accounts: instruction.accounts
+        .iter()
+        .map(|account| ProposalAccount {
+          pubkey: account.pubkey,
+          is_signer: account.is_signer,
+          is_writable: account.is_writable,
+        })
+        .collect(),
+      data: instruction.data.clone(),
+    },
+    vec![
+      AccountMeta::new(*proposer, true),
+      AccountMeta::new(*multisig, false),
+      AccountMeta::new(proposal, false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(system_program::id(), false),
+    ],
+  )
+}
+
+pub fn approve_proposal(program_id: &Pubkey, signer: &Pubkey, multisig: &Pubkey, index: u64) -> Instruction {
+  let (proposal, _) = find_proposal_address(program_id, multisig, index);
+
+  Instruction::new_with_bytes(
+    *program_id,
+    &[22],
+    vec![
+      AccountMeta::new_readonly(*signer, true),
+      AccountMeta::new_readonly(*multisig, false),
+      AccountMeta::new(proposal, false),
+    ],
+  )
+}
+
+/// Executes proposal `index` of `multisig`, which proposed `instruction`. Signers of the
+/// instruction other than the multisig authority have to sign the transaction.
+pub fn execute_proposal(program_id: &Pubkey, multisig: &Pubkey, index: u64, instruction: &Instruction) -> Instruction {
+  let (proposal, _) = find_proposal_address(program_id, multisig, index);
+  let (authority, _) = find_multisig_authority(program_id, multisig);
+
+  let mut accounts = vec![
+    AccountMeta::new_readonly(*multisig, false),
+    AccountMeta::new(proposal, false),
+    AccountMeta::new_readonly(authority, false),
+    AccountMeta::new_readonly(*program_id, false),
+  ];
+
+  accounts.extend(instruction.accounts.iter().map(|account| AccountMeta {
+    pubkey: account.pubkey,
+    is_signer: account.is_signer && account.pubkey != authority,
+    is_writable: account.is_writable,
+  }));
+
+  Instruction::new_with_bytes(*program_id, &[23], accounts)
+}
+
+/// Applies the TGE change queued on `sell` once its delay is over, anyone can send it.
+/// `directory_index` is the one recorded on the sale.
+pub fn apply_tge(program_id: &Pubkey, sell: &Pubkey, directory_index: Option<u64>) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*sell, false),
+    AccountMeta::new_readonly(sysvar::clock::id(), false),
+  ];
+
+  if let Some(index) = directory_index {
+    let (directory_page, _) = find_directory_page_address(program_id, directory_page_of(index));
+    accounts.push(AccountMeta::new(directory_page, false));
+  }
+
+  Instruction::new_with_bytes(*program_id, &[24], accounts)
+}
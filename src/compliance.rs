@@ -0,0 +1,57 @@
+use solana_program::{
+  account_info::AccountInfo,
+  clock::UnixTimestamp,
+  entrypoint::ProgramResult,
+  msg,
+  pubkey::Pubkey,
+};
+
+use crate::{
+  error::TokensellError,
+  state::{Attestation, ComplianceGate, ATTESTATION_SEED, PREFIX},
+};
+
+/// Address of the attestation `authority` issues for `wallet`.
+pub fn find_attestation_address(program_id: &Pubkey, authority: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    authority.as_ref(),
+    ATTESTATION_SEED.as_bytes(),
+    wallet.as_ref(),
+  ], program_id)
+}
+
+/// Fails unless `attestation_info` is an attestation of `wallet` by the gate's authority that
+/// is neither revoked nor expired at `now`.
+pub fn assert_attested(
+  program_id: &Pubkey,
+  gate: &ComplianceGate,
+  attestation_info: Option<&AccountInfo>,
+  wallet: &Pubkey,
+  now: UnixTimestamp,
+) -> ProgramResult {
+  let attestation_info = attestation_info.ok_or(TokensellError::AttestationRequired)?;
+
+  if find_attestation_address(program_id, &gate.authority, wallet).0 != *attestation_info.key {
+    msg!("Invalid attestation {} for {}", attestation_info.key, wallet);
+    return Err(TokensellError::InvalidAttestation.into());
+  }
+
+  if *attestation_info.owner != *program_id {
+    return Err(TokensellError::AttestationRequired.into());
+  }
+
+  let attestation = Attestation::from_account_info(attestation_info)?;
+
+  if attestation.revoked {
+    return Err(TokensellError::AttestationRevoked.into());
+  }
+
+  if now > attestation.expires_at {
+    msg!("Attestation expired at {}", attestation.expires_at);
+    return Err(TokensellError::AttestationExpired.into());
+  }
+
+  Ok(())
+}
@@ -0,0 +1,207 @@
+use solana_program::{
+  account_info::AccountInfo,
+  entrypoint::ProgramResult,
+  msg,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+};
+
+use crate::{
+  error::TokensellError,
+  state::{
+    Directory,
+    DirectoryEntry,
+    DirectoryPage,
+    Key,
+    SaleCounter,
+    SaleStatus,
+    COUNTER_SEED,
+    DIRECTORY_PAGE_CAPACITY,
+    DIRECTORY_PAGE_SIZE,
+    DIRECTORY_SEED,
+    DIRECTORY_SIZE,
+    PREFIX,
+    SALE_COUNTER_SIZE,
+  },
+  utils::create_or_allocate_account_raw,
+};
+
+pub struct RegisterSale<'a> {
+  pub payer: AccountInfo<'a>,
+  /// `SaleCounter` of the sale owner
+  pub counter: AccountInfo<'a>,
+  pub directory: AccountInfo<'a>,
+  /// page the sale is appended to
+  pub page: AccountInfo<'a>,
+  pub rent: AccountInfo<'a>,
+  pub system_program: AccountInfo<'a>,
+}
+
+/// Page of the directory holding the sale listed at `index`.
+pub fn directory_page_of(index: u64) -> u64 {
+  index / DIRECTORY_PAGE_CAPACITY as u64
+}
+
+/// Counts a new sale among its owner's sales and appends `entry` to the directory, creating
+/// the counter, the directory and the page on first use. Returns the index of the entry.
+///
+/// Sale ids are handed out in order, `sale_id` has to be the number of sales the owner
+/// created so far.
+pub fn register_sale(program_id: &Pubkey, accounts: RegisterSale, sale_id: u64, entry: DirectoryEntry) -> Result<u64, ProgramError> {
+  let RegisterSale {
+    payer,
+    counter,
+    directory,
+    page,
+    rent,
+    system_program,
+  } = accounts;
+
+  let created = open_account(program_id, &counter, &payer, &rent, &system_program, SALE_COUNTER_SIZE, &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    entry.owner.as_ref(),
+    COUNTER_SEED.as_bytes(),
+  ])?;
+
+  let mut sale_counter = if created {
+    SaleCounter {
+      key: Key::SaleCounter,
+      owner: entry.owner,
+      sales: 0,
+    }
+  } else {
+    SaleCounter::from_account_info(&counter)?
+  };
+
+  if sale_counter.sales != sale_id {
+    msg!("Next sale id is {}", sale_counter.sales);
+    return Err(TokensellError::InvalidSaleId.into());
+  }
+
+  sale_counter.sales = sale_counter.sales.checked_add(1).ok_or(TokensellError::Overflow)?;
+  sale_counter.save(&counter)?;
+
+  let created = open_account(program_id, &directory, &payer, &rent, &system_program, DIRECTORY_SIZE, &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    DIRECTORY_SEED.as_bytes(),
+  ])?;
+
+  let mut sale_directory = if created {
+    Directory {
+      key: Key::Directory,
+      sales: 0,
+    }
+  } else {
+    Directory::from_account_info(&directory)?
+  };
+
+  let index = sale_directory.sales;
+  let page_index = directory_page_of(index);
+
+  let created = open_account(program_id, &page, &payer, &rent, &system_program, DIRECTORY_PAGE_SIZE, &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    DIRECTORY_SEED.as_bytes(),
+    &page_index.to_le_bytes(),
+  ])?;
+
+  let mut directory_page = if created {
+    DirectoryPage {
+      key: Key::DirectoryPage,
+      page: page_index,
+      entries: vec![],
+    }
+  } else {
+    DirectoryPage::from_account_info(&page)?
+  };
+
+  directory_page.entries.push(entry);
+  directory_page.save(&page)?;
+
+  sale_directory.sales = index.checked_add(1).ok_or(TokensellError::Overflow)?;
+  sale_directory.save(&directory)?;
+
+  msg!("Listed sale {} at {}", sale_id, index);
+
+  Ok(index)
+}
+
+/// Updates the status of the sale listed at `index`, `page_info` being the page holding it.
+pub fn set_sale_status(
+  program_id: &Pubkey,
+  page_info: &AccountInfo,
+  sell: &Pubkey,
+  index: u64,
+  status: SaleStatus,
+) -> ProgramResult {
+  let (page_key, _bump) = Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    DIRECTORY_SEED.as_bytes(),
+    &directory_page_of(index).to_le_bytes(),
+  ], program_id);
+
+  if page_key != *page_info.key {
+    msg!("Invalid directory page {}", page_key);
+    return Err(TokensellError::InvalidDirectoryAccount.into());
+  }
+
+  if *page_info.owner != *program_id {
+    return Err(TokensellError::NotDirectoryAccount.into());
+  }
+
+  let mut page = DirectoryPage::from_account_info(page_info)?;
+  let entry = page.entries
+    .get_mut(index as usize % DIRECTORY_PAGE_CAPACITY)
+    .filter(|entry| entry.sell == *sell)
+    .ok_or(TokensellError::SaleNotListed)?;
+
+  entry.status = status;
+
+  page.save(page_info)
+}
+
+/// Checks `account_info` is the PDA of `seeds` and creates it with `size` bytes when empty.
+/// Returns whether the account was created.
+fn open_account<'a>(
+  program_id: &Pubkey,
+  account_info: &AccountInfo<'a>,
+  payer_info: &AccountInfo<'a>,
+  rent_sysvar_info: &AccountInfo<'a>,
+  system_program_info: &AccountInfo<'a>,
+  size: usize,
+  seeds: &[&[u8]],
+) -> Result<bool, ProgramError> {
+  let (key, bump) = Pubkey::find_program_address(seeds, program_id);
+
+  if key != *account_info.key {
+    msg!("Invalid account {}, expected {}", account_info.key, key);
+    return Err(TokensellError::InvalidDirectoryAccount.into());
+  }
+
+  if !account_info.data_is_empty() {
+    if *account_info.owner != *program_id {
+      return Err(TokensellError::NotDirectoryAccount.into());
+    }
+
+    return Ok(false);
+  }
+
+  let bump = [bump];
+  let mut signer_seeds = seeds.to_vec();
+  signer_seeds.push(&bump);
+
+  create_or_allocate_account_raw(
+    *program_id,
+    account_info,
+    rent_sysvar_info,
+    system_program_info,
+    payer_info,
+    size,
+    &signer_seeds,
+  )?;
+
+  Ok(true)
+}
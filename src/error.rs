@@ -1,5 +1,10 @@
-use solana_program::{program_error::ProgramError};
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use thiserror::Error;
 
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
@@ -7,26 +12,27 @@ pub enum TokensellError {
     #[error("Unknown instruction")]
     UnknownInstruction,
 
-    #[error("Failed to unpack instruction data")]
+    /// No longer returned, token program errors are passed through.
+    #[error("Token transfer failed")]
     TokenTransferFailed,
 
     /// Lamport balance below rent-exempt threshold.
     #[error("Lamport balance below rent-exempt threshold")]
     NotRentExempt,
 
-    #[error("Invalid USDT target account")]
+    #[error("Payment destination is not the sale's target account")]
     InvalidUSDTTargetAccount,
 
     #[error("Invalid account")]
     InvalidAccount,
 
-    #[error("Invalid account owner")]
+    #[error("Token account or mint is not owned by a token program")]
     InvalidOwner,
 
     #[error("No tokens in vault")]
     NoTokensInVault,
 
-    #[error("Token account mint mismatch")]
+    #[error("Token account holds a different mint than expected")]
     WrongMint,
 
     #[error("Integer overflow")]
@@ -54,7 +60,250 @@ pub enum TokensellError {
     MinimalDeposit,
 
     #[error("Account size mismatch")]
-    SizeMismatch
+    SizeMismatch,
+
+    #[error("Price numerator and denominator cant be zero")]
+    InvalidPrice,
+
+    #[error("Account uses an older layout and has to be migrated")]
+    MigrationRequired,
+
+    #[error("Participant still has tokens to claim")]
+    UnclaimedTokens,
+
+    #[error("Participant account is closed")]
+    ParticipantClosed,
+
+    #[error("Positions of this sale cant be transferred")]
+    TransfersDisabled,
+
+    #[error("Participant has no position")]
+    EmptyPosition,
+
+    #[error("Source token account holds a different mint than the sale")]
+    SourceMintMismatch,
+
+    #[error("Destination token account holds a different mint than the sale")]
+    DestinationMintMismatch,
+
+    #[error("Sale id has to be the owner's next sale id")]
+    InvalidSaleId,
+
+    #[error("Price feed account is not a Pyth price account of this sale")]
+    InvalidPriceFeed,
+
+    #[error("Oracle price is stale or not trading")]
+    StalePrice,
+
+    #[error("Oracle price confidence interval is too wide")]
+    PriceUncertain,
+
+    #[error("Sale account is not owned by the tokensell program")]
+    SellNotOwnedByProgram,
+
+    #[error("Participant account is not owned by the tokensell program")]
+    ParticipantNotOwnedByProgram,
+
+    #[error("Account is not a sale")]
+    NotSellAccount,
+
+    #[error("Account is not a participant")]
+    NotParticipantAccount,
+
+    #[error("Signer is not the owner of the sale")]
+    NotSellOwner,
+
+    #[error("Sale account is not the address of the owner and sale id")]
+    InvalidSellAddress,
+
+    #[error("Sell authority is not the authority of the sale")]
+    InvalidSellAuthority,
+
+    #[error("Vault is not the vault of the sale")]
+    InvalidVault,
+
+    #[error("Vault is not owned by the sell authority")]
+    InvalidVaultOwner,
+
+    #[error("Participant account is not the address of the sale and wallet")]
+    InvalidParticipantAddress,
+
+    #[error("Token program is not the program owning the mint")]
+    InvalidTokenProgram,
+
+    #[error("Associated token program account is not the associated token program")]
+    InvalidAssociatedTokenProgram,
+
+    #[error("Destination is not the participant's associated token account")]
+    NotAssociatedTokenAccount,
+
+    #[error("Destination token account is not owned by the participant")]
+    InvalidDestinationOwner,
+
+    #[error("Remaining accounts have to be participant and destination pairs")]
+    InvalidDistributionAccounts,
+
+    #[error("Position cant be transferred to the wallet holding it")]
+    TransferToSelf,
+
+    #[error("Account is not owned by the tokensell program")]
+    NotProgramAccount,
+
+    #[error("Account type has no layout to migrate to")]
+    NotMigratable,
+
+    #[error("Sale counter, directory or directory page is not at its address")]
+    InvalidDirectoryAccount,
+
+    #[error("Account is not a sale counter, directory or directory page")]
+    NotDirectoryAccount,
+
+    #[error("Sale is not listed at its index of the directory page")]
+    SaleNotListed,
+
+    #[error("Source mint is not the mint the sale is paid with")]
+    InvalidSourceMint,
+
+    #[error("Target mint is not the mint the sale sells")]
+    InvalidTargetMint,
+
+    #[error("Not enough tokens left in the sale")]
+    SoldOut,
+
+    #[error("Escrow is not the refund escrow of the sale")]
+    InvalidEscrow,
+
+    #[error("Sale doesnt allow refunds")]
+    RefundsDisabled,
+
+    #[error("Refund window of the position is closed")]
+    RefundWindowClosed,
+
+    #[error("Tokens were already claimed from the position")]
+    PositionClaimed,
+
+    #[error("Escrow cant be released while refunds are possible")]
+    EscrowLocked,
+
+    #[error("Sale requires an attestation of the wallet")]
+    AttestationRequired,
+
+    #[error("Attestation is not the address of the compliance authority and wallet")]
+    InvalidAttestation,
+
+    #[error("Account is not an attestation")]
+    NotAttestationAccount,
+
+    #[error("Attestation was revoked")]
+    AttestationRevoked,
+
+    #[error("Attestation has expired")]
+    AttestationExpired,
+
+    #[error("Voucher signature instruction required")]
+    VoucherRequired,
+
+    #[error("Invalid voucher")]
+    InvalidVoucher,
+
+    #[error("Voucher has expired")]
+    VoucherExpired,
+
+    #[error("Deposit exceeds the voucher amount")]
+    VoucherExceeded,
+
+    #[error("Invalid voucher receipt")]
+    InvalidVoucherReceipt,
+
+    #[error("Invalid staking tiers")]
+    InvalidStakingTiers,
+
+    #[error("Invalid stake account")]
+    InvalidStakeAccount,
+
+    #[error("Invalid stake vault")]
+    InvalidStakeVault,
+
+    #[error("Lock has to end in the future")]
+    InvalidLockPeriod,
+
+    #[error("Stake is still locked")]
+    StakeLocked,
+
+    #[error("Unstake exceeds the staked amount")]
+    InsufficientStake,
+
+    #[error("Stake doesn't reach the first tier")]
+    NoStakingTier,
+
+    #[error("Deposit exceeds the cap of the staking tier")]
+    TierCapExceeded,
+
+    #[error("Position is not revocable")]
+    NotRevocable,
+
+    #[error("Position was revoked")]
+    PositionRevoked,
+
+    #[error("Revocable positions can't be transferred")]
+    RevocablePosition,
+
+    #[error("Allocation and position differ in revocability")]
+    RevocableMismatch,
+
+    #[error("Multisig needs distinct signers and a threshold they can reach")]
+    InvalidMultisig,
+
+    #[error("Not a multisig account")]
+    NotMultisigAccount,
+
+    #[error("Signer is not part of the multisig")]
+    NotMultisigSigner,
+
+    #[error("Invalid proposal account")]
+    InvalidProposal,
+
+    #[error("Proposal was already approved by this signer")]
+    AlreadyApproved,
+
+    #[error("Proposal was already executed")]
+    ProposalExecuted,
+
+    #[error("Proposal doesn't have enough approvals")]
+    ThresholdNotReached,
+
+    #[error("Accounts don't match the proposed instruction")]
+    InvalidProposalAccounts,
+
+    #[error("TGE policy bounds are inverted or it has no delay")]
+    InvalidTgePolicy,
+
+    #[error("TGE passed and can't change anymore")]
+    TgeLocked,
+
+    #[error("TGE is outside the bounds of the sale's TGE policy")]
+    TgeOutOfBounds,
+
+    #[error("No TGE change is queued")]
+    NoPendingTge,
+
+    #[error("Queued TGE change is still timelocked")]
+    TgeTimelocked,
+
+    #[error("Claimed amount exceeds the position")]
+    InvalidClaimedAmount,
+
+    #[error("Associated token account has to exist for Token-2022 mints")]
+    AssociatedTokenAccountRequired,
+
+    #[error("Refund window doesn't fit in a timestamp")]
+    InvalidRefundPolicy,
+
+    #[error("Too many deposits are still refundable")]
+    TooManyRefundableDeposits,
+
+    #[error("Revocable positions take no deposits")]
+    RevocableDeposit,
 }
 
 impl From<TokensellError> for ProgramError {
@@ -62,3 +311,18 @@ impl From<TokensellError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl<T> DecodeError<T> for TokensellError {
+    fn type_of() -> &'static str {
+        "TokensellError"
+    }
+}
+
+impl PrintProgramError for TokensellError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("Error: {}", self);
+    }
+}
@@ -0,0 +1,145 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, ParticipantData, SellData, PARTICIPANT_SIZE, PREFIX},
+  error::{TokensellError},
+  utils::create_or_allocate_account_raw,
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct AddAllocationArgs {
+  pub instruction: u8,
+  pub amount: u64,
+  /// Whether the position is revocable, top-ups have to match the existing position.
+  pub revocable: bool,
+}
+
+/// Grants `amount` target tokens of the sale to `wallet` without payment, creating its
+/// position at the payer's expense or topping it up. Grants vest like bought positions and
+/// count against what is left of the sale.
+///
+/// Only the sale owner grants, the payer may be a different wallet. Revocable grants are
+/// signed by `wallet` too, like `SetRevocable`.
+pub fn process_add_allocation(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = AddAllocationArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let payer_info = next_account_info(account_info_iter)?;
+  let sell_owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let wallet_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !payer_info.is_signer || !sell_owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if args.revocable && !wallet_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  let mut sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  if sell.owner != *sell_owner_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  let (participant_key, bump) = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      wallet_info.key.as_ref()
+    ],
+    program_id
+  );
+
+  if participant_key != *participant_info.key {
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  if args.amount == 0 {
+    return Err(TokensellError::EmptyPosition.into());
+  }
+
+  msg!("Allocate {} to {}", args.amount, wallet_info.key);
+
+  sell.amount_left = sell.amount_left
+    .checked_sub(args.amount)
+    .ok_or(TokensellError::SoldOut)?;
+  sell.save(sell_info)?;
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  if participant_info.data_is_empty() {
+    create_or_allocate_account_raw(
+      *program_id,
+      participant_info,
+      rent_sysvar_info,
+      system_program_info,
+      payer_info,
+      PARTICIPANT_SIZE,
+      &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        sell_info.key.as_ref(),
+        wallet_info.key.as_ref(),
+        &[bump]
+      ]
+    )?;
+
+    ParticipantData {
+      key: Key::ParticipantDataV2,
+      owner: *wallet_info.key,
+      sell: *sell_info.key,
+      amount: args.amount,
+      claimed: 0,
+      paid: 0,
+      last_deposit_at: clock.unix_timestamp,
+      revocable: args.revocable,
+      revoked_at: None,
+      refundable: vec![],
+    }.save(participant_info)
+  } else {
+    if *participant_info.owner != *program_id {
+      return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+    }
+
+    let mut participant = ParticipantData::from_account_info(participant_info)?;
+
+    if participant.revoked_at.is_some() {
+      return Err(TokensellError::PositionRevoked.into());
+    }
+
+    if participant.revocable != args.revocable {
+      return Err(TokensellError::RevocableMismatch.into());
+    }
+
+    participant.amount = participant.amount
+      .checked_add(args.amount)
+      .ok_or(TokensellError::Overflow)?;
+
+    participant.save(participant_info)
+  }
+}
@@ -0,0 +1,64 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+  state::{Key, SaleStatus, SellData},
+  error::{TokensellError},
+  directory::set_sale_status,
+};
+
+/// Makes the TGE change queued by `SetTge` once its delay is over. Anyone can apply it, the
+/// change was public since it was queued. A queued TGE that passed in the meantime can't be
+/// applied anymore.
+///
+/// Sales listed in the directory pass the directory page holding them after the clock.
+pub fn process_apply_tge(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let sell_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  let mut sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  let pending = sell.pending_tge.take().ok_or(TokensellError::NoPendingTge)?;
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  if clock.unix_timestamp < pending.eta {
+    msg!("Timelocked until {}", pending.eta);
+    return Err(TokensellError::TgeTimelocked.into());
+  }
+
+  if sell.tge.is_some_and(|tge| clock.unix_timestamp > tge) {
+    return Err(TokensellError::TgeLocked.into());
+  }
+
+  if pending.tge <= clock.unix_timestamp {
+    msg!("Tge {} already passed", pending.tge);
+    return Err(TokensellError::TgeOutOfBounds.into());
+  }
+
+  msg!("Set tge {}", pending.tge);
+
+  sell.tge = Some(pending.tge);
+
+  if let Some(index) = sell.directory_index {
+    let directory_page_info = next_account_info(account_info_iter)?;
+
+    set_sale_status(program_id, directory_page_info, sell_info.key, index, SaleStatus::TgeSet)?;
+  }
+
+  sell.save(sell_info)
+}
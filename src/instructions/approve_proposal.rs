@@ -0,0 +1,40 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use crate::{
+  error::{TokensellError},
+  multisig::load_open_proposal,
+};
+
+/// Adds the approval of one of the multisig's signers to an open proposal.
+pub fn process_approve_proposal(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let signer_info = next_account_info(account_info_iter)?;
+  let multisig_info = next_account_info(account_info_iter)?;
+  let proposal_info = next_account_info(account_info_iter)?;
+
+  if !signer_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  let (multisig, mut proposal) = load_open_proposal(program_id, multisig_info, proposal_info)?;
+
+  let signer_index = multisig
+    .signer_index(signer_info.key)
+    .ok_or(TokensellError::NotMultisigSigner)?;
+
+  if proposal.approvals & (1 << signer_index) != 0 {
+    return Err(TokensellError::AlreadyApproved.into());
+  }
+
+  proposal.approvals |= 1 << signer_index;
+
+  msg!("Approvals {} of {}", proposal.approval_count(), multisig.threshold);
+
+  proposal.save(proposal_info)
+}
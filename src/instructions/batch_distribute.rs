@@ -0,0 +1,120 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+  state::{ParticipantData, PREFIX},
+  error::{TokensellError},
+  instructions::claim::{assert_claim_attested, load_claimable_sell, transfer_from_vault, unclaimed_amount},
+  utils::unpack_token_account,
+};
+
+/// Pushes vested tokens to many participants at once, on behalf of the sale owner.
+///
+/// Remaining accounts are `(participant, destination)` pairs, where the destination is a
+/// target mint token account owned by the participant's wallet. Participants with nothing
+/// left to claim are skipped. Sales gating claims take `(participant, destination,
+/// attestation)` triples instead.
+pub fn process_batch_distribute(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let target_mint_info = next_account_info(account_info_iter)?;
+  let sell_authority_info = next_account_info(account_info_iter)?;
+  let sell_vault_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  let (sell, sell_bump) = load_claimable_sell(
+    program_id,
+    sell_info,
+    target_mint_info,
+    sell_authority_info,
+    sell_vault_info,
+    token_program_info,
+  )?;
+
+  if sell.owner != *owner_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  let gated = sell.compliance.as_ref().is_some_and(|gate| gate.gate_claims);
+  let pairs = account_info_iter.as_slice().chunks_exact(if gated { 3 } else { 2 });
+
+  if pairs.len() == 0 || !pairs.remainder().is_empty() {
+    msg!("Expected participant and destination pairs");
+    return Err(TokensellError::InvalidDistributionAccounts.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  for pair in pairs {
+    let participant_info = &pair[0];
+    let destination_info = &pair[1];
+
+    if *participant_info.owner != *program_id {
+      return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+    }
+
+    let mut participant = ParticipantData::from_account_info(participant_info)?;
+
+    let participant_key = Pubkey::find_program_address(
+      &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        sell_info.key.as_ref(),
+        participant.owner.as_ref()
+      ],
+      program_id
+    ).0;
+
+    if participant_key != *participant_info.key {
+      return Err(TokensellError::InvalidParticipantAddress.into());
+    }
+
+    let destination = unpack_token_account(destination_info)?;
+
+    if destination.owner != participant.owner {
+      msg!("Destination {} is not owned by {}", destination_info.key, participant.owner);
+      return Err(TokensellError::InvalidDestinationOwner.into());
+    }
+
+    assert_claim_attested(program_id, &sell, pair.get(2), &participant.owner, clock.unix_timestamp)?;
+
+    let amount = unclaimed_amount(&sell, &participant, clock.unix_timestamp)?;
+
+    if amount == 0 {
+      continue;
+    }
+
+    participant.claimed = participant.claimed
+      .checked_add(amount)
+      .ok_or(TokensellError::Overflow)?;
+
+    participant.save(participant_info)?;
+
+    transfer_from_vault(
+      program_id,
+      sell_info,
+      sell_vault_info,
+      destination_info,
+      target_mint_info,
+      sell_authority_info,
+      token_program_info,
+      sell_bump,
+      amount,
+      sell.target_decimals,
+    )?;
+  }
+
+  Ok(())
+}
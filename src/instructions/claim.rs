@@ -3,16 +3,11 @@ use solana_program::{
   pubkey::Pubkey,
   account_info::{next_account_info, AccountInfo},
   msg,
-  program_pack::Pack,
+  clock::UnixTimestamp,
+  program_error::ProgramError,
   sysvar::{clock::Clock, Sysvar},
 };
 
-use spl_token::{
-  state::{Mint}
-};
-
-use borsh::{BorshDeserialize};
-
 use crate::{
   state::{
     Key,
@@ -21,14 +16,16 @@ use crate::{
     PREFIX,
   },
   error::{TokensellError},
+  compliance::assert_attested,
   utils::{
+    assert_token_program,
     spl_token_transfer,
     TokenTransferParams,
-  }
+  },
+  vesting::vested_amount,
 };
 
-static MONTH_SECONDS: u64 = 30 * 24 * 3600;
-
+/// Sales gating claims take the owner's attestation after the clock.
 pub fn process_claim(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
   let account_info_iter = &mut accounts.iter();
 
@@ -46,90 +43,166 @@ pub fn process_claim(program_id: &Pubkey, accounts: &[AccountInfo], _instruction
     return Err(TokensellError::SignatureRequired.into());
   }
 
-  if *sell_info.owner != *program_id {
-    msg!("Invalid sell account owner");
-    return Err(TokensellError::InvalidOwner.into());
-  }
-  
-  let sell = SellData::from_account_info(sell_info)?;
+  claim_vested(program_id, ClaimParams {
+    owner: payer_info.key,
+    sell: sell_info,
+    target_mint: target_mint_info,
+    sell_authority: sell_authority_info,
+    sell_vault: sell_vault_info,
+    token_program: token_program_info,
+    participant: participant_info,
+    target_token: target_token_info,
+    clock_sysvar: clock_sysvar_info,
+    attestation: account_info_iter.next(),
+  })
+}
 
-  if sell.key != Key::SellData {
-    return Err(TokensellError::InvalidAccount.into());
-  }
+pub struct ClaimParams<'a: 'b, 'b> {
+  /// wallet the participant account belongs to
+  pub owner: &'b Pubkey,
+  pub sell: &'b AccountInfo<'a>,
+  pub target_mint: &'b AccountInfo<'a>,
+  pub sell_authority: &'b AccountInfo<'a>,
+  pub sell_vault: &'b AccountInfo<'a>,
+  pub token_program: &'b AccountInfo<'a>,
+  pub participant: &'b AccountInfo<'a>,
+  /// token account receiving the unlocked tokens
+  pub target_token: &'b AccountInfo<'a>,
+  pub clock_sysvar: &'b AccountInfo<'a>,
+  /// attestation of the owner, required by sales gating claims
+  pub attestation: Option<&'b AccountInfo<'a>>,
+}
 
-  if sell.tge.is_none() || sell.source_vault.is_none() || sell.target_mint.is_none() {
-    return Err(TokensellError::NothingToClaim.into());
-  }
+/// Transfers everything vested and not yet claimed by `owner` to `target_token`.
+pub fn claim_vested(program_id: &Pubkey, params: ClaimParams<'_, '_>) -> ProgramResult {
+  let ClaimParams {
+    owner,
+    sell: sell_info,
+    target_mint: target_mint_info,
+    sell_authority: sell_authority_info,
+    sell_vault: sell_vault_info,
+    token_program: token_program_info,
+    participant: participant_info,
+    target_token: target_token_info,
+    clock_sysvar: clock_sysvar_info,
+    attestation: attestation_info,
+  } = params;
 
-  if *target_mint_info.key != sell.target_mint.unwrap() {
-    return Err(TokensellError::InvalidAccount.into());
-  }
+  let (sell, sell_bump) = load_claimable_sell(
+    program_id,
+    sell_info,
+    target_mint_info,
+    sell_authority_info,
+    sell_vault_info,
+    token_program_info,
+  )?;
 
   let participant_key = Pubkey::find_program_address(
     &[
       PREFIX.as_bytes(),
       program_id.as_ref(),
       sell_info.key.as_ref(),
-      payer_info.key.as_ref()
+      owner.as_ref()
     ],
     program_id
   ).0;
 
   if participant_key != *participant_info.key {
-    return Err(TokensellError::InvalidAccount.into());
+    return Err(TokensellError::InvalidParticipantAddress.into());
   }
 
-  if sell.source_vault.unwrap() != *sell_vault_info.key {
-    return Err(TokensellError::InvalidAccount.into());
+  let mut participant = ParticipantData::from_account_info(participant_info)?;
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  assert_claim_attested(program_id, &sell, attestation_info, owner, clock.unix_timestamp)?;
+
+  let amount = unclaimed_amount(&sell, &participant, clock.unix_timestamp)?;
+
+  if amount == 0 {
+    return Err(TokensellError::NothingToClaim.into());
   }
-  
-  let target_mint = Mint::unpack(&target_mint_info.data.borrow_mut())?;
-  let participant = ParticipantData::try_from_slice(&participant_info.data.borrow_mut())?;
-  let clock = Clock::from_account_info(&clock_sysvar_info)?;
 
-  let amount;
-  if clock.unix_timestamp <= sell.tge.unwrap() {
-    amount = 0;
-  } else {
-    let months_passed = (clock.unix_timestamp - sell.tge.unwrap()) as u64 / MONTH_SECONDS;
+  participant.claimed = participant.claimed
+    .checked_add(amount)
+    .ok_or(TokensellError::Overflow)?;
 
-    msg!("Claim {} {}", months_passed, sell.total_months);
+  participant.save(participant_info)?;
 
-    let total_amount = participant.amount;
-    let to_claim = total_amount - participant.claimed;
+  transfer_from_vault(
+    program_id,
+    sell_info,
+    sell_vault_info,
+    target_token_info,
+    target_mint_info,
+    sell_authority_info,
+    token_program_info,
+    sell_bump,
+    amount,
+    sell.target_decimals,
+  )
+}
 
-    if months_passed < sell.total_months {
-      let initial_amount = total_amount * sell.initial_perc / 100;
-      let month_amount = (total_amount - initial_amount) * months_passed / sell.total_months;
-      let freezed_amount = (total_amount - initial_amount - month_amount) * 10u64.pow(target_mint.decimals.into());
-  
-      msg!(
-        "Claim2 {}, {}, {}, {}",
-        total_amount,
-        initial_amount,
-        month_amount,
-        freezed_amount
-      );
+/// Loads a sale that has a TGE, checking the accounts claims transfer from.
+/// Returns the sale and the bump of its sell authority.
+pub fn load_claimable_sell(
+  program_id: &Pubkey,
+  sell_info: &AccountInfo,
+  target_mint_info: &AccountInfo,
+  sell_authority_info: &AccountInfo,
+  sell_vault_info: &AccountInfo,
+  token_program_info: &AccountInfo,
+) -> Result<(SellData, u8), ProgramError> {
+  let (sell, sell_bump) = load_vault_sell(
+    program_id,
+    sell_info,
+    target_mint_info,
+    sell_authority_info,
+    sell_vault_info,
+    token_program_info,
+  )?;
+
+  if sell.tge.is_none() {
+    return Err(TokensellError::NothingToClaim.into());
+  }
+
+  Ok((sell, sell_bump))
+}
+
+/// Loads a sale that has a vault, checking the accounts transfers from the vault use.
+/// Returns the sale and the bump of its sell authority.
+pub fn load_vault_sell(
+  program_id: &Pubkey,
+  sell_info: &AccountInfo,
+  target_mint_info: &AccountInfo,
+  sell_authority_info: &AccountInfo,
+  sell_vault_info: &AccountInfo,
+  token_program_info: &AccountInfo,
+) -> Result<(SellData, u8), ProgramError> {
+  if *sell_info.owner != *program_id {
+    msg!("Invalid sell account owner");
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
   
-      if to_claim > freezed_amount {
-        amount = to_claim - freezed_amount;
-      } else {
-        amount = 0;
-      }
-    } else {
-      amount = to_claim;
-    }
-    
-    msg!(
-      "Claim3 {}",
-      amount
-    );
+  let sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
   }
 
-  if amount == 0 {
+  if sell.source_vault.is_none() || sell.target_mint.is_none() {
     return Err(TokensellError::NothingToClaim.into());
   }
 
+  if *target_mint_info.key != sell.target_mint.unwrap() {
+    return Err(TokensellError::InvalidTargetMint.into());
+  }
+
+  assert_token_program(token_program_info, target_mint_info)?;
+
+  if sell.source_vault.unwrap() != *sell_vault_info.key {
+    return Err(TokensellError::InvalidVault.into());
+  }
+
   let (sell_authority, sell_bump) = Pubkey::find_program_address(&[
     PREFIX.as_bytes(),
     program_id.as_ref(),
@@ -137,13 +210,64 @@ pub fn process_claim(program_id: &Pubkey, accounts: &[AccountInfo], _instruction
   ], program_id);
 
   if sell_authority != *sell_authority_info.key {
-    return Err(TokensellError::InvalidAccount.into());
+    return Err(TokensellError::InvalidSellAuthority.into());
+  }
+
+  Ok((sell, sell_bump))
+}
+
+/// Checks the attestation of `owner` when the sale's compliance gate covers claims.
+pub fn assert_claim_attested(
+  program_id: &Pubkey,
+  sell: &SellData,
+  attestation_info: Option<&AccountInfo>,
+  owner: &Pubkey,
+  now: UnixTimestamp,
+) -> ProgramResult {
+  match &sell.compliance {
+    Some(gate) if gate.gate_claims => assert_attested(program_id, gate, attestation_info, owner, now),
+    _ => Ok(()),
   }
+}
+
+/// Vested amount of `participant` at `now` that hasn't been claimed yet. Revoked positions
+/// stopped vesting with everything they kept vested.
+pub fn unclaimed_amount(sell: &SellData, participant: &ParticipantData, now: UnixTimestamp) -> Result<u64, ProgramError> {
+  let tge = sell.tge.ok_or(TokensellError::NothingToClaim)?;
 
+  let vested = if participant.revoked_at.is_some() {
+    participant.amount
+  } else {
+    vested_amount(participant.amount, sell.initial_perc, sell.total_months, tge, now)?
+  };
+
+  let amount = vested.saturating_sub(participant.claimed);
+
+  msg!("Claim {} of {} vested", amount, vested);
+
+  Ok(amount)
+}
+
+/// Moves `amount` from the vault, the destination receives it net of any transfer fee.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_from_vault<'a>(
+  program_id: &Pubkey,
+  sell_info: &AccountInfo<'a>,
+  sell_vault_info: &AccountInfo<'a>,
+  destination_info: &AccountInfo<'a>,
+  target_mint_info: &AccountInfo<'a>,
+  sell_authority_info: &AccountInfo<'a>,
+  token_program_info: &AccountInfo<'a>,
+  sell_bump: u8,
+  amount: u64,
+  decimals: u8,
+) -> ProgramResult {
   spl_token_transfer(TokenTransferParams {
     source: sell_vault_info.clone(),
-    destination: target_token_info.clone(),
-    amount: amount,
+    destination: destination_info.clone(),
+    mint: target_mint_info.clone(),
+    amount,
+    decimals,
     authority: sell_authority_info.clone(),
     authority_signer_seeds: &[
       PREFIX.as_bytes(),
@@ -152,7 +276,5 @@ pub fn process_claim(program_id: &Pubkey, accounts: &[AccountInfo], _instruction
       &[sell_bump]
     ],
     token_program: token_program_info.clone(),
-  })?;
-  
-  Ok(())
+  })
 }
@@ -0,0 +1,92 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program::invoke,
+};
+
+use spl_associated_token_account::create_associated_token_account;
+
+use crate::{
+  error::{TokensellError},
+  instructions::claim::{claim_vested, ClaimParams},
+  utils::{assert_token_program, associated_token_address},
+};
+
+/// Claims on behalf of a participant, into their associated token account.
+///
+/// Anyone can submit it. The cranker pays the rent of the associated token account
+/// when it doesn't exist yet, for classic mints only, Token-2022 associated token accounts
+/// have to exist already. Sales gating claims take the owner's attestation last.
+pub fn process_claim_for(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let cranker_info = next_account_info(account_info_iter)?;
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let target_mint_info = next_account_info(account_info_iter)?;
+  let sell_authority_info = next_account_info(account_info_iter)?;
+  let sell_vault_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+  let target_token_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+  let associated_token_program_info = next_account_info(account_info_iter)?;
+
+  if !cranker_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  assert_token_program(token_program_info, target_mint_info)?;
+
+  if *associated_token_program_info.key != spl_associated_token_account::id() {
+    msg!("Invalid associated token program");
+    return Err(TokensellError::InvalidAssociatedTokenProgram.into());
+  }
+
+  if *target_token_info.key != associated_token_address(owner_info.key, target_mint_info.key, token_program_info.key) {
+    msg!("Target token account is not the participant's associated token account");
+    return Err(TokensellError::NotAssociatedTokenAccount.into());
+  }
+
+  if target_token_info.data_is_empty() {
+    // the associated token program only initializes classic token accounts
+    if *token_program_info.key != spl_token::id() {
+      return Err(TokensellError::AssociatedTokenAccountRequired.into());
+    }
+
+    msg!("Create associated token account");
+
+    let instruction = create_associated_token_account(cranker_info.key, owner_info.key, target_mint_info.key);
+
+    invoke(
+      &instruction,
+      &[
+        cranker_info.clone(),
+        target_token_info.clone(),
+        owner_info.clone(),
+        target_mint_info.clone(),
+        system_program_info.clone(),
+        token_program_info.clone(),
+        rent_sysvar_info.clone(),
+        associated_token_program_info.clone(),
+      ],
+    )?;
+  }
+
+  claim_vested(program_id, ClaimParams {
+    owner: owner_info.key,
+    sell: sell_info,
+    target_mint: target_mint_info,
+    sell_authority: sell_authority_info,
+    sell_vault: sell_vault_info,
+    token_program: token_program_info,
+    participant: participant_info,
+    target_token: target_token_info,
+    clock_sysvar: clock_sysvar_info,
+    attestation: account_info_iter.next(),
+  })
+}
@@ -0,0 +1,80 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{rent::Rent, Sysvar},
+};
+
+use crate::{
+  state::{
+    Key,
+    ParticipantData,
+    CLOSED_PARTICIPANT_SIZE,
+    PREFIX,
+  },
+  error::{TokensellError},
+};
+
+/// Returns the rent of a fully claimed participant account to its owner.
+///
+/// The account shrinks to a `ClosedParticipant` tombstone that keeps just enough lamports
+/// to stay rent exempt, so the same owner can't join the sale again.
+pub fn process_close_participant(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *participant_info.owner != *program_id {
+    return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+  }
+
+  let participant_key = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      owner_info.key.as_ref()
+    ],
+    program_id
+  ).0;
+
+  if participant_key != *participant_info.key {
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  let participant = ParticipantData::from_account_info(participant_info)?;
+
+  if participant.key != Key::ParticipantDataV2 || participant.owner != *owner_info.key {
+    return Err(TokensellError::NotParticipantAccount.into());
+  }
+
+  if participant.claimed < participant.amount {
+    return Err(TokensellError::UnclaimedTokens.into());
+  }
+
+  let rent = &Rent::from_account_info(rent_sysvar_info)?;
+  let refund = participant_info
+    .lamports()
+    .saturating_sub(rent.minimum_balance(CLOSED_PARTICIPANT_SIZE));
+
+  msg!("Close participant, refund {} lamports", refund);
+
+  **participant_info.lamports.borrow_mut() -= refund;
+  **owner_info.lamports.borrow_mut() = owner_info
+    .lamports()
+    .checked_add(refund)
+    .ok_or(TokensellError::Overflow)?;
+
+  participant_info.realloc(CLOSED_PARTICIPANT_SIZE, false)?;
+  participant_info.data.borrow_mut()[0] = Key::ClosedParticipant as u8;
+
+  Ok(())
+}
@@ -0,0 +1,80 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, Multisig, MULTISIG_SEED, MULTISIG_SIZE, PREFIX},
+  error::{TokensellError},
+  multisig::find_multisig_address,
+  utils::create_or_allocate_account_raw,
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct CreateMultisigArgs {
+  pub instruction: u8,
+  pub threshold: u8,
+  pub signers: Vec<Pubkey>,
+}
+
+/// Creates a multisig at the address of the create key, at the payer's expense. The signer
+/// set is fixed once created.
+///
+/// The create key signs, nobody else can take its address with a multisig of their own.
+pub fn process_create_multisig(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = CreateMultisigArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let payer_info = next_account_info(account_info_iter)?;
+  let create_key_info = next_account_info(account_info_iter)?;
+  let multisig_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !payer_info.is_signer || !create_key_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  let (multisig_key, bump) = find_multisig_address(program_id, create_key_info.key);
+
+  if multisig_key != *multisig_info.key {
+    return Err(TokensellError::NotMultisigAccount.into());
+  }
+
+  let multisig = Multisig {
+    key: Key::Multisig,
+    threshold: args.threshold,
+    proposals: 0,
+    signers: args.signers,
+  };
+
+  if !multisig.is_valid() {
+    return Err(TokensellError::InvalidMultisig.into());
+  }
+
+  msg!("Create {} of {} multisig", multisig.threshold, multisig.signers.len());
+
+  create_or_allocate_account_raw(
+    *program_id,
+    multisig_info,
+    rent_sysvar_info,
+    system_program_info,
+    payer_info,
+    MULTISIG_SIZE,
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      MULTISIG_SEED.as_bytes(),
+      create_key_info.key.as_ref(),
+      &[bump],
+    ],
+  )?;
+
+  multisig.save(multisig_info)
+}
@@ -0,0 +1,94 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, Multisig, Proposal, ProposalAccount, PREFIX, PROPOSAL_SEED},
+  error::{TokensellError},
+  multisig::find_proposal_address,
+  utils::create_or_allocate_account_raw,
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct CreateProposalArgs {
+  pub instruction: u8,
+  /// Accounts and data of the proposed instruction of this program.
+  pub accounts: Vec<ProposalAccount>,
+  pub data: Vec<u8>,
+}
+
+/// Proposes an instruction to the multisig, at the next proposal index. The proposer has
+/// to be one of its signers, pays for the proposal and approves it right away.
+pub fn process_create_proposal(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = CreateProposalArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let proposer_info = next_account_info(account_info_iter)?;
+  let multisig_info = next_account_info(account_info_iter)?;
+  let proposal_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !proposer_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *multisig_info.owner != *program_id {
+    return Err(TokensellError::NotMultisigAccount.into());
+  }
+
+  let mut multisig = Multisig::from_account_info(multisig_info)?;
+  let signer_index = multisig
+    .signer_index(proposer_info.key)
+    .ok_or(TokensellError::NotMultisigSigner)?;
+
+  let index = multisig.proposals;
+  let (proposal_key, bump) = find_proposal_address(program_id, multisig_info.key, index);
+
+  if proposal_key != *proposal_info.key {
+    return Err(TokensellError::InvalidProposal.into());
+  }
+
+  let proposal = Proposal {
+    key: Key::Proposal,
+    multisig: *multisig_info.key,
+    index,
+    approvals: 1 << signer_index,
+    executed: false,
+    accounts: args.accounts,
+    data: args.data,
+  };
+
+  msg!("Create proposal {}", index);
+
+  create_or_allocate_account_raw(
+    *program_id,
+    proposal_info,
+    rent_sysvar_info,
+    system_program_info,
+    proposer_info,
+    proposal.try_to_vec()?.len(),
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      multisig_info.key.as_ref(),
+      PROPOSAL_SEED.as_bytes(),
+      &index.to_le_bytes(),
+      &[bump],
+    ],
+  )?;
+
+  multisig.proposals = multisig.proposals
+    .checked_add(1)
+    .ok_or(TokensellError::Overflow)?;
+
+  proposal.save(proposal_info)?;
+  multisig.save(multisig_info)
+}
@@ -0,0 +1,76 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  instruction::{AccountMeta, Instruction},
+  msg,
+  program::invoke_signed,
+};
+
+use crate::{
+  state::PREFIX,
+  error::{TokensellError},
+  multisig::{find_multisig_authority, load_open_proposal},
+};
+
+/// Runs an approved proposal, signed by the multisig authority. Anyone can execute it once
+/// enough signers approved, signers of the proposed instruction other than the authority
+/// have to sign the transaction.
+///
+/// The program account follows the authority, then the accounts of the proposed
+/// instruction in order.
+pub fn process_execute_proposal(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let multisig_info = next_account_info(account_info_iter)?;
+  let proposal_info = next_account_info(account_info_iter)?;
+  let authority_info = next_account_info(account_info_iter)?;
+  let program_info = next_account_info(account_info_iter)?;
+
+  let (multisig, mut proposal) = load_open_proposal(program_id, multisig_info, proposal_info)?;
+
+  let (authority, bump) = find_multisig_authority(program_id, multisig_info.key);
+
+  if authority != *authority_info.key || *program_info.key != *program_id {
+    return Err(TokensellError::InvalidProposalAccounts.into());
+  }
+
+  if proposal.approval_count() < multisig.threshold as u32 {
+    msg!("Approvals {} of {}", proposal.approval_count(), multisig.threshold);
+    return Err(TokensellError::ThresholdNotReached.into());
+  }
+
+  let proposed_infos = account_info_iter.as_slice();
+
+  if proposed_infos.len() != proposal.accounts.len()
+    || proposed_infos.iter().zip(&proposal.accounts).any(|(info, account)| *info.key != account.pubkey)
+  {
+    return Err(TokensellError::InvalidProposalAccounts.into());
+  }
+
+  // marked before the call, so the proposed instruction can't execute it again
+  proposal.executed = true;
+  proposal.save(proposal_info)?;
+
+  msg!("Execute proposal {}", proposal.index);
+
+  let instruction = Instruction {
+    program_id: *program_id,
+    accounts: proposal.accounts
+      .iter()
+      .map(|account| AccountMeta {
+        pubkey: account.pubkey,
+        is_signer: account.is_signer,
+        is_writable: account.is_writable,
+      })
+      .collect(),
+    data: proposal.data,
+  };
+
+  invoke_signed(&instruction, accounts, &[&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    multisig_info.key.as_ref(),
+    &[bump],
+  ]])
+}
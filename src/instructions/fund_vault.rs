@@ -0,0 +1,76 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, SellData},
+  error::{TokensellError},
+  utils::{assert_token_program, spl_token_transfer, TokenTransferParams},
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct FundVaultArgs {
+  pub instruction: u8,
+  pub amount: u64,
+}
+
+/// Moves `amount` of the sold token from an account of the sale owner into the sale vault.
+pub fn process_fund_vault(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = FundVaultArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let funding_info = next_account_info(account_info_iter)?;
+  let sell_vault_info = next_account_info(account_info_iter)?;
+  let target_mint_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  let sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  if sell.owner != *owner_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  if sell.source_vault != Some(*sell_vault_info.key) {
+    return Err(TokensellError::InvalidVault.into());
+  }
+
+  if sell.target_mint != Some(*target_mint_info.key) {
+    return Err(TokensellError::InvalidTargetMint.into());
+  }
+
+  assert_token_program(token_program_info, target_mint_info)?;
+
+  msg!("Fund vault with {}", args.amount);
+
+  spl_token_transfer(TokenTransferParams {
+    source: funding_info.clone(),
+    destination: sell_vault_info.clone(),
+    mint: target_mint_info.clone(),
+    amount: args.amount,
+    decimals: sell.target_decimals,
+    authority: owner_info.clone(),
+    authority_signer_seeds: &[],
+    token_program: token_program_info.clone(),
+  })
+}
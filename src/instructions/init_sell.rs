@@ -3,40 +3,84 @@ use solana_program::{
   pubkey::Pubkey,
   account_info::{next_account_info, AccountInfo},
   msg,
-  program_pack::Pack,
   clock::UnixTimestamp,
   sysvar::{
     clock::Clock,
-    rent::Rent,
     Sysvar
   }
 };
 
 use borsh::{BorshSerialize, BorshDeserialize};
 
-use spl_token::{
-  state::Account,
-};
-
 use crate::{
-  state::{Key, SellData, PREFIX},
+  state::{
+    ComplianceGate,
+    DirectoryEntry,
+    Key,
+    PriceOracle,
+    RefundPolicy,
+    SaleStatus,
+    SellData,
+    StakingTiers,
+    TgePolicy,
+    ESCROW_SEED,
+    PREFIX,
+    TOKENSELL_SELL_SIZE,
+    VAULT_SEED,
+  },
   error::{TokensellError},
-  utils::{assert_rent_exempt}
+  oracle::read_price_account,
+  directory::{register_sale, RegisterSale},
+  utils::{
+    assert_token_program,
+    create_or_allocate_account_raw,
+    spl_token_create_account,
+    unpack_mint,
+    unpack_token_account,
+    TokenCreateAccount,
+  }
 };
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub struct InitSellArgs {
   pub instruction: u8,
-  pub exchange_rate: u64,
+  pub price_numerator: u64,
+  pub price_denominator: u64,
   pub start_time: UnixTimestamp,
   pub end_time: UnixTimestamp,
   pub initial_perc: u64,
   pub total_months: u64,
   pub min_deposit: u64,
   pub total_amount: u64,
+  pub transfers_disabled: bool,
+  /// Number of sales the owner created before, the sale address derives from it.
+  pub sale_id: u64,
+  /// Prices the sale in USD, converted to source tokens with this feed on deposit.
+  pub price_oracle: Option<PriceOracle>,
+  /// Lets participants take back their payment, deposits then wait in the sale escrow.
+  pub refund_policy: RefundPolicy,
+  /// Only lets wallets attested by the gate's authority buy.
+  pub compliance: Option<ComplianceGate>,
+  /// Only lets wallets holding a voucher signed by this key buy, up to the voucher amount.
+  pub voucher_signer: Option<Pubkey>,
+  /// Caps what each participant can buy by the amount they stake.
+  pub staking: Option<StakingTiers>,
+  /// Delays TGE changes and bounds the TGE, `TgePolicy::after` the sale's end when unset.
+  pub tge_policy: Option<TgePolicy>,
+  /// Program owning the feed of `price_oracle`, one of `PYTH_PROGRAM_IDS` when unset.
+  pub price_oracle_program: Option<Pubkey>,
 }
 
+/// Creates the sale account at the PDA of the payer and `sale_id`, and its vault, a token
+/// account at a PDA of the sale owned by the sell authority. The vault starts empty, it is
+/// funded with `FundVault` before the TGE.
+///
+/// The sale is counted in the owner's `SaleCounter` and listed in the sale directory, after
+/// the system program come the counter, the directory and the directory page for the sale.
+/// Sales priced in USD pass their price feed next. Sales with a refund policy then pass
+/// their escrow, a token account of the source mint at a PDA of the sale, and the token
+/// program of the source mint.
 pub fn process_init_sell(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
   let args = InitSellArgs::try_from_slice(instruction_data)?;
 
@@ -50,24 +94,59 @@ pub fn process_init_sell(program_id: &Pubkey, accounts: &[AccountInfo], instruct
   let target_info = next_account_info(account_info_iter)?;
   let rent_sysvar_info = next_account_info(account_info_iter)?;
   let clock_sysvar_info = next_account_info(account_info_iter)?;
+  let sell_authority_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+  let sale_counter_info = next_account_info(account_info_iter)?;
+  let directory_info = next_account_info(account_info_iter)?;
+  let directory_page_info = next_account_info(account_info_iter)?;
 
-  if *sell_info.owner != *program_id {
-    return Err(TokensellError::InvalidOwner.into());
+  if !payer_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
   }
 
-  let rent = &Rent::from_account_info(rent_sysvar_info)?;
-  let clock = &Clock::from_account_info(clock_sysvar_info)?;
+  if let Some(oracle) = &args.price_oracle {
+    let price_feed_info = next_account_info(account_info_iter)?;
+
+    if *price_feed_info.key != oracle.feed {
+      return Err(TokensellError::InvalidPriceFeed.into());
+    }
+
+    read_price_account(price_feed_info, args.price_oracle_program.as_ref())?;
+  } else if args.price_oracle_program.is_some() {
+    return Err(TokensellError::InvalidPriceFeed.into());
+  }
+
+  let escrow_infos = if args.refund_policy != RefundPolicy::None {
+    Some((next_account_info(account_info_iter)?, next_account_info(account_info_iter)?))
+  } else {
+    None
+  };
 
-  let mut sell = SellData::from_account_info(sell_info)?;
-  let token_vault = Account::unpack_from_slice(&token_vault_info.data.borrow_mut())?;
-  let target_acc = Account::unpack_from_slice(&target_info.data.borrow_mut())?;
+  let sale_id = args.sale_id.to_le_bytes();
 
-  assert_rent_exempt(rent, sell_info)?;
+  let (sell_key, sell_bump) = Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    payer_info.key.as_ref(),
+    &sale_id,
+  ], program_id);
 
-  if sell.key != Key::Uninitialized {
+  if sell_key != *sell_info.key {
+    msg!("Invalid sell account {}", sell_key);
+    return Err(TokensellError::InvalidSellAddress.into());
+  }
+
+  if !sell_info.data_is_empty() {
     return Err(TokensellError::AccountInitialized.into());
   }
 
+  let clock = &Clock::from_account_info(clock_sysvar_info)?;
+
+  let target_acc = unpack_token_account(target_info)?;
+  let target_mint = unpack_mint(target_mint_info)?;
+  let source_mint = unpack_mint(source_mint_info)?;
+
   msg!("Init sell {} {} {}", clock.unix_timestamp, args.start_time, args.end_time);
   
   if clock.unix_timestamp > args.end_time {
@@ -79,49 +158,169 @@ pub fn process_init_sell(program_id: &Pubkey, accounts: &[AccountInfo], instruct
     msg!("End time cant be less than start time");
     return Err(TokensellError::InvalidEndTimestamp.into());
   }
-  
+
+  // free grants go through AddAllocation
+  if args.price_numerator == 0 || args.price_denominator == 0 {
+    return Err(TokensellError::InvalidPrice.into());
+  }
+
+  if !args.refund_policy.is_valid() {
+    return Err(TokensellError::InvalidRefundPolicy.into());
+  }
+
+  if args.staking.as_ref().is_some_and(|staking| !staking.is_valid()) {
+    return Err(TokensellError::InvalidStakingTiers.into());
+  }
+
+  if args.tge_policy.as_ref().is_some_and(|policy| !policy.is_valid()) {
+    return Err(TokensellError::InvalidTgePolicy.into());
+  }
+
   let (sell_authority, _bump) = Pubkey::find_program_address(&[
     PREFIX.as_bytes(),
     program_id.as_ref(),
     sell_info.key.as_ref(),
   ], program_id);
 
-  msg!("Vault balance {}", token_vault.amount);
-
-  if token_vault.owner != sell_authority {
-    msg!("Invalid vault owner {}", sell_authority);
-    return Err(TokensellError::InvalidOwner.into());
+  if sell_authority != *sell_authority_info.key {
+    return Err(TokensellError::InvalidSellAuthority.into());
   }
 
-  if token_vault.amount <= 0 {
-    return Err(TokensellError::NoTokensInVault.into());
+  let (token_vault, vault_bump) = Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell_info.key.as_ref(),
+    VAULT_SEED.as_bytes(),
+  ], program_id);
+
+  if token_vault != *token_vault_info.key {
+    msg!("Invalid vault {}", token_vault);
+    return Err(TokensellError::InvalidVault.into());
   }
 
-  if token_vault.mint != *target_mint_info.key {
-    return Err(TokensellError::WrongMint.into());
+  if !token_vault_info.data_is_empty() {
+    return Err(TokensellError::AccountInitialized.into());
   }
 
+  assert_token_program(token_program_info, target_mint_info)?;
+
   if target_acc.mint != *source_mint_info.key {
     return Err(TokensellError::WrongMint.into());
   }
 
-  sell.key = Key::SellData;
-  sell.owner = *payer_info.key;
-  sell.source_mint = *source_mint_info.key;
-  sell.target_mint = None;
-  sell.source_vault = None;
-  sell.target_acc = *target_info.key;
-  sell.exchange_rate = args.exchange_rate;
-  sell.start_time = args.start_time;
-  sell.end_time = args.end_time;
-  sell.initial_perc = args.initial_perc;
-  sell.total_months = args.total_months;
-  sell.min_deposit = args.min_deposit;
-  sell.amount_total = args.total_amount;
-  sell.amount_left = args.total_amount;
-  sell.tge = None;
-  
-  sell.serialize(&mut *sell_info.data.borrow_mut())?;
+  spl_token_create_account(TokenCreateAccount {
+    payer: payer_info.clone(),
+    mint: target_mint_info.clone(),
+    account: token_vault_info.clone(),
+    authority: sell_authority_info.clone(),
+    account_seeds: &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      VAULT_SEED.as_bytes(),
+      &[vault_bump],
+    ],
+    token_program: token_program_info.clone(),
+    system_program: system_program_info.clone(),
+    rent: rent_sysvar_info.clone(),
+  })?;
+
+  if let Some((escrow_info, source_token_program_info)) = escrow_infos {
+    let (escrow, escrow_bump) = Pubkey::find_program_address(&[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      ESCROW_SEED.as_bytes(),
+    ], program_id);
+
+    if escrow != *escrow_info.key {
+      msg!("Invalid escrow {}", escrow);
+      return Err(TokensellError::InvalidEscrow.into());
+    }
+
+    assert_token_program(source_token_program_info, source_mint_info)?;
+
+    spl_token_create_account(TokenCreateAccount {
+      payer: payer_info.clone(),
+      mint: source_mint_info.clone(),
+      account: escrow_info.clone(),
+      authority: sell_authority_info.clone(),
+      account_seeds: &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        sell_info.key.as_ref(),
+        ESCROW_SEED.as_bytes(),
+        &[escrow_bump],
+      ],
+      token_program: source_token_program_info.clone(),
+      system_program: system_program_info.clone(),
+      rent: rent_sysvar_info.clone(),
+    })?;
+  }
+
+  create_or_allocate_account_raw(
+    *program_id,
+    sell_info,
+    rent_sysvar_info,
+    system_program_info,
+    payer_info,
+    TOKENSELL_SELL_SIZE,
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      payer_info.key.as_ref(),
+      &sale_id,
+      &[sell_bump],
+    ],
+  )?;
+
+  let directory_index = register_sale(program_id, RegisterSale {
+    payer: payer_info.clone(),
+    counter: sale_counter_info.clone(),
+    directory: directory_info.clone(),
+    page: directory_page_info.clone(),
+    rent: rent_sysvar_info.clone(),
+    system_program: system_program_info.clone(),
+  }, args.sale_id, DirectoryEntry {
+    sell: *sell_info.key,
+    owner: *payer_info.key,
+    status: SaleStatus::Pending,
+    start_time: args.start_time,
+    end_time: args.end_time,
+  })?;
+
+  let sell = SellData {
+    key: Key::SellDataV2,
+    owner: *payer_info.key,
+    source_mint: *source_mint_info.key,
+    target_mint: Some(*target_mint_info.key),
+    source_vault: Some(*token_vault_info.key),
+    target_acc: *target_info.key,
+    price_numerator: args.price_numerator,
+    price_denominator: args.price_denominator,
+    source_decimals: source_mint.decimals,
+    target_decimals: target_mint.decimals,
+    start_time: args.start_time,
+    end_time: args.end_time,
+    tge: None,
+    initial_perc: args.initial_perc,
+    total_months: args.total_months,
+    min_deposit: args.min_deposit,
+    amount_total: args.total_amount,
+    amount_left: args.total_amount,
+    transfers_disabled: args.transfers_disabled,
+    directory_index: Some(directory_index),
+    price_oracle: args.price_oracle,
+    refund_policy: args.refund_policy,
+    compliance: args.compliance,
+    voucher_signer: args.voucher_signer,
+    staking: args.staking,
+    tge_policy: Some(args.tge_policy.unwrap_or_else(|| TgePolicy::after(args.end_time))),
+    pending_tge: None,
+    price_oracle_program: args.price_oracle_program,
+  };
+
+  sell.save(sell_info)?;
 
   Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,83 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  clock::UnixTimestamp,
+  msg,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Attestation, Key, ATTESTATION_SEED, ATTESTATION_SIZE, PREFIX},
+  error::{TokensellError},
+  compliance::find_attestation_address,
+  utils::create_or_allocate_account_raw,
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct IssueAttestationArgs {
+  pub instruction: u8,
+  pub expires_at: UnixTimestamp,
+  /// ISO 3166-1 alpha-2 country code of the wallet holder.
+  pub jurisdiction: Option<[u8; 2]>,
+}
+
+/// Attests `wallet` on behalf of the signing compliance authority, which pays for the
+/// attestation account. Issuing again renews the attestation and lifts a revocation.
+pub fn process_issue_attestation(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = IssueAttestationArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let authority_info = next_account_info(account_info_iter)?;
+  let wallet_info = next_account_info(account_info_iter)?;
+  let attestation_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !authority_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  let (attestation_key, bump) = find_attestation_address(program_id, authority_info.key, wallet_info.key);
+
+  if attestation_key != *attestation_info.key {
+    return Err(TokensellError::InvalidAttestation.into());
+  }
+
+  if attestation_info.data_is_empty() {
+    create_or_allocate_account_raw(
+      *program_id,
+      attestation_info,
+      rent_sysvar_info,
+      system_program_info,
+      authority_info,
+      ATTESTATION_SIZE,
+      &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        authority_info.key.as_ref(),
+        ATTESTATION_SEED.as_bytes(),
+        wallet_info.key.as_ref(),
+        &[bump],
+      ],
+    )?;
+  } else if *attestation_info.owner != *program_id {
+    return Err(TokensellError::InvalidAttestation.into());
+  } else {
+    Attestation::from_account_info(attestation_info)?;
+  }
+
+  msg!("Attest {} until {}", wallet_info.key, args.expires_at);
+
+  Attestation {
+    key: Key::Attestation,
+    authority: *authority_info.key,
+    wallet: *wallet_info.key,
+    expires_at: args.expires_at,
+    jurisdiction: args.jurisdiction,
+    revoked: false,
+  }.save(attestation_info)
+}
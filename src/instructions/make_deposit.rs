@@ -3,6 +3,7 @@ use solana_program::{
   pubkey::Pubkey,
   account_info::{next_account_info, AccountInfo},
   msg,
+  clock::UnixTimestamp,
   sysvar::{clock::Clock, Sysvar}
 };
 
@@ -12,14 +13,25 @@ use crate::{
   state::{
     Key,
     ParticipantData,
+    RefundPolicy,
+    RefundableDeposit,
     SellData,
+    ESCROW_SEED,
     PARTICIPANT_SIZE,
     PREFIX,
   },
   error::{TokensellError},
+  compliance::assert_attested,
+  voucher::{read_voucher, redeem_voucher},
+  staking::assert_tier_cap,
+  oracle::{oracle_price, read_price_account},
+  price::{payment_amount, purchasable_amount},
   utils::{
+    assert_token_program,
     create_or_allocate_account_raw,
     spl_token_transfer,
+    unpack_mint,
+    unpack_token_account,
     TokenTransferParams,
   }
 };
@@ -31,6 +43,25 @@ pub struct MakeDepositArgs {
   pub amount: u64,
 }
 
+/// Buys `amount` target tokens at the sale price.
+///
+/// When the payment mint charges a transfer fee, the position is sized on what actually
+/// reached the sale's target account.
+///
+/// Sales priced in USD take their price feed after the source mint, the price in source
+/// tokens is taken from it at deposit time.
+///
+/// Sales with a refund policy are paid into their escrow instead of the target account,
+/// the payment can then be refunded until the refund window of the position closes.
+///
+/// Sales behind a compliance gate take the payer's attestation after the price feed.
+///
+/// Sales with a voucher signer then take the instructions sysvar and the receipt of the
+/// payer's voucher. The voucher is signed in an ed25519 program instruction right before
+/// the deposit, and deposits with it can't buy more than its amount in total.
+///
+/// Sales with staking tiers take the payer's stake last, the position can't grow beyond the
+/// cap of the payer's tier.
 pub fn process_make_deposit(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
   let args = MakeDepositArgs::try_from_slice(instruction_data)?;
 
@@ -46,28 +77,26 @@ pub fn process_make_deposit(program_id: &Pubkey, accounts: &[AccountInfo], instr
   let rent_sysvar_info = next_account_info(account_info_iter)?;
   let clock_sysvar_info = next_account_info(account_info_iter)?;
   let system_program_info = next_account_info(account_info_iter)?;
+  let source_mint_info = next_account_info(account_info_iter)?;
 
   if !payer_info.is_signer {
     return Err(TokensellError::SignatureRequired.into());
   }
 
-  if *token_program_info.key != spl_token::id() {
-    msg!("Invalid token program");
-    return Err(TokensellError::InvalidAccount.into());
-  }
+  assert_token_program(token_program_info, source_mint_info)?;
 
   if *sell_info.owner != *program_id {
     msg!("Invalid sell account owner");
-    return Err(TokensellError::InvalidOwner.into());
+    return Err(TokensellError::SellNotOwnedByProgram.into());
   }
 
-  let sell = SellData::from_account_info(sell_info)?;
+  let mut sell = SellData::from_account_info(sell_info)?;
 
-  if sell.key != Key::SellData {
-    return Err(TokensellError::InvalidAccount.into());
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
   }
 
-  let clock = Clock::from_account_info(&clock_sysvar_info)?;
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
 
   if clock.unix_timestamp < sell.start_time {
     return Err(TokensellError::SellNotStarted.into());
@@ -76,13 +105,77 @@ pub fn process_make_deposit(program_id: &Pubkey, accounts: &[AccountInfo], instr
   if clock.unix_timestamp > sell.end_time {
     return Err(TokensellError::SellEnded.into());
   }
-  
-  if *usdt_target_info.key != sell.target_acc {
-    msg!("Invalid target token account {} {}", usdt_target_info.key, sell.target_acc);
-    return Err(TokensellError::InvalidAccount.into());
+
+  if sell.refund_policy == RefundPolicy::None {
+    if *usdt_target_info.key != sell.target_acc {
+      msg!("Invalid target token account {} {}", usdt_target_info.key, sell.target_acc);
+      return Err(TokensellError::InvalidUSDTTargetAccount.into());
+    }
+  } else {
+    let escrow = Pubkey::find_program_address(&[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      ESCROW_SEED.as_bytes(),
+    ], program_id).0;
+
+    if *usdt_target_info.key != escrow {
+      msg!("Invalid escrow {} {}", usdt_target_info.key, escrow);
+      return Err(TokensellError::InvalidEscrow.into());
+    }
+  }
+
+  if *source_mint_info.key != sell.source_mint {
+    return Err(TokensellError::InvalidSourceMint.into());
+  }
+
+  let (price_numerator, price_denominator) = match &sell.price_oracle {
+    Some(oracle) => {
+      let price_feed_info = next_account_info(account_info_iter)?;
+
+      if *price_feed_info.key != oracle.feed {
+        msg!("Invalid price feed {}", price_feed_info.key);
+        return Err(TokensellError::InvalidPriceFeed.into());
+      }
+
+      let price = read_price_account(price_feed_info, sell.price_oracle_program.as_ref())?;
+      msg!("Oracle price {} conf {} expo {} at {}", price.price, price.conf, price.expo, price.timestamp);
+
+      let rate = oracle_price(oracle, &price, clock.unix_timestamp, sell.price_numerator, sell.price_denominator)?;
+      msg!("Deposit rate {} / {}", rate.0, rate.1);
+
+      rate
+    },
+    None => (sell.price_numerator, sell.price_denominator),
+  };
+
+  if let Some(gate) = &sell.compliance {
+    assert_attested(program_id, gate, account_info_iter.next(), payer_info.key, clock.unix_timestamp)?;
   }
 
-  let usdt_amount = sell.exchange_rate * args.amount;
+  let voucher = match &sell.voucher_signer {
+    Some(signer) => {
+      let instructions_info = next_account_info(account_info_iter)?;
+      let receipt_info = next_account_info(account_info_iter)?;
+      let voucher = read_voucher(instructions_info, signer, sell_info.key, payer_info.key, clock.unix_timestamp)?;
+
+      Some((voucher, receipt_info))
+    },
+    None => None,
+  };
+
+  let stake_info = match &sell.staking {
+    Some(_) => Some(next_account_info(account_info_iter)?),
+    None => None,
+  };
+
+  let usdt_amount = payment_amount(
+    args.amount,
+    price_numerator,
+    price_denominator,
+    sell.source_decimals,
+    sell.target_decimals,
+  )?;
 
   let (participant_key, bump) = Pubkey::find_program_address(
     &[
@@ -96,11 +189,70 @@ pub fn process_make_deposit(program_id: &Pubkey, accounts: &[AccountInfo], instr
 
   if participant_key != *participant_info.key {    
     msg!("Invalid participant account");
-    return Err(TokensellError::InvalidAccount.into());
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  let balance_before = unpack_token_account(usdt_target_info)?.amount;
+
+  msg!("Start transfer {}", usdt_amount);
+
+  spl_token_transfer(TokenTransferParams {
+    source: usdt_source_info.clone(),
+    destination: usdt_target_info.clone(),
+    mint: source_mint_info.clone(),
+    amount: usdt_amount,
+    decimals: unpack_mint(source_mint_info)?.decimals,
+    authority: transfer_authority_info.clone(),
+    authority_signer_seeds: &[],
+    token_program: token_program_info.clone(),
+  })?;
+
+  let received = unpack_token_account(usdt_target_info)?.amount
+    .checked_sub(balance_before)
+    .ok_or(TokensellError::Overflow)?;
+
+  let amount = if received < usdt_amount {
+    msg!("Received {} after transfer fees", received);
+
+    let amount = purchasable_amount(
+      received,
+      price_numerator,
+      price_denominator,
+      sell.source_decimals,
+      sell.target_decimals,
+    )?.min(args.amount);
+
+    if amount == 0 {
+      return Err(TokensellError::MinimalDeposit.into());
+    }
+
+    amount
+  } else {
+    args.amount
+  };
+
+  if let Some((voucher, receipt_info)) = voucher {
+    redeem_voucher(program_id, &voucher, receipt_info, payer_info, rent_sysvar_info, system_program_info, amount)?;
   }
 
+  if let (Some(staking), Some(stake_info)) = (&sell.staking, stake_info) {
+    let bought = if participant_info.data_is_empty() {
+      0
+    } else {
+      ParticipantData::from_account_info(participant_info)?.amount
+    };
+    let total = bought.checked_add(amount).ok_or(TokensellError::Overflow)?;
+
+    assert_tier_cap(program_id, staking, stake_info, payer_info.key, sell.end_time, total)?;
+  }
+
+  sell.amount_left = sell.amount_left
+    .checked_sub(amount)
+    .ok_or(TokensellError::SoldOut)?;
+  sell.save(sell_info)?;
+
   if participant_info.data_is_empty() {
-    if usdt_amount < sell.min_deposit {
+    if received < sell.min_deposit {
       return Err(TokensellError::MinimalDeposit.into());
     }
 
@@ -122,35 +274,63 @@ pub fn process_make_deposit(program_id: &Pubkey, accounts: &[AccountInfo], instr
       ]
     )?;
 
-    ParticipantData {
-      key: Key::ParticipantData,
+    let mut data = ParticipantData {
+      key: Key::ParticipantDataV2,
       owner: *payer_info.key,
       sell: *sell_info.key,
-      amount: args.amount,
+      amount,
       claimed: 0,
-    }.serialize(&mut *participant_info.data.borrow_mut())?;
+      paid: received,
+      last_deposit_at: clock.unix_timestamp,
+      revocable: false,
+      revoked_at: None,
+      refundable: vec![],
+    };
+    record_refundable(&sell, &mut data, amount, received, clock.unix_timestamp)?;
+    data.save(participant_info)?;
   } else {
-    let mut data = ParticipantData::try_from_slice(&participant_info.data.borrow_mut())?;
-  
+    let mut data = ParticipantData::from_account_info(participant_info)?;
+
+    if data.revoked_at.is_some() {
+      return Err(TokensellError::PositionRevoked.into());
+    }
+
+    if data.revocable {
+      return Err(TokensellError::RevocableDeposit.into());
+    }
+
     data.amount = data.amount
-      .checked_add(args.amount)
+      .checked_add(amount)
+      .ok_or(TokensellError::Overflow)?;
+    data.paid = data.paid
+      .checked_add(received)
       .ok_or(TokensellError::Overflow)?;
+    data.last_deposit_at = clock.unix_timestamp;
+    record_refundable(&sell, &mut data, amount, received, clock.unix_timestamp)?;
 
-    data.serialize(&mut *participant_info.data.borrow_mut())?;
+    data.save(participant_info)?;
   }
 
-  msg!("Start transfer {}", usdt_amount);
+  msg!("Hello from {}", program_id);
 
-  spl_token_transfer(TokenTransferParams {
-    source: usdt_source_info.clone(),
-    destination: usdt_target_info.clone(),
-    amount: usdt_amount,
-    authority: transfer_authority_info.clone(),
-    authority_signer_seeds: &[],
-    token_program: token_program_info.clone(),
-  })?;
+  Ok(())
+}
 
-  msg!("Hello from {}", program_id);
+/// Deposits of sales refunding each deposit for a while are refundable on their own.
+fn record_refundable(
+  sell: &SellData,
+  participant: &mut ParticipantData,
+  amount: u64,
+  paid: u64,
+  now: UnixTimestamp,
+) -> ProgramResult {
+  if let RefundPolicy::AfterDeposit(_) = sell.refund_policy {
+    participant.add_refundable(&sell.refund_policy, sell.end_time, RefundableDeposit {
+      amount,
+      paid,
+      deposited_at: now,
+    }, now)?;
+  }
 
   Ok(())
-}
\ No newline at end of file
+}
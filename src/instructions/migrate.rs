@@ -0,0 +1,241 @@
+use std::slice::Iter;
+
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  borsh::try_from_slice_unchecked,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{
+    Key,
+    ParticipantData,
+    RefundPolicy,
+    SellData,
+    SellDataV1,
+    PARTICIPANT_SIZE,
+    PREFIX,
+    TOKENSELL_SELL_SIZE,
+  },
+  error::{TokensellError},
+  utils::{resize_account, unpack_mint, unpack_token_account},
+};
+
+/// Instruction data of participants created before versioning, other accounts only pass the tag.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct MigrateParticipantArgs {
+  pub instruction: u8,
+  /// Tokens already paid out to the participant, legacy claims never recorded them.
+  pub claimed: u64,
+}
+
+/// Rewrites a sell or participant account in the latest layout, resizing it as needed.
+///
+/// Sales created before versioning never recorded their vault and target mint, so their
+/// owner has to sign and pass the source mint, target mint and vault after the system program.
+///
+/// Participants created before versioning never recorded their claims either, and the vault's
+/// transfers can't be read on-chain. The sale owner and the participant both sign the reconciled
+/// `claimed`, so neither can set it alone. The sale and then the participant wallet follow the
+/// system program.
+pub fn process_migrate(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let payer_info = next_account_info(account_info_iter)?;
+  let account_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !payer_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *account_info.owner != *program_id {
+    return Err(TokensellError::NotProgramAccount.into());
+  }
+
+  let key = Key::from_data(&account_info.data.borrow());
+
+  msg!("Migrate {:?}", key);
+
+  let size = match key {
+    Key::ParticipantDataV1 | Key::ParticipantDataV2 => PARTICIPANT_SIZE,
+    Key::SellDataV1 | Key::SellDataV2 => TOKENSELL_SELL_SIZE,
+    Key::Uninitialized
+    | Key::ClosedParticipant
+    | Key::SaleCounter
+    | Key::Directory
+    | Key::DirectoryPage
+    | Key::Attestation
+    | Key::VoucherReceipt
+    | Key::Stake
+    | Key::Multisig
+    | Key::Proposal => return Err(TokensellError::NotMigratable.into()),
+  };
+
+  // fields appended since the account was written decode from the zero padding
+  let mut data = account_info.data.borrow().to_vec();
+  data.resize(data.len().max(size), 0);
+
+  if key == Key::SellDataV1 {
+    let legacy: SellDataV1 = try_from_slice_unchecked(&data)?;
+    let sell = upgrade_sell(program_id, account_info, payer_info, legacy, account_info_iter)?;
+
+    resize_account(account_info, payer_info, rent_sysvar_info, system_program_info, size)?;
+    return sell.save(account_info);
+  }
+
+  if key == Key::ParticipantDataV1 {
+    let args = MigrateParticipantArgs::try_from_slice(instruction_data)?;
+    let mut participant: ParticipantData = try_from_slice_unchecked(&data)?;
+    let sell_info = next_account_info(account_info_iter)?;
+    let wallet_info = next_account_info(account_info_iter)?;
+
+    if participant.sell != *sell_info.key || *sell_info.owner != *program_id {
+      return Err(TokensellError::InvalidSellAddress.into());
+    }
+
+    if sell_owner(sell_info)? != *payer_info.key {
+      return Err(TokensellError::NotSellOwner.into());
+    }
+
+    if participant.owner != *wallet_info.key {
+      return Err(TokensellError::InvalidParticipantAddress.into());
+    }
+
+    if !wallet_info.is_signer {
+      return Err(TokensellError::SignatureRequired.into());
+    }
+
+    if args.claimed > participant.amount {
+      return Err(TokensellError::InvalidClaimedAmount.into());
+    }
+
+    msg!("Reconcile claimed {}", args.claimed);
+
+    participant.key = Key::ParticipantDataV2;
+    participant.claimed = args.claimed;
+
+    resize_account(account_info, payer_info, rent_sysvar_info, system_program_info, size)?;
+    return participant.save(account_info);
+  }
+
+  if key == key.latest() && account_info.data_len() == size {
+    msg!("Account is up to date");
+    return Ok(());
+  }
+
+  resize_account(account_info, payer_info, rent_sysvar_info, system_program_info, size)?;
+
+  if key == Key::SellDataV2 {
+    let sell: SellData = try_from_slice_unchecked(&data)?;
+    sell.save(account_info)
+  } else {
+    let participant: ParticipantData = try_from_slice_unchecked(&data)?;
+    participant.save(account_info)
+  }
+}
+
+/// Owner of a sale in either layout, the sale may itself still await migration.
+fn sell_owner(sell_info: &AccountInfo) -> Result<Pubkey, ProgramError> {
+  let mut data = sell_info.data.borrow().to_vec();
+  data.resize(data.len().max(TOKENSELL_SELL_SIZE), 0);
+
+  match Key::from_data(&data) {
+    Key::SellDataV1 => Ok(try_from_slice_unchecked::<SellDataV1>(&data)?.owner),
+    Key::SellDataV2 => Ok(try_from_slice_unchecked::<SellData>(&data)?.owner),
+    _ => Err(TokensellError::NotSellAccount.into()),
+  }
+}
+
+fn upgrade_sell(
+  program_id: &Pubkey,
+  sell_info: &AccountInfo,
+  payer_info: &AccountInfo,
+  legacy: SellDataV1,
+  account_info_iter: &mut Iter<AccountInfo>,
+) -> Result<SellData, ProgramError> {
+  let source_mint_info = next_account_info(account_info_iter)?;
+  let target_mint_info = next_account_info(account_info_iter)?;
+  let token_vault_info = next_account_info(account_info_iter)?;
+
+  if legacy.owner != *payer_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  if *source_mint_info.key != legacy.source_mint {
+    return Err(TokensellError::InvalidSourceMint.into());
+  }
+
+  if legacy.target_mint.unwrap_or(*target_mint_info.key) != *target_mint_info.key {
+    return Err(TokensellError::InvalidTargetMint.into());
+  }
+
+  if legacy.source_vault.unwrap_or(*token_vault_info.key) != *token_vault_info.key {
+    return Err(TokensellError::InvalidVault.into());
+  }
+
+  let source_mint = unpack_mint(source_mint_info)?;
+  let target_mint = unpack_mint(target_mint_info)?;
+  let token_vault = unpack_token_account(token_vault_info)?;
+
+  let (sell_authority, _bump) = Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell_info.key.as_ref(),
+  ], program_id);
+
+  if token_vault.owner != sell_authority {
+    return Err(TokensellError::InvalidVaultOwner.into());
+  }
+
+  if token_vault.mint != *target_mint_info.key {
+    return Err(TokensellError::WrongMint.into());
+  }
+
+  // the legacy rate is in source base units per target base unit
+  let price_numerator = 10u64
+    .checked_pow(target_mint.decimals.into())
+    .and_then(|scale| scale.checked_mul(legacy.exchange_rate))
+    .ok_or(TokensellError::Overflow)?;
+  let price_denominator = 10u64
+    .checked_pow(source_mint.decimals.into())
+    .ok_or(TokensellError::Overflow)?;
+
+  Ok(SellData {
+    key: Key::SellDataV2,
+    owner: legacy.owner,
+    source_mint: legacy.source_mint,
+    target_mint: Some(*target_mint_info.key),
+    source_vault: Some(*token_vault_info.key),
+    target_acc: legacy.target_acc,
+    price_numerator,
+    price_denominator,
+    source_decimals: source_mint.decimals,
+    target_decimals: target_mint.decimals,
+    start_time: legacy.start_time,
+    end_time: legacy.end_time,
+    tge: legacy.tge,
+    initial_perc: legacy.initial_perc,
+    total_months: legacy.total_months,
+    min_deposit: legacy.min_deposit,
+    amount_total: legacy.amount_total,
+    amount_left: legacy.amount_left,
+    transfers_disabled: false,
+    directory_index: None,
+    price_oracle: None,
+    refund_policy: RefundPolicy::None,
+    compliance: None,
+    voucher_signer: None,
+    staking: None,
+    tge_policy: None,
+    pending_tge: None,
+    price_oracle_program: None,
+  })
+}
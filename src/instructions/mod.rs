@@ -1,4 +1,25 @@
 pub mod make_deposit;
 pub mod init_sell;
 pub mod claim;
-pub mod set_tge;
\ No newline at end of file
+pub mod set_tge;
+pub mod migrate;
+pub mod close_participant;
+pub mod claim_for;
+pub mod batch_distribute;
+pub mod transfer_position;
+pub mod fund_vault;
+pub mod refund;
+pub mod release_escrow;
+pub mod issue_attestation;
+pub mod revoke_attestation;
+pub mod stake;
+pub mod unstake;
+pub mod set_revocable;
+pub mod revoke;
+pub mod add_allocation;
+pub mod set_owner;
+pub mod create_multisig;
+pub mod create_proposal;
+pub mod approve_proposal;
+pub mod execute_proposal;
+pub mod apply_tge;
\ No newline at end of file
@@ -0,0 +1,225 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+  state::{
+    Key,
+    ParticipantData,
+    RefundPolicy,
+    SellData,
+    ESCROW_SEED,
+    PREFIX,
+  },
+  error::{TokensellError},
+  utils::{
+    assert_token_program,
+    spl_token_transfer,
+    TokenTransferParams,
+  },
+};
+
+/// Gives a participant back what they paid, from the sale escrow, and returns the tokens
+/// to the sale. Sales refunding until their end refund the whole position, sales refunding
+/// each deposit for a while only the deposits still in their window.
+///
+/// Positions tokens were claimed from, and revoked ones, can't be refunded.
+pub fn process_refund(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+  let escrow_info = next_account_info(account_info_iter)?;
+  let sell_authority_info = next_account_info(account_info_iter)?;
+  let destination_info = next_account_info(account_info_iter)?;
+  let source_mint_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  let (mut sell, sell_bump) = load_escrowed_sell(
+    program_id,
+    sell_info,
+    escrow_info,
+    sell_authority_info,
+    source_mint_info,
+    token_program_info,
+  )?;
+
+  if *participant_info.owner != *program_id {
+    return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+  }
+
+  let participant_key = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      owner_info.key.as_ref()
+    ],
+    program_id
+  ).0;
+
+  if participant_key != *participant_info.key {
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  let mut participant = ParticipantData::from_account_info(participant_info)?;
+
+  if participant.key != Key::ParticipantDataV2 || participant.owner != *owner_info.key {
+    return Err(TokensellError::NotParticipantAccount.into());
+  }
+
+  if participant.amount == 0 {
+    return Err(TokensellError::EmptyPosition.into());
+  }
+
+  if participant.claimed > 0 {
+    return Err(TokensellError::PositionClaimed.into());
+  }
+
+  if participant.revoked_at.is_some() {
+    return Err(TokensellError::PositionRevoked.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  let (amount, paid) = if let RefundPolicy::AfterDeposit(_) = sell.refund_policy {
+    participant.take_refundable(&sell.refund_policy, sell.end_time, clock.unix_timestamp)?
+  } else if sell.refund_policy
+    .refund_deadline(participant.last_deposit_at, sell.end_time)
+    .is_some_and(|deadline| clock.unix_timestamp <= deadline)
+  {
+    (participant.amount, participant.paid)
+  } else {
+    (0, 0)
+  };
+
+  if amount == 0 {
+    msg!("No deposit in its refund window");
+    return Err(TokensellError::RefundWindowClosed.into());
+  }
+
+  msg!("Refund {} paid for {}", paid, amount);
+
+  sell.amount_left = sell.amount_left
+    .checked_add(amount)
+    .ok_or(TokensellError::Overflow)?;
+
+  participant.amount = participant.amount
+    .checked_sub(amount)
+    .ok_or(TokensellError::Overflow)?;
+  participant.paid = participant.paid
+    .checked_sub(paid)
+    .ok_or(TokensellError::Overflow)?;
+
+  sell.save(sell_info)?;
+  participant.save(participant_info)?;
+
+  transfer_from_escrow(
+    program_id,
+    sell_info,
+    escrow_info,
+    destination_info,
+    source_mint_info,
+    sell_authority_info,
+    token_program_info,
+    sell_bump,
+    paid,
+    sell.source_decimals,
+  )
+}
+
+/// Loads a sale with a refund policy, checking the accounts refunds transfer from.
+/// Returns the sale and the bump of its sell authority.
+pub fn load_escrowed_sell(
+  program_id: &Pubkey,
+  sell_info: &AccountInfo,
+  escrow_info: &AccountInfo,
+  sell_authority_info: &AccountInfo,
+  source_mint_info: &AccountInfo,
+  token_program_info: &AccountInfo,
+) -> Result<(SellData, u8), ProgramError> {
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  let sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  if sell.refund_policy == RefundPolicy::None {
+    return Err(TokensellError::RefundsDisabled.into());
+  }
+
+  let escrow = Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell_info.key.as_ref(),
+    ESCROW_SEED.as_bytes(),
+  ], program_id).0;
+
+  if escrow != *escrow_info.key {
+    return Err(TokensellError::InvalidEscrow.into());
+  }
+
+  let (sell_authority, sell_bump) = Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell_info.key.as_ref(),
+  ], program_id);
+
+  if sell_authority != *sell_authority_info.key {
+    return Err(TokensellError::InvalidSellAuthority.into());
+  }
+
+  if *source_mint_info.key != sell.source_mint {
+    return Err(TokensellError::InvalidSourceMint.into());
+  }
+
+  assert_token_program(token_program_info, source_mint_info)?;
+
+  Ok((sell, sell_bump))
+}
+
+/// Moves `amount` from the escrow, signed by the sell authority.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_from_escrow<'a>(
+  program_id: &Pubkey,
+  sell_info: &AccountInfo<'a>,
+  escrow_info: &AccountInfo<'a>,
+  destination_info: &AccountInfo<'a>,
+  source_mint_info: &AccountInfo<'a>,
+  sell_authority_info: &AccountInfo<'a>,
+  token_program_info: &AccountInfo<'a>,
+  sell_bump: u8,
+  amount: u64,
+  decimals: u8,
+) -> ProgramResult {
+  spl_token_transfer(TokenTransferParams {
+    source: escrow_info.clone(),
+    destination: destination_info.clone(),
+    mint: source_mint_info.clone(),
+    amount,
+    decimals,
+    authority: sell_authority_info.clone(),
+    authority_signer_seeds: &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      &[sell_bump]
+    ],
+    token_program: token_program_info.clone(),
+  })
+}
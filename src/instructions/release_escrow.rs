@@ -0,0 +1,71 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+  error::{TokensellError},
+  instructions::refund::{load_escrowed_sell, transfer_from_escrow},
+  utils::unpack_token_account,
+};
+
+/// Pays the escrow out to the sale's target account once no position can be refunded
+/// anymore. Anyone can crank it, the destination is fixed by the sale.
+pub fn process_release_escrow(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let sell_info = next_account_info(account_info_iter)?;
+  let escrow_info = next_account_info(account_info_iter)?;
+  let sell_authority_info = next_account_info(account_info_iter)?;
+  let target_info = next_account_info(account_info_iter)?;
+  let source_mint_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+  let (sell, sell_bump) = load_escrowed_sell(
+    program_id,
+    sell_info,
+    escrow_info,
+    sell_authority_info,
+    source_mint_info,
+    token_program_info,
+  )?;
+
+  if *target_info.key != sell.target_acc {
+    return Err(TokensellError::InvalidUSDTTargetAccount.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+  let deadline = sell.refund_policy
+    .escrow_deadline(sell.end_time)
+    .ok_or(TokensellError::RefundsDisabled)?;
+
+  if clock.unix_timestamp <= deadline {
+    msg!("Refunds are possible until {}", deadline);
+    return Err(TokensellError::EscrowLocked.into());
+  }
+
+  let amount = unpack_token_account(escrow_info)?.amount;
+
+  msg!("Release {} from escrow", amount);
+
+  if amount == 0 {
+    return Ok(());
+  }
+
+  transfer_from_escrow(
+    program_id,
+    sell_info,
+    escrow_info,
+    target_info,
+    source_mint_info,
+    sell_authority_info,
+    token_program_info,
+    sell_bump,
+    amount,
+    sell.source_decimals,
+  )
+}
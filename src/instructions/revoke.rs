@@ -0,0 +1,112 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+  state::{Key, ParticipantData, PREFIX},
+  error::{TokensellError},
+  instructions::claim::{load_vault_sell, transfer_from_vault},
+  vesting::vested_amount,
+};
+
+/// Stops vesting of a revocable position. The participant keeps what vested until now, the
+/// rest leaves the vault to `destination`, a target token account of the owner's choosing.
+///
+/// Before TGE nothing has vested and the whole position is taken back. The revoked tokens
+/// no longer count towards the sale total.
+pub fn process_revoke(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let sell_owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+  let target_mint_info = next_account_info(account_info_iter)?;
+  let sell_authority_info = next_account_info(account_info_iter)?;
+  let sell_vault_info = next_account_info(account_info_iter)?;
+  let destination_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+  if !sell_owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  let (mut sell, sell_bump) = load_vault_sell(
+    program_id,
+    sell_info,
+    target_mint_info,
+    sell_authority_info,
+    sell_vault_info,
+    token_program_info,
+  )?;
+
+  if sell.owner != *sell_owner_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  if *participant_info.owner != *program_id {
+    return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+  }
+
+  let mut participant = ParticipantData::from_account_info(participant_info)?;
+
+  let participant_key = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      participant.owner.as_ref()
+    ],
+    program_id
+  ).0;
+
+  if participant.key != Key::ParticipantDataV2 || participant_key != *participant_info.key {
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  if !participant.revocable {
+    return Err(TokensellError::NotRevocable.into());
+  }
+
+  if participant.revoked_at.is_some() {
+    return Err(TokensellError::PositionRevoked.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  let vested = match sell.tge {
+    Some(tge) => vested_amount(participant.amount, sell.initial_perc, sell.total_months, tge, clock.unix_timestamp)?,
+    None => 0,
+  };
+  let unvested = participant.amount - vested;
+
+  msg!("Revoke {} of {}, {} vested", unvested, participant.amount, vested);
+
+  participant.amount = vested;
+  participant.revoked_at = Some(clock.unix_timestamp);
+  sell.amount_total = sell.amount_total.saturating_sub(unvested);
+
+  participant.save(participant_info)?;
+  sell.save(sell_info)?;
+
+  if unvested == 0 {
+    return Ok(());
+  }
+
+  transfer_from_vault(
+    program_id,
+    sell_info,
+    sell_vault_info,
+    destination_info,
+    target_mint_info,
+    sell_authority_info,
+    token_program_info,
+    sell_bump,
+    unvested,
+    sell.target_decimals,
+  )
+}
@@ -0,0 +1,39 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use crate::{
+  state::Attestation,
+  error::{TokensellError},
+};
+
+/// Revokes an attestation of the signing compliance authority, the wallet can't buy in
+/// the sales it gates until it is issued again.
+pub fn process_revoke_attestation(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let authority_info = next_account_info(account_info_iter)?;
+  let attestation_info = next_account_info(account_info_iter)?;
+
+  if !authority_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *attestation_info.owner != *program_id {
+    return Err(TokensellError::InvalidAttestation.into());
+  }
+
+  let mut attestation = Attestation::from_account_info(attestation_info)?;
+
+  if attestation.authority != *authority_info.key {
+    return Err(TokensellError::InvalidAttestation.into());
+  }
+
+  msg!("Revoke attestation of {}", attestation.wallet);
+
+  attestation.revoked = true;
+  attestation.save(attestation_info)
+}
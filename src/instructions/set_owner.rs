@@ -0,0 +1,54 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, SellData},
+  error::{TokensellError},
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct SetOwnerArgs {
+  pub instruction: u8,
+  pub owner: Pubkey,
+}
+
+/// Hands the admin rights of a sale to `owner`, such as the authority of a multisig. The
+/// sale keeps its address and its directory entry keeps the creator.
+pub fn process_set_owner(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = SetOwnerArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  let mut sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  if sell.owner != *owner_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  msg!("Set owner {}", args.owner);
+
+  sell.owner = args.owner;
+  sell.save(sell_info)
+}
@@ -0,0 +1,80 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, ParticipantData, SellData, PREFIX},
+  error::{TokensellError},
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct SetRevocableArgs {
+  pub instruction: u8,
+  pub revocable: bool,
+}
+
+/// Marks a position as revocable, or clears the mark. The sale owner and the participant
+/// both sign, neither can change the terms of the position alone.
+pub fn process_set_revocable(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = SetRevocableArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let sell_owner_info = next_account_info(account_info_iter)?;
+  let owner_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+
+  if !sell_owner_info.is_signer || !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  if *participant_info.owner != *program_id {
+    return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+  }
+
+  let sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  if sell.owner != *sell_owner_info.key {
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  let participant_key = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      owner_info.key.as_ref()
+    ],
+    program_id
+  ).0;
+
+  if participant_key != *participant_info.key {
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  let mut participant = ParticipantData::from_account_info(participant_info)?;
+
+  if participant.revoked_at.is_some() {
+    return Err(TokensellError::PositionRevoked.into());
+  }
+
+  msg!("Set revocable {}", args.revocable);
+
+  participant.revocable = args.revocable;
+  participant.save(participant_info)
+}
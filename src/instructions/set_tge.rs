@@ -3,28 +3,31 @@ use solana_program::{
   pubkey::Pubkey,
   account_info::{next_account_info, AccountInfo},
   msg,
-  program_pack::Pack,
   clock::UnixTimestamp,
-};
-
-use spl_token::{
-  state::Account,
+  sysvar::{clock::Clock, Sysvar},
 };
 
 use borsh::{BorshSerialize, BorshDeserialize};
 
 use crate::{
-  state::{Key, SellData, PREFIX},
+  state::{Key, PendingTge, SellData, TgePolicy, PREFIX},
   error::{TokensellError},
+  utils::unpack_token_account,
 };
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
 pub struct SetTgeArgs {
   pub instruction: u8,
+  /// `None` is rejected, a TGE can't be cleared.
   pub tge: Option<UnixTimestamp>,
 }
 
+/// Queues a TGE change within the bounds of the sale's TGE policy, `ApplyTge` makes it once
+/// the policy's delay is over. The TGE can't change anymore once it passed, nor be cleared.
+///
+/// Sales created before policies existed have none, their first TGE change stores
+/// `TgePolicy::around` the TGE it sets, or the one they already had.
 pub fn process_set_tge(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
   let args = SetTgeArgs::try_from_slice(instruction_data)?;
 
@@ -34,24 +37,35 @@ pub fn process_set_tge(program_id: &Pubkey, accounts: &[AccountInfo], instructio
   let sell_info = next_account_info(account_info_iter)?;
   let target_mint_info = next_account_info(account_info_iter)?;
   let source_vault_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
   
   if !payer_info.is_signer {
     return Err(TokensellError::SignatureRequired.into());
   }
 
   if *sell_info.owner != *program_id {
-    return Err(TokensellError::InvalidOwner.into());
+    return Err(TokensellError::SellNotOwnedByProgram.into());
   }
 
   let mut sell = SellData::from_account_info(sell_info)?;
-  let token_vault = Account::unpack_from_slice(&source_vault_info.data.borrow_mut())?;
+  let token_vault = unpack_token_account(source_vault_info)?;
 
-  if sell.key != Key::SellData {
-    return Err(TokensellError::InvalidAccount.into());
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
   }
 
   if sell.owner != *payer_info.key {
-    return Err(TokensellError::InvalidAccount.into());
+    return Err(TokensellError::NotSellOwner.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  if sell.tge.is_some_and(|tge| clock.unix_timestamp > tge) {
+    return Err(TokensellError::TgeLocked.into());
+  }
+
+  if sell.source_vault != Some(*source_vault_info.key) {
+    return Err(TokensellError::InvalidVault.into());
   }
   
   let (sell_authority, _bump) = Pubkey::find_program_address(&[
@@ -64,7 +78,7 @@ pub fn process_set_tge(program_id: &Pubkey, accounts: &[AccountInfo], instructio
 
   if token_vault.owner != sell_authority {
     msg!("Invalid vault owner {}", sell_authority);
-    return Err(TokensellError::InvalidOwner.into());
+    return Err(TokensellError::InvalidVaultOwner.into());
   }
 
   if token_vault.amount < sell.amount_total {
@@ -75,11 +89,23 @@ pub fn process_set_tge(program_id: &Pubkey, accounts: &[AccountInfo], instructio
     return Err(TokensellError::WrongMint.into());
   }
 
-  msg!("Set tge {}", args.tge.unwrap_or(-1));
-  
-  sell.tge = args.tge;
-  
-  sell.serialize(&mut *sell_info.data.borrow_mut())?;
+  let tge = args.tge.ok_or(TokensellError::TgeOutOfBounds)?;
+  let policy = match &sell.tge_policy {
+    Some(policy) => policy.clone(),
+    None => TgePolicy::around(sell.tge.unwrap_or(tge)),
+  };
+
+  if !policy.allows(tge) {
+    return Err(TokensellError::TgeOutOfBounds.into());
+  }
 
-  Ok(())
-}
\ No newline at end of file
+  let eta = clock.unix_timestamp
+    .checked_add(policy.delay)
+    .ok_or(TokensellError::Overflow)?;
+
+  msg!("Queue tge {} until {}", tge, eta);
+
+  sell.tge_policy = Some(policy);
+  sell.pending_tge = Some(PendingTge { tge, eta });
+  sell.save(sell_info)
+}
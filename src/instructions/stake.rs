@@ -0,0 +1,157 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  clock::UnixTimestamp,
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Key, Stake, PREFIX, STAKE_SEED, STAKE_SIZE, VAULT_SEED},
+  error::{TokensellError},
+  staking::{find_stake_address, find_stake_authority, find_stake_vault_address},
+  utils::{
+    assert_token_program,
+    create_or_allocate_account_raw,
+    spl_token_create_account,
+    spl_token_transfer,
+    unpack_mint,
+    unpack_token_account,
+    TokenCreateAccount,
+    TokenTransferParams,
+  },
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct StakeArgs {
+  pub instruction: u8,
+  pub amount: u64,
+  /// Extends the lock of the whole stake, a lock ending earlier is kept.
+  pub lock_until: UnixTimestamp,
+}
+
+/// Locks `amount` of a mint from an account of the owner into the vault of their stake of
+/// that mint, creating both on first use. Sales with staking tiers of the mint cap their
+/// participants by it.
+pub fn process_stake(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = StakeArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let stake_info = next_account_info(account_info_iter)?;
+  let stake_vault_info = next_account_info(account_info_iter)?;
+  let stake_authority_info = next_account_info(account_info_iter)?;
+  let mint_info = next_account_info(account_info_iter)?;
+  let source_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  assert_token_program(token_program_info, mint_info)?;
+
+  let (stake_key, stake_bump) = find_stake_address(program_id, mint_info.key, owner_info.key);
+
+  if stake_key != *stake_info.key {
+    return Err(TokensellError::InvalidStakeAccount.into());
+  }
+
+  if find_stake_authority(program_id, stake_info.key).0 != *stake_authority_info.key {
+    return Err(TokensellError::InvalidStakeVault.into());
+  }
+
+  let (stake_vault, vault_bump) = find_stake_vault_address(program_id, stake_info.key);
+
+  if stake_vault != *stake_vault_info.key {
+    return Err(TokensellError::InvalidStakeVault.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  if args.lock_until <= clock.unix_timestamp {
+    return Err(TokensellError::InvalidLockPeriod.into());
+  }
+
+  let mut stake = if stake_info.data_is_empty() {
+    create_or_allocate_account_raw(
+      *program_id,
+      stake_info,
+      rent_sysvar_info,
+      system_program_info,
+      owner_info,
+      STAKE_SIZE,
+      &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        STAKE_SEED.as_bytes(),
+        mint_info.key.as_ref(),
+        owner_info.key.as_ref(),
+        &[stake_bump],
+      ],
+    )?;
+
+    spl_token_create_account(TokenCreateAccount {
+      payer: owner_info.clone(),
+      mint: mint_info.clone(),
+      account: stake_vault_info.clone(),
+      authority: stake_authority_info.clone(),
+      account_seeds: &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        stake_info.key.as_ref(),
+        VAULT_SEED.as_bytes(),
+        &[vault_bump],
+      ],
+      token_program: token_program_info.clone(),
+      system_program: system_program_info.clone(),
+      rent: rent_sysvar_info.clone(),
+    })?;
+
+    Stake {
+      key: Key::Stake,
+      owner: *owner_info.key,
+      mint: *mint_info.key,
+      amount: 0,
+      unlock_at: 0,
+    }
+  } else if *stake_info.owner != *program_id {
+    return Err(TokensellError::InvalidStakeAccount.into());
+  } else {
+    Stake::from_account_info(stake_info)?
+  };
+
+  let balance_before = unpack_token_account(stake_vault_info)?.amount;
+
+  spl_token_transfer(TokenTransferParams {
+    source: source_info.clone(),
+    destination: stake_vault_info.clone(),
+    mint: mint_info.clone(),
+    amount: args.amount,
+    decimals: unpack_mint(mint_info)?.decimals,
+    authority: owner_info.clone(),
+    authority_signer_seeds: &[],
+    token_program: token_program_info.clone(),
+  })?;
+
+  let received = unpack_token_account(stake_vault_info)?.amount
+    .checked_sub(balance_before)
+    .ok_or(TokensellError::Overflow)?;
+
+  stake.amount = stake.amount
+    .checked_add(received)
+    .ok_or(TokensellError::Overflow)?;
+  stake.unlock_at = stake.unlock_at.max(args.lock_until);
+
+  msg!("Staked {} until {}", stake.amount, stake.unlock_at);
+
+  stake.save(stake_info)
+}
@@ -0,0 +1,187 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+  state::{
+    Key,
+    ParticipantData,
+    SellData,
+    MAX_REFUNDABLE_DEPOSITS,
+    PARTICIPANT_SIZE,
+    PREFIX,
+  },
+  error::{TokensellError},
+  utils::create_or_allocate_account_raw,
+  compliance::assert_attested,
+  staking::assert_tier_cap,
+};
+
+/// Moves a whole position, with what was already claimed from it, to `recipient`.
+///
+/// The position merges into the recipient's participant account, which is created at the
+/// owner's expense when missing. The emptied account can then be closed.
+///
+/// The merged position keeps the later of both deposit times and the refundable deposits
+/// of both, refunds return their payment to the recipient.
+///
+/// Revocable grants can't be transferred, and no position can be merged into one.
+///
+/// The recipient has to qualify as a buyer: sales with a compliance gate take the recipient's
+/// attestation and sales with staking tiers its stake, the merged position can't grow beyond
+/// the cap of its tier. Voucher allocations are bound to their wallet, so positions of
+/// voucher sales can't be transferred.
+pub fn process_transfer_position(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let recipient_info = next_account_info(account_info_iter)?;
+  let sell_info = next_account_info(account_info_iter)?;
+  let participant_info = next_account_info(account_info_iter)?;
+  let recipient_participant_info = next_account_info(account_info_iter)?;
+  let rent_sysvar_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+  let system_program_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  if *sell_info.owner != *program_id {
+    return Err(TokensellError::SellNotOwnedByProgram.into());
+  }
+
+  if *participant_info.owner != *program_id {
+    return Err(TokensellError::ParticipantNotOwnedByProgram.into());
+  }
+
+  let sell = SellData::from_account_info(sell_info)?;
+
+  if sell.key != Key::SellDataV2 {
+    return Err(TokensellError::NotSellAccount.into());
+  }
+
+  if sell.transfers_disabled || sell.voucher_signer.is_some() {
+    return Err(TokensellError::TransfersDisabled.into());
+  }
+
+  if owner_info.key == recipient_info.key {
+    return Err(TokensellError::TransferToSelf.into());
+  }
+
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  if let Some(gate) = &sell.compliance {
+    assert_attested(program_id, gate, account_info_iter.next(), recipient_info.key, clock.unix_timestamp)?;
+  }
+
+  let stake_info = match &sell.staking {
+    Some(_) => Some(next_account_info(account_info_iter)?),
+    None => None,
+  };
+
+  let participant_key = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      owner_info.key.as_ref()
+    ],
+    program_id
+  ).0;
+
+  let (recipient_participant_key, recipient_bump) = Pubkey::find_program_address(
+    &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      sell_info.key.as_ref(),
+      recipient_info.key.as_ref()
+    ],
+    program_id
+  );
+
+  if participant_key != *participant_info.key || recipient_participant_key != *recipient_participant_info.key {
+    return Err(TokensellError::InvalidParticipantAddress.into());
+  }
+
+  let mut participant = ParticipantData::from_account_info(participant_info)?;
+
+  if participant.amount == 0 {
+    return Err(TokensellError::EmptyPosition.into());
+  }
+
+  let mut recipient = if recipient_participant_info.data_is_empty() {
+    msg!("Create recipient account");
+
+    create_or_allocate_account_raw(
+      *program_id,
+      recipient_participant_info,
+      rent_sysvar_info,
+      system_program_info,
+      owner_info,
+      PARTICIPANT_SIZE,
+      &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        sell_info.key.as_ref(),
+        recipient_info.key.as_ref(),
+        &[recipient_bump]
+      ]
+    )?;
+
+    ParticipantData {
+      key: Key::ParticipantDataV2,
+      owner: *recipient_info.key,
+      sell: *sell_info.key,
+      amount: 0,
+      claimed: 0,
+      paid: 0,
+      last_deposit_at: 0,
+      revocable: false,
+      revoked_at: None,
+      refundable: vec![],
+    }
+  } else {
+    ParticipantData::from_account_info(recipient_participant_info)?
+  };
+
+  if participant.revocable || recipient.revocable {
+    return Err(TokensellError::RevocablePosition.into());
+  }
+
+  msg!("Transfer position of {}, {} claimed", participant.amount, participant.claimed);
+
+  recipient.amount = recipient.amount
+    .checked_add(participant.amount)
+    .ok_or(TokensellError::Overflow)?;
+
+  if let (Some(staking), Some(stake_info)) = (&sell.staking, stake_info) {
+    assert_tier_cap(program_id, staking, stake_info, recipient_info.key, sell.end_time, recipient.amount)?;
+  }
+
+  recipient.claimed = recipient.claimed
+    .checked_add(participant.claimed)
+    .ok_or(TokensellError::Overflow)?;
+  recipient.paid = recipient.paid
+    .checked_add(participant.paid)
+    .ok_or(TokensellError::Overflow)?;
+  recipient.last_deposit_at = recipient.last_deposit_at.max(participant.last_deposit_at);
+  recipient.refundable.append(&mut participant.refundable);
+
+  if recipient.refundable.len() > MAX_REFUNDABLE_DEPOSITS {
+    return Err(TokensellError::TooManyRefundableDeposits.into());
+  }
+
+  participant.amount = 0;
+  participant.claimed = 0;
+  participant.paid = 0;
+
+  participant.save(participant_info)?;
+  recipient.save(recipient_participant_info)?;
+
+  Ok(())
+}
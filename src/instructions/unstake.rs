@@ -0,0 +1,90 @@
+use solana_program::{
+  entrypoint::ProgramResult,
+  pubkey::Pubkey,
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  sysvar::{clock::Clock, Sysvar},
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use crate::{
+  state::{Stake, PREFIX},
+  error::{TokensellError},
+  staking::{find_stake_address, find_stake_authority, find_stake_vault_address},
+  utils::{assert_token_program, spl_token_transfer, unpack_mint, TokenTransferParams},
+};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct UnstakeArgs {
+  pub instruction: u8,
+  pub amount: u64,
+}
+
+/// Moves `amount` out of the owner's stake once its lock ended.
+pub fn process_unstake(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  let args = UnstakeArgs::try_from_slice(instruction_data)?;
+
+  let account_info_iter = &mut accounts.iter();
+
+  let owner_info = next_account_info(account_info_iter)?;
+  let stake_info = next_account_info(account_info_iter)?;
+  let stake_vault_info = next_account_info(account_info_iter)?;
+  let stake_authority_info = next_account_info(account_info_iter)?;
+  let mint_info = next_account_info(account_info_iter)?;
+  let destination_info = next_account_info(account_info_iter)?;
+  let token_program_info = next_account_info(account_info_iter)?;
+  let clock_sysvar_info = next_account_info(account_info_iter)?;
+
+  if !owner_info.is_signer {
+    return Err(TokensellError::SignatureRequired.into());
+  }
+
+  assert_token_program(token_program_info, mint_info)?;
+
+  if *stake_info.owner != *program_id
+    || find_stake_address(program_id, mint_info.key, owner_info.key).0 != *stake_info.key
+  {
+    return Err(TokensellError::InvalidStakeAccount.into());
+  }
+
+  let (stake_authority, authority_bump) = find_stake_authority(program_id, stake_info.key);
+
+  if stake_authority != *stake_authority_info.key
+    || find_stake_vault_address(program_id, stake_info.key).0 != *stake_vault_info.key
+  {
+    return Err(TokensellError::InvalidStakeVault.into());
+  }
+
+  let mut stake = Stake::from_account_info(stake_info)?;
+  let clock = Clock::from_account_info(clock_sysvar_info)?;
+
+  if clock.unix_timestamp < stake.unlock_at {
+    msg!("Stake unlocks at {}", stake.unlock_at);
+    return Err(TokensellError::StakeLocked.into());
+  }
+
+  stake.amount = stake.amount
+    .checked_sub(args.amount)
+    .ok_or(TokensellError::InsufficientStake)?;
+  stake.save(stake_info)?;
+
+  msg!("Unstake {}", args.amount);
+
+  spl_token_transfer(TokenTransferParams {
+    source: stake_vault_info.clone(),
+    destination: destination_info.clone(),
+    mint: mint_info.clone(),
+    amount: args.amount,
+    decimals: unpack_mint(mint_info)?.decimals,
+    authority: stake_authority_info.clone(),
+    authority_signer_seeds: &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      stake_info.key.as_ref(),
+      &[authority_bump],
+    ],
+    token_program: token_program_info.clone(),
+  })
+}
@@ -3,3 +3,14 @@ pub mod state;
 pub mod error;
 pub mod utils;
 pub mod instructions;
+pub mod price;
+pub mod vesting;
+pub mod directory;
+pub mod oracle;
+pub mod compliance;
+pub mod voucher;
+pub mod staking;
+pub mod multisig;
+
+#[cfg(feature = "client")]
+pub mod client;
@@ -0,0 +1,70 @@
+use solana_program::{
+  account_info::AccountInfo,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+};
+
+use crate::{
+  error::TokensellError,
+  state::{Multisig, Proposal, MULTISIG_SEED, PREFIX, PROPOSAL_SEED},
+};
+
+/// Multisig created with `create_key`, any key picked by its creator.
+pub fn find_multisig_address(program_id: &Pubkey, create_key: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    MULTISIG_SEED.as_bytes(),
+    create_key.as_ref(),
+  ], program_id)
+}
+
+/// Key signing the proposals of the multisig, set as owner of the sales it controls.
+pub fn find_multisig_authority(program_id: &Pubkey, multisig: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    multisig.as_ref(),
+  ], program_id)
+}
+
+pub fn find_proposal_address(program_id: &Pubkey, multisig: &Pubkey, index: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    multisig.as_ref(),
+    PROPOSAL_SEED.as_bytes(),
+    &index.to_le_bytes(),
+  ], program_id)
+}
+
+/// Loads the multisig and one of its proposals, checking the proposal belongs to it and
+/// hasn't been executed yet.
+pub fn load_open_proposal(
+  program_id: &Pubkey,
+  multisig_info: &AccountInfo,
+  proposal_info: &AccountInfo,
+) -> Result<(Multisig, Proposal), ProgramError> {
+  if *multisig_info.owner != *program_id {
+    return Err(TokensellError::NotMultisigAccount.into());
+  }
+
+  if *proposal_info.owner != *program_id {
+    return Err(TokensellError::InvalidProposal.into());
+  }
+
+  let multisig = Multisig::from_account_info(multisig_info)?;
+  let proposal = Proposal::from_account_info(proposal_info)?;
+
+  if proposal.multisig != *multisig_info.key
+    || find_proposal_address(program_id, multisig_info.key, proposal.index).0 != *proposal_info.key
+  {
+    return Err(TokensellError::InvalidProposal.into());
+  }
+
+  if proposal.executed {
+    return Err(TokensellError::ProposalExecuted.into());
+  }
+
+  Ok((multisig, proposal))
+}
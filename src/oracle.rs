@@ -0,0 +1,146 @@
+use solana_program::{
+  account_info::AccountInfo,
+  clock::UnixTimestamp,
+  msg,
+  pubkey,
+  pubkey::Pubkey,
+};
+
+use crate::{
+  error::TokensellError,
+  state::PriceOracle,
+};
+
+/// Pyth oracle programs of mainnet-beta and devnet, feeds of sales that don't name their own
+/// oracle program have to be owned by one of them.
+pub const PYTH_PROGRAM_IDS: [Pubkey; 2] = [
+  pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH"),
+  pubkey!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s"),
+];
+
+/// Magic number opening every Pyth account.
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+pub const PYTH_VERSION: u32 = 2;
+/// Account type of Pyth price accounts, as opposed to mapping and product accounts.
+pub const PYTH_PRICE_ACCOUNT: u32 = 3;
+/// Status of the aggregate price while publishers are trading.
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Offsets of the fields read from a Pyth v2 price account.
+pub const PYTH_EXPO_OFFSET: usize = 20;
+pub const PYTH_TIMESTAMP_OFFSET: usize = 96;
+pub const PYTH_AGG_PRICE_OFFSET: usize = 208;
+pub const PYTH_AGG_CONF_OFFSET: usize = 216;
+pub const PYTH_AGG_STATUS_OFFSET: usize = 224;
+/// Bytes up to the end of the aggregate price, the publisher components that follow are ignored.
+pub const PYTH_PRICE_MIN_SIZE: usize = 240;
+
+/// Aggregate price of a feed, `price * 10^expo` USD per whole token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OraclePrice {
+  pub price: i64,
+  pub conf: u64,
+  pub expo: i32,
+  pub timestamp: UnixTimestamp,
+  pub trading: bool,
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+  let mut bytes = [0; N];
+  bytes.copy_from_slice(&data[offset..offset + N]);
+  bytes
+}
+
+/// Reads the aggregate price of a Pyth v2 price account.
+pub fn read_price(data: &[u8]) -> Result<OraclePrice, TokensellError> {
+  if data.len() < PYTH_PRICE_MIN_SIZE
+    || u32::from_le_bytes(read_bytes(data, 0)) != PYTH_MAGIC
+    || u32::from_le_bytes(read_bytes(data, 4)) != PYTH_VERSION
+    || u32::from_le_bytes(read_bytes(data, 8)) != PYTH_PRICE_ACCOUNT
+  {
+    return Err(TokensellError::InvalidPriceFeed);
+  }
+
+  Ok(OraclePrice {
+    price: i64::from_le_bytes(read_bytes(data, PYTH_AGG_PRICE_OFFSET)),
+    conf: u64::from_le_bytes(read_bytes(data, PYTH_AGG_CONF_OFFSET)),
+    expo: i32::from_le_bytes(read_bytes(data, PYTH_EXPO_OFFSET)),
+    timestamp: i64::from_le_bytes(read_bytes(data, PYTH_TIMESTAMP_OFFSET)),
+    trading: u32::from_le_bytes(read_bytes(data, PYTH_AGG_STATUS_OFFSET)) == PYTH_STATUS_TRADING,
+  })
+}
+
+/// Reads the aggregate price of a price feed account, checking it belongs to `oracle_program`,
+/// or to Pyth when it is unset. The feed has to follow the Pyth v2 layout either way.
+pub fn read_price_account(feed_info: &AccountInfo, oracle_program: Option<&Pubkey>) -> Result<OraclePrice, TokensellError> {
+  let owned = match oracle_program {
+    Some(program) => feed_info.owner == program,
+    None => PYTH_PROGRAM_IDS.contains(feed_info.owner),
+  };
+
+  if !owned {
+    msg!("Price feed owned by {}", feed_info.owner);
+    return Err(TokensellError::InvalidPriceFeed);
+  }
+
+  read_price(&feed_info.data.borrow())
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  a
+}
+
+/// Converts a price of `numerator / denominator` USD per whole target token into whole
+/// source tokens per whole target token, `price` being the feed of the source token.
+///
+/// The scaled fraction is reduced before it is narrowed back to `u64`, only prices that
+/// don't fit once reduced fail with `Overflow`.
+///
+/// Fails when the feed isn't trading, is older than `oracle.max_age` seconds at `now`,
+/// or its confidence interval is wider than `oracle.max_confidence_bps` of the price.
+pub fn oracle_price(
+  oracle: &PriceOracle,
+  price: &OraclePrice,
+  now: UnixTimestamp,
+  numerator: u64,
+  denominator: u64,
+) -> Result<(u64, u64), TokensellError> {
+  if price.price <= 0 {
+    return Err(TokensellError::InvalidPriceFeed);
+  }
+
+  // publishers' clocks may run slightly ahead of the cluster
+  let age = now.saturating_sub(price.timestamp).max(0) as u64;
+
+  if !price.trading || age > oracle.max_age {
+    return Err(TokensellError::StalePrice);
+  }
+
+  let feed_price = price.price as u128;
+
+  if (price.conf as u128) * 10_000 > feed_price * (oracle.max_confidence_bps as u128) {
+    return Err(TokensellError::PriceUncertain);
+  }
+
+  let scale = 10u128
+    .checked_pow(price.expo.unsigned_abs())
+    .ok_or(TokensellError::Overflow)?;
+
+  let (numerator, denominator) = if price.expo < 0 {
+    ((numerator as u128).checked_mul(scale), (denominator as u128).checked_mul(feed_price))
+  } else {
+    (Some(numerator as u128), (denominator as u128).checked_mul(feed_price).and_then(|value| value.checked_mul(scale)))
+  };
+
+  let numerator = numerator.ok_or(TokensellError::Overflow)?;
+  let denominator = denominator.ok_or(TokensellError::Overflow)?;
+  let divisor = gcd(numerator, denominator).max(1);
+
+  let numerator = u64::try_from(numerator / divisor).map_err(|_| TokensellError::Overflow)?;
+  let denominator = u64::try_from(denominator / divisor).map_err(|_| TokensellError::Overflow)?;
+
+  Ok((numerator, denominator))
+}
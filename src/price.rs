@@ -0,0 +1,59 @@
+use crate::error::TokensellError;
+
+/// Source token base units owed for `amount` base units of the target token.
+///
+/// The price is `numerator / denominator` whole source tokens per whole target token,
+/// so the result is scaled by `10^source_decimals / 10^target_decimals`. Fractions of a
+/// base unit are rounded up, in favour of the sale.
+pub fn payment_amount(
+  amount: u64,
+  numerator: u64,
+  denominator: u64,
+  source_decimals: u8,
+  target_decimals: u8,
+) -> Result<u64, TokensellError> {
+  if denominator == 0 {
+    return Err(TokensellError::InvalidPrice);
+  }
+
+  let dividend = (amount as u128)
+    .checked_mul(numerator as u128)
+    .and_then(|value| value.checked_mul(10u128.checked_pow(source_decimals.into())?))
+    .ok_or(TokensellError::Overflow)?;
+
+  let divisor = 10u128
+    .checked_pow(target_decimals.into())
+    .and_then(|scale| scale.checked_mul(denominator as u128))
+    .ok_or(TokensellError::Overflow)?;
+
+  let payment = dividend / divisor + u128::from(dividend % divisor != 0);
+
+  u64::try_from(payment).map_err(|_| TokensellError::Overflow)
+}
+
+/// Target token base units `payment` source base units buy, the inverse of `payment_amount`.
+///
+/// Fractions of a base unit are rounded down, in favour of the sale.
+pub fn purchasable_amount(
+  payment: u64,
+  numerator: u64,
+  denominator: u64,
+  source_decimals: u8,
+  target_decimals: u8,
+) -> Result<u64, TokensellError> {
+  if numerator == 0 || denominator == 0 {
+    return Err(TokensellError::InvalidPrice);
+  }
+
+  let dividend = (payment as u128)
+    .checked_mul(denominator as u128)
+    .and_then(|value| value.checked_mul(10u128.checked_pow(target_decimals.into())?))
+    .ok_or(TokensellError::Overflow)?;
+
+  let divisor = 10u128
+    .checked_pow(source_decimals.into())
+    .and_then(|scale| scale.checked_mul(numerator as u128))
+    .ok_or(TokensellError::Overflow)?;
+
+  u64::try_from(dividend / divisor).map_err(|_| TokensellError::Overflow)
+}
@@ -4,6 +4,7 @@ use solana_program::{
   pubkey::Pubkey,
   account_info::AccountInfo,
   msg,
+  program_error::PrintProgramError,
 };
 
 use crate::{
@@ -13,12 +14,43 @@ use crate::{
     init_sell::process_init_sell,
     claim::process_claim,
     set_tge::process_set_tge,
+    migrate::process_migrate,
+    close_participant::process_close_participant,
+    claim_for::process_claim_for,
+    batch_distribute::process_batch_distribute,
+    transfer_position::process_transfer_position,
+    fund_vault::process_fund_vault,
+    refund::process_refund,
+    release_escrow::process_release_escrow,
+    issue_attestation::process_issue_attestation,
+    revoke_attestation::process_revoke_attestation,
+    stake::process_stake,
+    unstake::process_unstake,
+    set_revocable::process_set_revocable,
+    revoke::process_revoke,
+    add_allocation::process_add_allocation,
+    set_owner::process_set_owner,
+    create_multisig::process_create_multisig,
+    create_proposal::process_create_proposal,
+    approve_proposal::process_approve_proposal,
+    execute_proposal::process_execute_proposal,
+    apply_tge::process_apply_tge,
   },
 };
 
 // use borsh::{BorshSerialize, BorshDeserialize};
 
+/// Runs the instruction and logs the message of the error it fails with.
 pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+  if let Err(error) = dispatch(program_id, accounts, instruction_data) {
+    error.print::<TokensellError>();
+    return Err(error);
+  }
+
+  Ok(())
+}
+
+fn dispatch(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
   if instruction_data.is_empty() {
     return Err(TokensellError::UnknownInstruction.into());
   }
@@ -42,6 +74,90 @@ pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instru
       msg!("Instruction: Set TGE");
       process_set_tge(program_id, accounts, instruction_data)
     },
+    4 => {
+      msg!("Instruction: Migrate");
+      process_migrate(program_id, accounts, instruction_data)
+    },
+    5 => {
+      msg!("Instruction: Close participant");
+      process_close_participant(program_id, accounts, instruction_data)
+    },
+    6 => {
+      msg!("Instruction: Claim for");
+      process_claim_for(program_id, accounts, instruction_data)
+    },
+    7 => {
+      msg!("Instruction: Batch distribute");
+      process_batch_distribute(program_id, accounts, instruction_data)
+    },
+    8 => {
+      msg!("Instruction: Transfer position");
+      process_transfer_position(program_id, accounts, instruction_data)
+    },
+    9 => {
+      msg!("Instruction: Fund vault");
+      process_fund_vault(program_id, accounts, instruction_data)
+    },
+    10 => {
+      msg!("Instruction: Refund");
+      process_refund(program_id, accounts, instruction_data)
+    },
+    11 => {
+      msg!("Instruction: Release escrow");
+      process_release_escrow(program_id, accounts, instruction_data)
+    },
+    12 => {
+      msg!("Instruction: Issue attestation");
+      process_issue_attestation(program_id, accounts, instruction_data)
+    },
+    13 => {
+      msg!("Instruction: Revoke attestation");
+      process_revoke_attestation(program_id, accounts, instruction_data)
+    },
+    14 => {
+      msg!("Instruction: Stake");
+      process_stake(program_id, accounts, instruction_data)
+    },
+    15 => {
+      msg!("Instruction: Unstake");
+      process_unstake(program_id, accounts, instruction_data)
+    },
+    16 => {
+      msg!("Instruction: Set revocable");
+      process_set_revocable(program_id, accounts, instruction_data)
+    },
+    17 => {
+      msg!("Instruction: Revoke");
+      process_revoke(program_id, accounts, instruction_data)
+    },
+    18 => {
+      msg!("Instruction: Add allocation");
+      process_add_allocation(program_id, accounts, instruction_data)
+    },
+    19 => {
+      msg!("Instruction: Set owner");
+      process_set_owner(program_id, accounts, instruction_data)
+    },
+    20 => {
+      msg!("Instruction: Create multisig");
+      process_create_multisig(program_id, accounts, instruction_data)
+    },
+    21 => {
+      msg!("Instruction: Create proposal");
+      process_create_proposal(program_id, accounts, instruction_data)
+    },
+    22 => {
+      msg!("Instruction: Approve proposal");
+      process_approve_proposal(program_id, accounts, instruction_data)
+    },
+    23 => {
+      msg!("Instruction: Execute proposal");
+      process_execute_proposal(program_id, accounts, instruction_data)
+    },
+    24 => {
+      msg!("Instruction: Apply TGE");
+      process_apply_tge(program_id, accounts, instruction_data)
+    },
     _ => Err(TokensellError::UnknownInstruction.into())
   }
 }
@@ -0,0 +1,82 @@
+use solana_program::{
+  account_info::AccountInfo,
+  clock::UnixTimestamp,
+  entrypoint::ProgramResult,
+  msg,
+  pubkey::Pubkey,
+};
+
+use crate::{
+  error::TokensellError,
+  state::{Stake, StakingTiers, PREFIX, STAKE_SEED, VAULT_SEED},
+};
+
+/// Stake of `mint` tokens locked by `owner`.
+pub fn find_stake_address(program_id: &Pubkey, mint: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    STAKE_SEED.as_bytes(),
+    mint.as_ref(),
+    owner.as_ref(),
+  ], program_id)
+}
+
+/// Owner of the stake vault, signing unstake transfers.
+pub fn find_stake_authority(program_id: &Pubkey, stake: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    stake.as_ref(),
+  ], program_id)
+}
+
+/// Token account holding the staked tokens.
+pub fn find_stake_vault_address(program_id: &Pubkey, stake: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    stake.as_ref(),
+    VAULT_SEED.as_bytes(),
+  ], program_id)
+}
+
+/// Fails unless the tier of `wallet`'s stake lets it hold `total` target tokens.
+///
+/// Only stakes locked at least until the sale ends at `end_time` count, a wallet without
+/// one is treated as staking nothing.
+pub fn assert_tier_cap(
+  program_id: &Pubkey,
+  staking: &StakingTiers,
+  stake_info: &AccountInfo,
+  wallet: &Pubkey,
+  end_time: UnixTimestamp,
+  total: u64,
+) -> ProgramResult {
+  if find_stake_address(program_id, &staking.mint, wallet).0 != *stake_info.key {
+    msg!("Invalid stake {} for {}", stake_info.key, wallet);
+    return Err(TokensellError::InvalidStakeAccount.into());
+  }
+
+  let staked = if *stake_info.owner == *program_id {
+    let stake = Stake::from_account_info(stake_info)?;
+
+    if stake.unlock_at < end_time {
+      msg!("Stake unlocks at {}, before the sale ends", stake.unlock_at);
+      0
+    } else {
+      stake.amount
+    }
+  } else {
+    0
+  };
+
+  let cap = staking.cap(staked).ok_or(TokensellError::NoStakingTier)?;
+
+  if total > cap {
+    msg!("Tier of {} staked caps at {}", staked, cap);
+    return Err(TokensellError::TierCapExceeded.into());
+  }
+
+  Ok(())
+}
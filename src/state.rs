@@ -3,6 +3,7 @@ use solana_program::{
   pubkey::Pubkey,
   clock::UnixTimestamp,
   account_info::AccountInfo,
+  entrypoint::ProgramResult,
   program_error::ProgramError,
   borsh::try_from_slice_unchecked
 };
@@ -11,31 +12,202 @@ use crate::{
     error::TokensellError
 };
 
-pub static PARTICIPANT_SIZE: usize = 1 + 32 + 32 + 32 + 8;
-pub static TOKENSELL_SELL_SIZE: usize = 1 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8;
+pub static MAX_REFUNDABLE_DEPOSITS: usize = 8;
+pub static PARTICIPANT_SIZE: usize = 1 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 9 + 4 + MAX_REFUNDABLE_DEPOSITS * (8 + 8 + 8);
+pub static CLOSED_PARTICIPANT_SIZE: usize = 1;
+pub static TOKENSELL_SELL_SIZE: usize = 1 + 32 + 32 + 33 + 33 + 32 + 8 + 8 + 1 + 1 + 8 + 8 + 9 + 8 + 8 + 8 + 8 + 8 + 1 + 9 + 49 + 9 + 34 + 33 + 1 + STAKING_TIERS_SIZE + 25 + 17 + 33;
+pub static SALE_COUNTER_SIZE: usize = 1 + 32 + 8;
+pub static DIRECTORY_SIZE: usize = 1 + 8;
+pub static DIRECTORY_ENTRY_SIZE: usize = 32 + 32 + 1 + 8 + 8;
+pub static DIRECTORY_PAGE_CAPACITY: usize = 32;
+pub static DIRECTORY_PAGE_SIZE: usize = 1 + 8 + 4 + DIRECTORY_PAGE_CAPACITY * DIRECTORY_ENTRY_SIZE;
+pub static ATTESTATION_SIZE: usize = 1 + 32 + 32 + 8 + 3 + 1;
+pub static VOUCHER_RECEIPT_SIZE: usize = 1 + 32 + 32 + 8 + 8;
+pub static STAKE_SIZE: usize = 1 + 32 + 32 + 8 + 8;
+pub static MAX_STAKING_TIERS: usize = 4;
+pub static STAKING_TIERS_SIZE: usize = 32 + 4 + MAX_STAKING_TIERS * (8 + 8);
+pub static MAX_MULTISIG_SIGNERS: usize = 16;
+pub static MULTISIG_SIZE: usize = 1 + 1 + 8 + 4 + MAX_MULTISIG_SIGNERS * 32;
 
 pub static PREFIX: &str = "tokensell";
+pub static VAULT_SEED: &str = "vault";
+pub static ESCROW_SEED: &str = "escrow";
+pub static COUNTER_SEED: &str = "counter";
+pub static DIRECTORY_SEED: &str = "directory";
+pub static ATTESTATION_SEED: &str = "attestation";
+pub static VOUCHER_SEED: &str = "voucher";
+pub static STAKE_SEED: &str = "stake";
+pub static MULTISIG_SEED: &str = "multisig";
+pub static PROPOSAL_SEED: &str = "proposal";
 
+pub static MONTH_SECONDS: u64 = 30 * 24 * 3600;
+/// Policy of sales created without one, see `TgePolicy::after` and `TgePolicy::around`.
+pub static DEFAULT_TGE_DELAY: i64 = 2 * 24 * 3600;
+pub static DEFAULT_TGE_WINDOW: i64 = 90 * 24 * 3600;
+
+/// Account type and layout version, stored in the first byte of every account.
+/// Layouts only ever append fields, and appended fields must decode from zeroed bytes, so
+/// `migrate` can grow accounts written by an older version in place.
 #[repr(C)]
-#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub enum Key {
     Uninitialized,
-    ParticipantData,
-    SellData,
+    ParticipantDataV1,
+    SellDataV1,
+    ParticipantDataV2,
+    SellDataV2,
+    /// Tombstone left by `close_participant`, so the PDA can't be created again.
+    ClosedParticipant,
+    SaleCounter,
+    Directory,
+    DirectoryPage,
+    Attestation,
+    VoucherReceipt,
+    Stake,
+    Multisig,
+    Proposal,
+}
+
+impl Key {
+    /// Key of the current layout for the same account type.
+    pub fn latest(&self) -> Key {
+        match self {
+            Key::ParticipantDataV1 => Key::ParticipantDataV2,
+            Key::SellDataV1 => Key::SellDataV2,
+            key => key.clone(),
+        }
+    }
+
+    pub fn from_data(data: &[u8]) -> Key {
+        data.first()
+            .and_then(|key| Key::try_from_slice(&[*key]).ok())
+            .unwrap_or(Key::Uninitialized)
+    }
 }
 
+/// Fails with `MigrationRequired` when `data` holds an older layout of the `latest` account
+/// type, either an older key or an account too short for fields appended since.
+pub fn assert_latest_layout(data: &[u8], latest: Key, size: usize) -> ProgramResult {
+    let key = Key::from_data(data);
+
+    if key.latest() == latest && (key != latest || data.len() < size) {
+        return Err(TokensellError::MigrationRequired.into());
+    }
+
+    if data.len() < size {
+        return Err(TokensellError::SizeMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// Serializes `value` at the start of the account and zeroes the rest, so fields appended
+/// in later layouts decode as zero.
+fn write_account<T: BorshSerialize>(value: &T, a: &AccountInfo) -> ProgramResult {
+    let bytes = value.try_to_vec()?;
+    let mut data = a.data.borrow_mut();
+
+    if bytes.len() > data.len() {
+        return Err(TokensellError::SizeMismatch.into());
+    }
+
+    data[..bytes.len()].copy_from_slice(&bytes);
+    data[bytes.len()..].fill(0);
+
+    Ok(())
+}
+
+/// A wallet's deposits into one sale and what it has claimed so far.
 #[repr(C)]
-#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub struct ParticipantData {
     pub key: Key,
     pub owner: Pubkey,
     pub sell: Pubkey,
     pub amount: u64,
     pub claimed: u64,
+    /// Source base units the sale received for `amount`, zero for positions bought before
+    /// it was recorded.
+    pub paid: u64,
+    /// Time of the latest deposit, refund windows counted from deposits start there.
+    pub last_deposit_at: UnixTimestamp,
+    /// Set by the sale owner together with the participant, the owner can then revoke the
+    /// position and take back what hasn't vested.
+    pub revocable: bool,
+    /// Time the position was revoked at. `amount` is then what had vested, all of it claimable.
+    pub revoked_at: Option<UnixTimestamp>,
+    /// Deposits of sales refunding each deposit for a while, at most `MAX_REFUNDABLE_DEPOSITS`.
+    /// Deposits drop out once their window closed.
+    pub refundable: Vec<RefundableDeposit>,
+}
+
+/// Deposit refundable until `RefundPolicy::refund_deadline` of its time.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct RefundableDeposit {
+    pub amount: u64,
+    pub paid: u64,
+    pub deposited_at: UnixTimestamp,
+}
+
+impl ParticipantData {
+    pub fn from_account_info(a: &AccountInfo) -> Result<ParticipantData, ProgramError> {
+        if Key::from_data(&a.data.borrow()) == Key::ClosedParticipant {
+            return Err(TokensellError::ParticipantClosed.into());
+        }
+
+        assert_latest_layout(&a.data.borrow(), Key::ParticipantDataV2, PARTICIPANT_SIZE)?;
+
+        let participant: ParticipantData = try_from_slice_unchecked(&a.data.borrow_mut())?;
+
+        Ok(participant)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+
+    /// Records a deposit of a sale refunding each deposit for a while, deposits whose
+    /// window closed before `now` drop out.
+    pub fn add_refundable(
+        &mut self,
+        policy: &RefundPolicy,
+        end_time: UnixTimestamp,
+        deposit: RefundableDeposit,
+        now: UnixTimestamp,
+    ) -> ProgramResult {
+        self.refundable.retain(|deposit| {
+            policy.refund_deadline(deposit.deposited_at, end_time).is_some_and(|deadline| now <= deadline)
+        });
+
+        if self.refundable.len() >= MAX_REFUNDABLE_DEPOSITS {
+            return Err(TokensellError::TooManyRefundableDeposits.into());
+        }
+
+        self.refundable.push(deposit);
+
+        Ok(())
+    }
+
+    /// Amount and payment of the deposits still refundable at `now`, all deposits drop out.
+    pub fn take_refundable(&mut self, policy: &RefundPolicy, end_time: UnixTimestamp, now: UnixTimestamp) -> Result<(u64, u64), ProgramError> {
+        let mut amount: u64 = 0;
+        let mut paid: u64 = 0;
+
+        for deposit in self.refundable.drain(..) {
+            if policy.refund_deadline(deposit.deposited_at, end_time).is_some_and(|deadline| now <= deadline) {
+                amount = amount.checked_add(deposit.amount).ok_or(TokensellError::Overflow)?;
+                paid = paid.checked_add(deposit.paid).ok_or(TokensellError::Overflow)?;
+            }
+        }
+
+        Ok((amount, paid))
+    }
 }
 
+/// A sale: its mints, schedule, limits and the gates deposits and claims go through.
 #[repr(C)]
-#[derive(Clone, BorshSerialize, BorshDeserialize, PartialEq)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub struct SellData {
     pub key: Key,
     pub owner: Pubkey,
@@ -43,7 +215,12 @@ pub struct SellData {
     pub target_mint: Option<Pubkey>,
     pub source_vault: Option<Pubkey>,
     pub target_acc: Pubkey,
-    pub exchange_rate: u64,
+    /// Whole source tokens paid per whole target token, as numerator / denominator, or USD
+    /// when the sale has a `price_oracle`.
+    pub price_numerator: u64,
+    pub price_denominator: u64,
+    pub source_decimals: u8,
+    pub target_decimals: u8,
     pub start_time: UnixTimestamp,
     pub end_time: UnixTimestamp,
     pub tge: Option<UnixTimestamp>,
@@ -52,16 +229,473 @@ pub struct SellData {
     pub min_deposit: u64,
     pub amount_total: u64,
     pub amount_left: u64,
+    /// Set by sellers who don't allow positions to move to another wallet.
+    pub transfers_disabled: bool,
+    /// Position in the sale directory, unset for sales created before it existed.
+    pub directory_index: Option<u64>,
+    /// Set for sales priced in USD, the price is then converted with the feed on deposit.
+    pub price_oracle: Option<PriceOracle>,
+    /// How long participants can take back their payment, which waits in the sale escrow
+    /// until then.
+    pub refund_policy: RefundPolicy,
+    /// Set for sales only open to wallets attested by a compliance authority.
+    pub compliance: Option<ComplianceGate>,
+    /// Set for sales only open to wallets holding a voucher signed by this key.
+    pub voucher_signer: Option<Pubkey>,
+    /// Set for sales capping each participant by the amount they stake.
+    pub staking: Option<StakingTiers>,
+    /// Delay of TGE changes and bounds of the TGE, unset only for sales created before
+    /// policies existed until their first TGE change.
+    pub tge_policy: Option<TgePolicy>,
+    /// TGE change queued by the owner under the sale's `tge_policy`.
+    pub pending_tge: Option<PendingTge>,
+    /// Program owning the feed of `price_oracle`, Pyth when unset.
+    pub price_oracle_program: Option<Pubkey>,
+}
+
+/// Bounds on the TGE of a sale, fixed when it is created.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct TgePolicy {
+    /// Seconds a queued TGE change waits before it can be applied.
+    pub delay: i64,
+    pub earliest: UnixTimestamp,
+    pub latest: UnixTimestamp,
+}
+
+impl TgePolicy {
+    /// Default policy of new sales, keeping the TGE within `DEFAULT_TGE_WINDOW` after the
+    /// sale ends at `end_time`.
+    pub fn after(end_time: UnixTimestamp) -> TgePolicy {
+        TgePolicy {
+            delay: DEFAULT_TGE_DELAY,
+            earliest: end_time,
+            latest: end_time.saturating_add(DEFAULT_TGE_WINDOW),
+        }
+    }
+
+    /// Default policy of sales created before policies existed, keeping the TGE within
+    /// `DEFAULT_TGE_WINDOW` of `tge`.
+    pub fn around(tge: UnixTimestamp) -> TgePolicy {
+        TgePolicy {
+            delay: DEFAULT_TGE_DELAY,
+            earliest: tge.saturating_sub(DEFAULT_TGE_WINDOW),
+            latest: tge.saturating_add(DEFAULT_TGE_WINDOW),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.delay > 0 && self.earliest <= self.latest
+    }
+
+    pub fn allows(&self, tge: UnixTimestamp) -> bool {
+        self.earliest <= tge && tge <= self.latest
+    }
+}
+
+/// TGE change that can be applied from `eta` on.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct PendingTge {
+    pub tge: UnixTimestamp,
+    pub eta: UnixTimestamp,
+}
+
+/// Compliance authority whose attestation a wallet needs to buy in a sale.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ComplianceGate {
+    pub authority: Pubkey,
+    /// Claims need a valid attestation as well.
+    pub gate_claims: bool,
+}
+
+/// Allocation caps by amount of `mint` staked, a wallet staking less than the first tier
+/// can't buy.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct StakingTiers {
+    pub mint: Pubkey,
+    /// Up to `MAX_STAKING_TIERS`, ascending in both stake and cap.
+    pub tiers: Vec<StakingTier>,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct StakingTier {
+    pub min_staked: u64,
+    /// Target tokens a participant of the tier can buy in total.
+    pub max_amount: u64,
+}
+
+impl StakingTiers {
+    pub fn is_valid(&self) -> bool {
+        !self.tiers.is_empty()
+            && self.tiers.len() <= MAX_STAKING_TIERS
+            && self.tiers.windows(2).all(|pair| {
+                pair[0].min_staked < pair[1].min_staked && pair[0].max_amount <= pair[1].max_amount
+            })
+    }
+
+    /// Cap of the highest tier `staked` reaches.
+    pub fn cap(&self, staked: u64) -> Option<u64> {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| staked >= tier.min_staked)
+            .map(|tier| tier.max_amount)
+    }
+}
+
+/// Pyth price feed of the source token, for sales priced in USD per whole target token.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct PriceOracle {
+    pub feed: Pubkey,
+    /// Oldest feed update accepted, in seconds.
+    pub max_age: u64,
+    /// Widest confidence interval accepted, in basis points of the price.
+    pub max_confidence_bps: u64,
+}
+
+/// Refund window of the participants of a sale.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub enum RefundPolicy {
+    /// Payments go straight to the target account.
+    None,
+    /// Each deposit can be refunded for this many seconds.
+    AfterDeposit(u64),
+    /// Deposits can be refunded until the sale ends.
+    UntilEnd,
+}
+
+impl RefundPolicy {
+    /// Windows have to fit in a timestamp.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            RefundPolicy::AfterDeposit(seconds) => i64::try_from(*seconds).is_ok(),
+            _ => true,
+        }
+    }
+
+    /// Last moment a deposit made at `deposited_at` can be refunded.
+    pub fn refund_deadline(&self, deposited_at: UnixTimestamp, end_time: UnixTimestamp) -> Option<UnixTimestamp> {
+        match self {
+            RefundPolicy::None => None,
+            RefundPolicy::AfterDeposit(seconds) => {
+                Some(deposited_at.saturating_add(i64::try_from(*seconds).unwrap_or(i64::MAX)))
+            },
+            RefundPolicy::UntilEnd => Some(end_time),
+        }
+    }
+
+    /// Last moment any position of a sale ending at `end_time` can be refunded, the escrow
+    /// is released to the target account after it.
+    pub fn escrow_deadline(&self, end_time: UnixTimestamp) -> Option<UnixTimestamp> {
+        self.refund_deadline(end_time, end_time)
+    }
 }
 
 impl SellData {
     pub fn from_account_info(a: &AccountInfo) -> Result<SellData, ProgramError> {
-        if a.data_len() < TOKENSELL_SELL_SIZE {
-            return Err(TokensellError::SizeMismatch.into());
-        }
+        assert_latest_layout(&a.data.borrow(), Key::SellDataV2, TOKENSELL_SELL_SIZE)?;
 
         let auction: SellData = try_from_slice_unchecked(&a.data.borrow_mut())?;
 
         Ok(auction)
     }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+/// Sale layout written before versioning, with an integer exchange rate in base units
+/// and the vault and target mint left unset.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct SellDataV1 {
+    pub key: Key,
+    pub owner: Pubkey,
+    pub source_mint: Pubkey,
+    pub target_mint: Option<Pubkey>,
+    pub source_vault: Option<Pubkey>,
+    pub target_acc: Pubkey,
+    pub exchange_rate: u64,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+    pub tge: Option<UnixTimestamp>,
+    pub initial_perc: u64,
+    pub total_months: u64,
+    pub min_deposit: u64,
+    pub amount_total: u64,
+    pub amount_left: u64,
+}
+
+/// Sales created by `owner`, whose sale ids run from zero to `sales` - 1.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct SaleCounter {
+    pub key: Key,
+    pub owner: Pubkey,
+    pub sales: u64,
+}
+
+impl SaleCounter {
+    pub fn from_account_info(a: &AccountInfo) -> Result<SaleCounter, ProgramError> {
+        let counter: SaleCounter = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if counter.key != Key::SaleCounter {
+            return Err(TokensellError::NotDirectoryAccount.into());
+        }
+
+        Ok(counter)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+/// Number of sales listed in the directory, sale `n` is entry `n % DIRECTORY_PAGE_CAPACITY`
+/// of page `n / DIRECTORY_PAGE_CAPACITY`.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Directory {
+    pub key: Key,
+    pub sales: u64,
+}
+
+impl Directory {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Directory, ProgramError> {
+        let directory: Directory = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if directory.key != Key::Directory {
+            return Err(TokensellError::NotDirectoryAccount.into());
+        }
+
+        Ok(directory)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub enum SaleStatus {
+    /// Created, the TGE isn't set yet.
+    Pending,
+    /// The TGE is set and participants can claim from it.
+    TgeSet,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct DirectoryEntry {
+    pub sell: Pubkey,
+    pub owner: Pubkey,
+    pub status: SaleStatus,
+    pub start_time: UnixTimestamp,
+    pub end_time: UnixTimestamp,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct DirectoryPage {
+    pub key: Key,
+    pub page: u64,
+    pub entries: Vec<DirectoryEntry>,
+}
+
+impl DirectoryPage {
+    pub fn from_account_info(a: &AccountInfo) -> Result<DirectoryPage, ProgramError> {
+        let page: DirectoryPage = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if page.key != Key::DirectoryPage {
+            return Err(TokensellError::NotDirectoryAccount.into());
+        }
+
+        Ok(page)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+/// Attestation of `wallet` by a compliance `authority`, valid until `expires_at` unless
+/// revoked.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Attestation {
+    pub key: Key,
+    pub authority: Pubkey,
+    pub wallet: Pubkey,
+    pub expires_at: UnixTimestamp,
+    /// ISO 3166-1 alpha-2 country code of the wallet holder, when the authority records it.
+    pub jurisdiction: Option<[u8; 2]>,
+    pub revoked: bool,
+}
+
+impl Attestation {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Attestation, ProgramError> {
+        let attestation: Attestation = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if attestation.key != Key::Attestation {
+            return Err(TokensellError::NotAttestationAccount.into());
+        }
+
+        Ok(attestation)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+/// Amount bought so far with the voucher `nonce` of `wallet` in `sell`.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct VoucherReceipt {
+    pub key: Key,
+    pub sell: Pubkey,
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub spent: u64,
+}
+
+impl VoucherReceipt {
+    pub fn from_account_info(a: &AccountInfo) -> Result<VoucherReceipt, ProgramError> {
+        let receipt: VoucherReceipt = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if receipt.key != Key::VoucherReceipt {
+            return Err(TokensellError::InvalidVoucherReceipt.into());
+        }
+
+        Ok(receipt)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+/// `amount` of `mint` locked by `owner` until `unlock_at`, in the vault of the stake.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Stake {
+    pub key: Key,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub unlock_at: UnixTimestamp,
+}
+
+impl Stake {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Stake, ProgramError> {
+        let stake: Stake = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if stake.key != Key::Stake {
+            return Err(TokensellError::InvalidStakeAccount.into());
+        }
+
+        Ok(stake)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+}
+
+/// M-of-N signer set. Its authority, `find_multisig_authority`, can own sales, instructions
+/// signed by it run through proposals once `threshold` signers approved them.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Multisig {
+    pub key: Key,
+    pub threshold: u8,
+    /// Proposals created so far, which is the index of the next one.
+    pub proposals: u64,
+    pub signers: Vec<Pubkey>,
+}
+
+impl Multisig {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Multisig, ProgramError> {
+        let multisig: Multisig = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if multisig.key != Key::Multisig {
+            return Err(TokensellError::NotMultisigAccount.into());
+        }
+
+        Ok(multisig)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+
+    /// At least one and at most `MAX_MULTISIG_SIGNERS` distinct signers, and a threshold
+    /// they can reach.
+    pub fn is_valid(&self) -> bool {
+        let distinct = self.signers
+            .iter()
+            .enumerate()
+            .all(|(index, signer)| !self.signers[..index].contains(signer));
+
+        distinct
+            && self.signers.len() <= MAX_MULTISIG_SIGNERS
+            && self.threshold > 0
+            && self.threshold as usize <= self.signers.len()
+    }
+
+    pub fn signer_index(&self, signer: &Pubkey) -> Option<usize> {
+        self.signers.iter().position(|key| key == signer)
+    }
+}
+
+/// Account of an instruction proposed to a multisig.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Instruction of this program signed by a multisig authority once enough of its signers
+/// approved it.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Proposal {
+    pub key: Key,
+    pub multisig: Pubkey,
+    pub index: u64,
+    /// Bit `i` is set once the multisig signer at index `i` approved.
+    pub approvals: u16,
+    pub executed: bool,
+    pub accounts: Vec<ProposalAccount>,
+    pub data: Vec<u8>,
+}
+
+impl Proposal {
+    pub fn from_account_info(a: &AccountInfo) -> Result<Proposal, ProgramError> {
+        let proposal: Proposal = try_from_slice_unchecked(&a.data.borrow())?;
+
+        if proposal.key != Key::Proposal {
+            return Err(TokensellError::InvalidProposal.into());
+        }
+
+        Ok(proposal)
+    }
+
+    pub fn save(&self, a: &AccountInfo) -> ProgramResult {
+        write_account(self, a)
+    }
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
 }
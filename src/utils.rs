@@ -10,50 +10,112 @@ use solana_program::{
   msg
 };
 
+use spl_token_2022::{
+  extension::{ExtensionType, StateWithExtensions},
+  state::{Account, Mint},
+};
+
 use crate::error::TokensellError;
 
-/// Issue a spl_token `Transfer` instruction.
+/// True for the classic Token program and Token-2022.
+pub fn is_token_program(key: &Pubkey) -> bool {
+    *key == spl_token::id() || *key == spl_token_2022::id()
+}
+
+/// Fails unless `token_program_info` is a supported token program and owns `mint_info`.
+pub fn assert_token_program(token_program_info: &AccountInfo, mint_info: &AccountInfo) -> ProgramResult {
+    if !is_token_program(token_program_info.key) || mint_info.owner != token_program_info.key {
+        msg!("Invalid token program {}", token_program_info.key);
+        return Err(TokensellError::InvalidTokenProgram.into());
+    }
+
+    Ok(())
+}
+
+/// Associated token account of `wallet` for `mint`, whose address depends on the token program.
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &spl_associated_token_account::id(),
+    ).0
+}
+
+/// Unpacks a token account of either token program, skipping Token-2022 extensions.
+pub fn unpack_token_account(account_info: &AccountInfo) -> Result<Account, ProgramError> {
+    if !is_token_program(account_info.owner) {
+        return Err(TokensellError::InvalidOwner.into());
+    }
+
+    Ok(StateWithExtensions::<Account>::unpack(&account_info.data.borrow())?.base)
+}
+
+/// Unpacks a mint of either token program, skipping Token-2022 extensions.
+pub fn unpack_mint(mint_info: &AccountInfo) -> Result<Mint, ProgramError> {
+    if !is_token_program(mint_info.owner) {
+        return Err(TokensellError::InvalidOwner.into());
+    }
+
+    Ok(StateWithExtensions::<Mint>::unpack(&mint_info.data.borrow())?.base)
+}
+
+/// Issue a `TransferChecked` instruction, after checking both token accounts hold `mint`.
+/// Errors of the token program, such as insufficient funds, are returned as is.
+///
+/// Callers pass the sale's mint for the transfer, so a mismatch means a token account of
+/// another mint was supplied.
 #[inline(always)]
 pub fn spl_token_transfer(params: TokenTransferParams<'_, '_>) -> ProgramResult {
     let TokenTransferParams {
         source,
         destination,
+        mint,
         authority,
         token_program,
         amount,
+        decimals,
         authority_signer_seeds,
     } = params;
 
-    let result;
-    
-    if authority_signer_seeds.is_empty() {
-      result = invoke(
-        &spl_token::instruction::transfer(
+    if unpack_token_account(&source)?.mint != *mint.key {
+        msg!("Source {} doesn't hold mint {}", source.key, mint.key);
+        return Err(TokensellError::SourceMintMismatch.into());
+    }
+
+    if unpack_token_account(&destination)?.mint != *mint.key {
+        msg!("Destination {} doesn't hold mint {}", destination.key, mint.key);
+        return Err(TokensellError::DestinationMintMismatch.into());
+    }
+
+    let instruction = if *token_program.key == spl_token_2022::id() {
+        spl_token_2022::instruction::transfer_checked(
             token_program.key,
             source.key,
+            mint.key,
             destination.key,
             authority.key,
             &[],
             amount,
-        )?,
-        &[source, destination, authority, token_program],
-      );
+            decimals,
+        )?
     } else {
-      result = invoke_signed(
-        &spl_token::instruction::transfer(
+        spl_token::instruction::transfer_checked(
             token_program.key,
             source.key,
+            mint.key,
             destination.key,
             authority.key,
             &[],
             amount,
-        )?,
-        &[source, destination, authority, token_program],
-        &[authority_signer_seeds],
-      );
-    }
+            decimals,
+        )?
+    };
+    let accounts = [source, mint, destination, authority, token_program];
 
-    result.map_err(|_| TokensellError::TokenTransferFailed.into())
+    if authority_signer_seeds.is_empty() {
+        invoke(&instruction, &accounts)
+    } else {
+        invoke_signed(&instruction, &accounts, &[authority_signer_seeds])
+    }
 }
 
 
@@ -76,7 +138,7 @@ pub fn create_or_allocate_account_raw<'a>(
     if required_lamports > 0 {
         msg!("Transfer {} lamports to the new account", required_lamports);
         invoke(
-            &system_instruction::transfer(&payer_info.key, new_account_info.key, required_lamports),
+            &system_instruction::transfer(payer_info.key, new_account_info.key, required_lamports),
             &[
                 payer_info.clone(),
                 new_account_info.clone(),
@@ -89,34 +151,79 @@ pub fn create_or_allocate_account_raw<'a>(
     invoke_signed(
         &system_instruction::allocate(new_account_info.key, size.try_into().unwrap()),
         &[new_account_info.clone(), system_program_info.clone()],
-        &[&signer_seeds],
+        &[signer_seeds],
     )?;
 
     msg!("Assign the account to the owning program");
     invoke_signed(
         &system_instruction::assign(new_account_info.key, &program_id),
         &[new_account_info.clone(), system_program_info.clone()],
-        &[&signer_seeds],
+        &[signer_seeds],
     )?;
     msg!("Completed assignation!");
 
     Ok(())
 }
 
-/// Create a new SPL token account.
+/// Resize a program owned account, topping up its lamports from the payer to keep it rent exempt.
+pub fn resize_account<'a>(
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    rent_sysvar_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    size: usize,
+) -> ProgramResult {
+    let rent = &Rent::from_account_info(rent_sysvar_info)?;
+    let required_lamports = rent
+        .minimum_balance(size)
+        .saturating_sub(account_info.lamports());
+
+    if required_lamports > 0 {
+        msg!("Transfer {} lamports to the resized account", required_lamports);
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, required_lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    account_info.realloc(size, true)
+}
+
+/// Create a new token account at a PDA of this program, sized for the extensions `mint`
+/// requires under Token-2022.
 #[inline(always)]
-pub fn spl_token_create_account<'a>(params: TokenCreateAccount<'_, '_>) -> ProgramResult {
+pub fn spl_token_create_account(params: TokenCreateAccount<'_, '_>) -> ProgramResult {
     let TokenCreateAccount {
         payer,
         mint,
         account,
         authority,
-        authority_seeds,
+        account_seeds,
         token_program,
         system_program,
         rent,
     } = params;
-    let acct = &account.key.clone();
+
+    let (size, instruction) = if *token_program.key == spl_token_2022::id() {
+        let mint_data = mint.data.borrow();
+        let extensions = StateWithExtensions::<Mint>::unpack(&mint_data)?.get_extension_types()?;
+
+        (
+            ExtensionType::get_account_len::<Account>(
+                &ExtensionType::get_required_init_account_extensions(&extensions),
+            ),
+            spl_token_2022::instruction::initialize_account(token_program.key, account.key, mint.key, authority.key)?,
+        )
+    } else {
+        (
+            spl_token::state::Account::LEN,
+            spl_token::instruction::initialize_account(token_program.key, account.key, mint.key, authority.key)?,
+        )
+    };
 
     create_or_allocate_account_raw(
         *token_program.key,
@@ -124,28 +231,21 @@ pub fn spl_token_create_account<'a>(params: TokenCreateAccount<'_, '_>) -> Progr
         &rent,
         &system_program,
         &payer,
-        spl_token::state::Account::LEN,
-        authority_seeds,
+        size,
+        account_seeds,
     )?;
 
-    msg!("Created account {}", acct);
-    
-    invoke_signed(
-        &spl_token::instruction::initialize_account(
-            &spl_token::id(),
-            acct,
-            mint.key,
-            authority.key,
-        )?,
+    msg!("Created account {}", account.key);
+
+    invoke(
+        &instruction,
         &[
             account,
-            authority,
             mint,
-            token_program,
-            system_program,
+            authority,
             rent,
+            token_program,
         ],
-        &[authority_seeds],
     )?;
 
     Ok(())
@@ -164,8 +264,12 @@ pub struct TokenTransferParams<'a: 'b, 'b> {
   pub source: AccountInfo<'a>,
   /// destination
   pub destination: AccountInfo<'a>,
+  /// mint of both accounts
+  pub mint: AccountInfo<'a>,
   /// amount
   pub amount: u64,
+  /// decimals of the mint
+  pub decimals: u8,
   /// authority
   pub authority: AccountInfo<'a>,
   /// authority_signer_seeds
@@ -183,8 +287,8 @@ pub struct TokenCreateAccount<'a: 'b, 'b> {
   pub account: AccountInfo<'a>,
   /// authority
   pub authority: AccountInfo<'a>,
-  /// authority seeds
-  pub authority_seeds: &'b [&'b [u8]],
+  /// seeds of the new account address
+  pub account_seeds: &'b [&'b [u8]],
   /// token_program
   pub token_program: AccountInfo<'a>,
   pub system_program: AccountInfo<'a>,
@@ -0,0 +1,49 @@
+use solana_program::clock::UnixTimestamp;
+
+use crate::{
+  error::TokensellError,
+  state::MONTH_SECONDS,
+};
+
+/// Amount of `total` unlocked at `now` for a sale with the given `tge`.
+///
+/// `initial_perc` percent unlocks right after TGE, the rest unlocks linearly every
+/// month over `total_months`. Everything is unlocked once `total_months` have passed.
+/// Amounts are in target token base units. Percentages above 100 are treated as 100.
+pub fn vested_amount(
+  total: u64,
+  initial_perc: u64,
+  total_months: u64,
+  tge: UnixTimestamp,
+  now: UnixTimestamp,
+) -> Result<u64, TokensellError> {
+  if now <= tge {
+    return Ok(0);
+  }
+
+  let elapsed = now.checked_sub(tge).ok_or(TokensellError::Overflow)? as u64;
+  let months_passed = elapsed / MONTH_SECONDS;
+
+  if months_passed >= total_months {
+    return Ok(total);
+  }
+
+  let total = total as u128;
+
+  let initial_amount = total
+    .checked_mul(initial_perc.min(100) as u128)
+    .ok_or(TokensellError::Overflow)?
+    / 100;
+
+  let month_amount = total
+    .checked_sub(initial_amount)
+    .and_then(|locked| locked.checked_mul(months_passed as u128))
+    .ok_or(TokensellError::Overflow)?
+    / total_months as u128;
+
+  let vested = initial_amount
+    .checked_add(month_amount)
+    .ok_or(TokensellError::Overflow)?;
+
+  u64::try_from(vested).map_err(|_| TokensellError::Overflow)
+}
@@ -0,0 +1,209 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::{
+  account_info::AccountInfo,
+  clock::UnixTimestamp,
+  ed25519_program,
+  entrypoint::ProgramResult,
+  msg,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+  sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::{
+  error::TokensellError,
+  state::{Key, VoucherReceipt, PREFIX, VOUCHER_RECEIPT_SIZE, VOUCHER_SEED},
+  utils::create_or_allocate_account_raw,
+};
+
+/// Allocation of `wallet` in `sell`, signed off-chain by the sale's voucher signer. The
+/// signature covers the Borsh encoding of the voucher.
+#[repr(C)]
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
+pub struct Voucher {
+  pub wallet: Pubkey,
+  pub sell: Pubkey,
+  /// Target tokens the wallet can buy with the voucher, over all its deposits.
+  pub max_amount: u64,
+  pub expires_at: UnixTimestamp,
+  /// Each nonce is a separate allocation, spent amounts are tracked per nonce.
+  pub nonce: u64,
+}
+
+pub const VOUCHER_SIZE: usize = 32 + 32 + 8 + 8 + 8;
+
+/// Layout of ed25519 program instructions carrying a single signature: the signature count
+/// and a padding byte, the offsets, then the public key, signature and message they point to.
+pub const ED25519_OFFSETS_START: usize = 2;
+pub const ED25519_PUBKEY_START: usize = ED25519_OFFSETS_START + 14;
+pub const ED25519_SIGNATURE_START: usize = ED25519_PUBKEY_START + 32;
+pub const ED25519_MESSAGE_START: usize = ED25519_SIGNATURE_START + 64;
+/// Instruction index of offsets pointing into the ed25519 instruction itself.
+pub const ED25519_SELF_INDEX: u16 = u16::MAX;
+
+/// Receipt tracking what `wallet` spent of its voucher `nonce` in `sell`.
+pub fn find_voucher_receipt_address(program_id: &Pubkey, sell: &Pubkey, wallet: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    sell.as_ref(),
+    VOUCHER_SEED.as_bytes(),
+    wallet.as_ref(),
+    &nonce.to_le_bytes(),
+  ], program_id)
+}
+
+/// Data of an ed25519 program instruction verifying `signature` of `message` by `signer`.
+pub fn ed25519_instruction_data(signer: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Vec<u8> {
+  let offsets = [
+    ED25519_SIGNATURE_START as u16,
+    ED25519_SELF_INDEX,
+    ED25519_PUBKEY_START as u16,
+    ED25519_SELF_INDEX,
+    ED25519_MESSAGE_START as u16,
+    message.len() as u16,
+    ED25519_SELF_INDEX,
+  ];
+
+  let mut data = vec![1, 0];
+  data.extend(offsets.iter().flat_map(|offset| offset.to_le_bytes()));
+  data.extend_from_slice(signer.as_ref());
+  data.extend_from_slice(signature);
+  data.extend_from_slice(message);
+  data
+}
+
+fn read_u16(data: &[u8], offset: usize) -> usize {
+  u16::from_le_bytes([data[offset], data[offset + 1]]) as usize
+}
+
+/// Voucher signed by `signer` in the data of an ed25519 program instruction.
+///
+/// The ed25519 program already verified the signature, only the signer and the message it
+/// verified are checked here, so all offsets have to point into the instruction itself.
+pub fn parse_voucher(data: &[u8], signer: &Pubkey) -> Result<Voucher, TokensellError> {
+  if data.len() < ED25519_PUBKEY_START || data[0] != 1 {
+    return Err(TokensellError::InvalidVoucher);
+  }
+
+  let self_index = ED25519_SELF_INDEX as usize;
+
+  if read_u16(data, ED25519_OFFSETS_START + 2) != self_index
+    || read_u16(data, ED25519_OFFSETS_START + 6) != self_index
+    || read_u16(data, ED25519_OFFSETS_START + 12) != self_index
+  {
+    return Err(TokensellError::InvalidVoucher);
+  }
+
+  let pubkey_offset = read_u16(data, ED25519_OFFSETS_START + 4);
+  let message_offset = read_u16(data, ED25519_OFFSETS_START + 8);
+  let message_size = read_u16(data, ED25519_OFFSETS_START + 10);
+
+  let pubkey = data.get(pubkey_offset..pubkey_offset + 32).ok_or(TokensellError::InvalidVoucher)?;
+
+  if pubkey != signer.as_ref() || message_size != VOUCHER_SIZE {
+    return Err(TokensellError::InvalidVoucher);
+  }
+
+  let message = data.get(message_offset..message_offset + message_size).ok_or(TokensellError::InvalidVoucher)?;
+
+  Voucher::try_from_slice(message).map_err(|_| TokensellError::InvalidVoucher)
+}
+
+/// Voucher of `wallet` in `sell` signed by `signer`, from the ed25519 program instruction
+/// right before the current one. Fails once the voucher expired at `now`.
+pub fn read_voucher(
+  instructions_info: &AccountInfo,
+  signer: &Pubkey,
+  sell: &Pubkey,
+  wallet: &Pubkey,
+  now: UnixTimestamp,
+) -> Result<Voucher, ProgramError> {
+  let current = load_current_index_checked(instructions_info)? as usize;
+
+  if current == 0 {
+    return Err(TokensellError::VoucherRequired.into());
+  }
+
+  let instruction = load_instruction_at_checked(current - 1, instructions_info)?;
+
+  if instruction.program_id != ed25519_program::id() {
+    return Err(TokensellError::VoucherRequired.into());
+  }
+
+  let voucher = parse_voucher(&instruction.data, signer)
+    .inspect_err(|_| msg!("No voucher signed by {}", signer))?;
+
+  if voucher.sell != *sell || voucher.wallet != *wallet {
+    msg!("Voucher of {} in {}", voucher.wallet, voucher.sell);
+    return Err(TokensellError::InvalidVoucher.into());
+  }
+
+  if now > voucher.expires_at {
+    msg!("Voucher expired at {}", voucher.expires_at);
+    return Err(TokensellError::VoucherExpired.into());
+  }
+
+  Ok(voucher)
+}
+
+/// Adds `amount` to what was spent of `voucher`, creating its receipt at `payer_info`'s
+/// expense on first use. Fails beyond the voucher amount.
+pub fn redeem_voucher<'a>(
+  program_id: &Pubkey,
+  voucher: &Voucher,
+  receipt_info: &AccountInfo<'a>,
+  payer_info: &AccountInfo<'a>,
+  rent_sysvar_info: &AccountInfo<'a>,
+  system_program_info: &AccountInfo<'a>,
+  amount: u64,
+) -> ProgramResult {
+  let (receipt_key, bump) = find_voucher_receipt_address(program_id, &voucher.sell, &voucher.wallet, voucher.nonce);
+
+  if receipt_key != *receipt_info.key {
+    return Err(TokensellError::InvalidVoucherReceipt.into());
+  }
+
+  let mut receipt = if receipt_info.data_is_empty() {
+    create_or_allocate_account_raw(
+      *program_id,
+      receipt_info,
+      rent_sysvar_info,
+      system_program_info,
+      payer_info,
+      VOUCHER_RECEIPT_SIZE,
+      &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        voucher.sell.as_ref(),
+        VOUCHER_SEED.as_bytes(),
+        voucher.wallet.as_ref(),
+        &voucher.nonce.to_le_bytes(),
+        &[bump],
+      ],
+    )?;
+
+    VoucherReceipt {
+      key: Key::VoucherReceipt,
+      sell: voucher.sell,
+      wallet: voucher.wallet,
+      nonce: voucher.nonce,
+      spent: 0,
+    }
+  } else if *receipt_info.owner != *program_id {
+    return Err(TokensellError::InvalidVoucherReceipt.into());
+  } else {
+    VoucherReceipt::from_account_info(receipt_info)?
+  };
+
+  receipt.spent = receipt.spent
+    .checked_add(amount)
+    .ok_or(TokensellError::Overflow)?;
+
+  if receipt.spent > voucher.max_amount {
+    msg!("Voucher allows {}, {} spent", voucher.max_amount, receipt.spent);
+    return Err(TokensellError::VoucherExceeded.into());
+  }
+
+  receipt.save(receipt_info)
+}
@@ -0,0 +1,181 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{signature::{Keypair, Signer}, transport::TransportError};
+
+use live_tokensell::{client, error::TokensellError};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn funded_wallet(context: &mut ProgramTestContext) -> Keypair {
+  let wallet = Keypair::new();
+  fund(context, &wallet.pubkey(), 1_000_000_000).await;
+  wallet
+}
+
+/// Grant signed by the context payer as sale owner, `payer` paying for new positions.
+/// `wallet` signs revocable grants.
+async fn grant(
+  context: &mut ProgramTestContext,
+  payer: &Keypair,
+  test_sell: &TestSell,
+  wallet: &Keypair,
+  amount: u64,
+  revocable: bool,
+) -> Result<(), TransportError> {
+  let instruction = client::add_allocation(
+    &program_id(),
+    &payer.pubkey(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &wallet.pubkey(),
+    amount,
+    revocable,
+  );
+
+  if revocable {
+    process(context, &[instruction], &[payer, wallet]).await
+  } else {
+    process(context, &[instruction], &[payer]).await
+  }
+}
+
+#[tokio::test]
+async fn grant_vests_like_a_bought_position() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 0).await;
+  let payer = funded_wallet(&mut context).await;
+
+  grant(&mut context, &payer, &test_sell, &participant.wallet, 1_000, false).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!((data.amount, data.paid, data.revocable), (1_000, 0, false));
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.amount_left, 1_000_000 - 1_000);
+
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+  warp_to(&mut context, TGE + 1).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 100);
+}
+
+#[tokio::test]
+async fn grant_tops_up_a_bought_position() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let payer = funded_wallet(&mut context).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  grant(&mut context, &payer, &test_sell, &participant.wallet, 500, false).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!((data.amount, data.paid), (1_500, 2_000));
+
+  assert_error(
+    grant(&mut context, &payer, &test_sell, &participant.wallet, 500, true).await,
+    TokensellError::RevocableMismatch,
+  );
+}
+
+#[tokio::test]
+async fn revocable_grant_is_revoked() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let payer = funded_wallet(&mut context).await;
+  let wallet = Keypair::new();
+  let owner = context.payer.pubkey();
+  let treasury = create_token_account(&mut context, &test_sell.target_mint, &owner).await;
+
+  grant(&mut context, &payer, &test_sell, &wallet, 1_000, true).await.unwrap();
+
+  let instruction = client::revoke(
+    &program_id(),
+    &owner,
+    &wallet.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    &treasury,
+    &test_sell.target_program,
+  );
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &treasury).await, 1_000);
+}
+
+#[tokio::test]
+async fn only_sell_owner_grants() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let other = funded_wallet(&mut context).await;
+
+  let instruction = client::add_allocation(
+    &program_id(),
+    &other.pubkey(),
+    &other.pubkey(),
+    &test_sell.pubkey(),
+    &Pubkey::new_unique(),
+    1_000,
+    false,
+  );
+
+  assert_error(process(&mut context, &[instruction], &[&other]).await, TokensellError::NotSellOwner);
+}
+
+#[tokio::test]
+async fn grants_are_limited_to_what_is_left() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let payer = funded_wallet(&mut context).await;
+
+  assert_error(
+    grant(&mut context, &payer, &test_sell, &Keypair::new(), 1_000_001, false).await,
+    TokensellError::SoldOut,
+  );
+}
+
+#[tokio::test]
+async fn revocable_grant_needs_wallet_signature() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let payer = funded_wallet(&mut context).await;
+
+  let mut instruction = client::add_allocation(
+    &program_id(),
+    &payer.pubkey(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &Pubkey::new_unique(),
+    1_000,
+    true,
+  );
+  instruction.accounts[3].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[&payer]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn revocable_grant_takes_no_deposits() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let payer = funded_wallet(&mut context).await;
+
+  grant(&mut context, &payer, &test_sell, &participant.wallet, 1_000, true).await.unwrap();
+
+  // paid deposits would be revoked along with the grant
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(
+    participant.deposit(&mut context, &test_sell, 1_000).await,
+    TokensellError::RevocableDeposit,
+  );
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+
+use live_tokensell::{client, error::TokensellError};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn vesting_sale(context: &mut ProgramTestContext) -> (TestSell, Vec<TestParticipant>) {
+  let test_sell = TestSell::create(context, default_args()).await;
+  let mut participants = vec![];
+
+  warp_to(context, NOW + 200).await;
+
+  for amount in [1_000, 2_000, 3_000] {
+    let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+    participant.deposit(context, &test_sell, amount).await.unwrap();
+    participants.push(participant);
+  }
+
+  test_sell.set_tge(context, TGE).await.unwrap();
+
+  (test_sell, participants)
+}
+
+fn distribute_instruction(context: &ProgramTestContext, test_sell: &TestSell, participants: &[TestParticipant]) -> Instruction {
+  let recipients: Vec<(Pubkey, Pubkey)> = participants
+    .iter()
+    .map(|participant| (participant.pubkey(), participant.target_token))
+    .collect();
+
+  client::batch_distribute(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    &test_sell.target_program,
+    &recipients,
+    test_sell.claim_compliance_authority(),
+  )
+}
+
+#[tokio::test]
+async fn owner_distributes_vested_tokens() {
+  let mut context = start().await;
+  let (test_sell, participants) = vesting_sale(&mut context).await;
+
+  // the first participant already claimed this month
+  warp_to(&mut context, TGE + MONTH).await;
+  participants[0].claim(&mut context, &test_sell).await.unwrap();
+
+  let instruction = distribute_instruction(&context, &test_sell, &participants);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  for (participant, expected) in participants.iter().zip([190, 380, 570]) {
+    assert_eq!(token_balance(&mut context, &participant.target_token).await, expected);
+
+    let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+    assert_eq!(data.claimed, expected);
+  }
+
+  warp_to(&mut context, TGE + 10 * MONTH).await;
+  let instruction = distribute_instruction(&context, &test_sell, &participants);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  for (participant, expected) in participants.iter().zip([1_000, 2_000, 3_000]) {
+    assert_eq!(token_balance(&mut context, &participant.target_token).await, expected);
+  }
+}
+
+#[tokio::test]
+async fn distribute_requires_sale_owner() {
+  let mut context = start().await;
+  let (test_sell, participants) = vesting_sale(&mut context).await;
+  let stranger = Keypair::new();
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = distribute_instruction(&context, &test_sell, &participants);
+  instruction.accounts[0].pubkey = stranger.pubkey();
+
+  assert_error(process(&mut context, &[instruction], &[&stranger]).await, TokensellError::NotSellOwner);
+}
+
+#[tokio::test]
+async fn distribute_requires_participant_destination() {
+  let mut context = start().await;
+  let (test_sell, participants) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = distribute_instruction(&context, &test_sell, &participants);
+  instruction.accounts[8].pubkey = participants[1].target_token;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidDestinationOwner);
+}
+
+#[tokio::test]
+async fn distribute_rejects_unpaired_accounts() {
+  let mut context = start().await;
+  let (test_sell, participants) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = distribute_instruction(&context, &test_sell, &participants);
+  instruction.accounts.pop();
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidDistributionAccounts);
+}
+
+#[tokio::test]
+async fn distribute_rejects_participant_of_other_sale() {
+  let mut context = start().await;
+  let (test_sell, participants) = vesting_sale(&mut context).await;
+  let (other_sell, others) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = distribute_instruction(&context, &test_sell, &participants);
+  instruction.accounts[7].pubkey =
+    client::find_participant_address(&program_id(), &other_sell.pubkey(), &others[0].pubkey()).0;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidParticipantAddress);
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::signature::Signer;
+
+use live_tokensell::error::TokensellError;
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn vesting_sale(context: &mut ProgramTestContext) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create(context, default_args()).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+  participant.deposit(context, &test_sell, 1_000).await.unwrap();
+
+  test_sell.set_tge(context, TGE).await.unwrap();
+
+  (test_sell, participant)
+}
+
+#[tokio::test]
+async fn claims_follow_vesting_schedule() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::NothingToClaim);
+
+  // 10% unlocks right after TGE
+  warp_to(&mut context, TGE + 1).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 100);
+
+  warp_to(&mut context, TGE + MONTH - 1).await;
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::NothingToClaim);
+
+  // 100 + 900 * 3 / 10
+  warp_to(&mut context, TGE + 3 * MONTH).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 370);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.claimed, 370);
+
+  warp_to(&mut context, TGE + 10 * MONTH).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 1_000);
+  assert_eq!(token_balance(&mut context, &test_sell.vault).await, 999_000);
+
+  warp_to(&mut context, TGE + 20 * MONTH).await;
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::NothingToClaim);
+}
+
+#[tokio::test]
+async fn claim_without_tge_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  warp_to(&mut context, NOW + 100 * MONTH).await;
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::NothingToClaim);
+}
+
+#[tokio::test]
+async fn claim_without_deposit_fails() {
+  let mut context = start().await;
+  let (test_sell, _) = vesting_sale(&mut context).await;
+  let stranger = TestParticipant::create(&mut context, &test_sell, 0).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+  assert_error(stranger.claim(&mut context, &test_sell).await, TokensellError::SizeMismatch);
+}
+
+#[tokio::test]
+async fn claim_requires_signature() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = participant.claim_instruction(&test_sell);
+  instruction.accounts[0].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn claim_rejects_substituted_accounts() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+  let payer = context.payer.pubkey();
+  let other_vault = create_token_account(&mut context, &test_sell.target_mint, &payer).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  // target mint, sell authority, vault and participant
+  for (index, pubkey, error) in [
+    (2, test_sell.source_mint, TokensellError::InvalidTargetMint),
+    (3, Pubkey::new_unique(), TokensellError::InvalidSellAuthority),
+    (4, other_vault, TokensellError::InvalidVault),
+    (6, Pubkey::new_unique(), TokensellError::InvalidParticipantAddress),
+  ] {
+    let mut instruction = participant.claim_instruction(&test_sell);
+    instruction.accounts[index].pubkey = pubkey;
+
+    assert_error(process(&mut context, &[instruction], &[&participant.wallet]).await, error);
+  }
+}
+
+#[tokio::test]
+async fn claim_rejects_other_token_program() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = participant.claim_instruction(&test_sell);
+  instruction.accounts[5].pubkey = Pubkey::new_unique();
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidTokenProgram,
+  );
+}
+
+#[tokio::test]
+async fn claim_rejects_sell_not_owned_by_program() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = participant.claim_instruction(&test_sell);
+  instruction.accounts[1].pubkey = Pubkey::new_unique();
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::SellNotOwnedByProgram,
+  );
+}
+
+#[tokio::test]
+async fn claim_into_other_mint_fails() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+
+  warp_to(&mut context, TGE + MONTH).await;
+
+  let mut instruction = participant.claim_instruction(&test_sell);
+  instruction.accounts[7].pubkey = participant.source_token;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::DestinationMintMismatch,
+  );
+}
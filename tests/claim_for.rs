@@ -0,0 +1,161 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{
+  account::AccountSharedData,
+  signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use live_tokensell::{client, error::TokensellError, utils::associated_token_address};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn vesting_sale(context: &mut ProgramTestContext) -> (TestSell, TestParticipant) {
+  vesting_sale_with(context, MintConfig::classic()).await
+}
+
+async fn vesting_sale_with(context: &mut ProgramTestContext, target: MintConfig) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create_with(context, default_args(), target, MintConfig::classic()).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+  participant.deposit(context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(context, TGE).await.unwrap();
+
+  (test_sell, participant)
+}
+
+fn claim_for_instruction(cranker: &Keypair, owner: &Pubkey, test_sell: &TestSell) -> solana_program::instruction::Instruction {
+  client::claim_for(
+    &program_id(),
+    &cranker.pubkey(),
+    owner,
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    &test_sell.target_program,
+    test_sell.claim_compliance_authority(),
+  )
+}
+
+async fn cranker(context: &mut ProgramTestContext) -> Keypair {
+  let cranker = Keypair::new();
+  fund(context, &cranker.pubkey(), 1_000_000_000).await;
+  cranker
+}
+
+#[tokio::test]
+async fn cranker_creates_ata_and_claims() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+  let cranker = cranker(&mut context).await;
+  let ata = get_associated_token_address(&participant.pubkey(), &test_sell.target_mint);
+  let wallet_lamports = get_account(&mut context, &participant.pubkey()).await.unwrap().lamports;
+
+  warp_to(&mut context, TGE + 1).await;
+  let instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  process(&mut context, &[instruction], &[&cranker]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &ata).await, 100);
+  assert_eq!(get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await.claimed, 100);
+  assert_eq!(get_account(&mut context, &participant.pubkey()).await.unwrap().lamports, wallet_lamports);
+  assert!(get_account(&mut context, &cranker.pubkey()).await.unwrap().lamports < 1_000_000_000);
+}
+
+#[tokio::test]
+async fn cranker_claims_into_existing_ata() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+  let cranker = cranker(&mut context).await;
+  let ata = get_associated_token_address(&participant.pubkey(), &test_sell.target_mint);
+
+  warp_to(&mut context, TGE + 1).await;
+  let instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  process(&mut context, &[instruction], &[&cranker]).await.unwrap();
+
+  warp_to(&mut context, TGE + 3 * MONTH).await;
+  let instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  process(&mut context, &[instruction], &[&cranker]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &ata).await, 370);
+
+  // the participant's own claims continue from the crank
+  warp_to(&mut context, TGE + 10 * MONTH).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 630);
+}
+
+#[tokio::test]
+async fn claim_for_requires_participant_ata() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+  let cranker = cranker(&mut context).await;
+
+  warp_to(&mut context, TGE + 1).await;
+
+  let mut instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  instruction.accounts[8].pubkey = participant.target_token;
+
+  assert_error(process(&mut context, &[instruction], &[&cranker]).await, TokensellError::NotAssociatedTokenAccount);
+}
+
+#[tokio::test]
+async fn claim_for_rejects_other_associated_token_program() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+  let cranker = cranker(&mut context).await;
+
+  warp_to(&mut context, TGE + 1).await;
+
+  let mut instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  instruction.accounts[12].pubkey = Pubkey::new_unique();
+
+  assert_error(process(&mut context, &[instruction], &[&cranker]).await, TokensellError::InvalidAssociatedTokenProgram);
+}
+
+#[tokio::test]
+async fn claim_for_requires_cranker_signature() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale(&mut context).await;
+  let cranker = Keypair::new();
+
+  warp_to(&mut context, TGE + 1).await;
+
+  let mut instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  instruction.accounts[0].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn token_2022_claim_for_requires_existing_ata() {
+  let mut context = start().await;
+  let (test_sell, participant) = vesting_sale_with(&mut context, MintConfig::token_2022(None)).await;
+  let cranker = cranker(&mut context).await;
+  let ata = associated_token_address(&participant.pubkey(), &test_sell.target_mint, &spl_token_2022::id());
+
+  warp_to(&mut context, TGE + 1).await;
+  let instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  assert_error(
+    process(&mut context, &[instruction], &[&cranker]).await,
+    TokensellError::AssociatedTokenAccountRequired,
+  );
+
+  // the associated token program this program calls only creates classic token accounts,
+  // Token-2022 ATAs are created by the participant beforehand
+  let token_account = create_token_account(&mut context, &test_sell.target_mint, &participant.pubkey()).await;
+  let account = get_account(&mut context, &token_account).await.unwrap();
+  context.set_account(&ata, &AccountSharedData::from(account));
+
+  warp_to(&mut context, TGE + 2).await;
+  let instruction = claim_for_instruction(&cranker, &participant.pubkey(), &test_sell);
+  process(&mut context, &[instruction], &[&cranker]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &ata).await, 100);
+}
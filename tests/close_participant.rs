@@ -0,0 +1,126 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::signature::Signer;
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{Key, CLOSED_PARTICIPANT_SIZE},
+};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn claimed_sale(context: &mut ProgramTestContext) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create(context, default_args()).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+  participant.deposit(context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(context, TGE).await.unwrap();
+
+  warp_to(context, TGE + 10 * MONTH).await;
+  participant.claim(context, &test_sell).await.unwrap();
+
+  (test_sell, participant)
+}
+
+fn participant_address(test_sell: &TestSell, participant: &TestParticipant) -> Pubkey {
+  client::find_participant_address(&program_id(), &test_sell.pubkey(), &participant.pubkey()).0
+}
+
+#[tokio::test]
+async fn close_refunds_rent_and_leaves_tombstone() {
+  let mut context = start().await;
+  let (test_sell, participant) = claimed_sale(&mut context).await;
+  let address = participant_address(&test_sell, &participant);
+
+  let rent = context.banks_client.get_rent().await.unwrap();
+  let participant_lamports = get_account(&mut context, &address).await.unwrap().lamports;
+  let wallet_lamports = get_account(&mut context, &participant.pubkey()).await.unwrap().lamports;
+
+  let instruction = client::close_participant(&program_id(), &participant.pubkey(), &test_sell.pubkey());
+  process(&mut context, &[instruction], &[&participant.wallet]).await.unwrap();
+
+  let tombstone = get_account(&mut context, &address).await.unwrap();
+  assert_eq!(tombstone.data, vec![Key::ClosedParticipant as u8]);
+  assert_eq!(tombstone.lamports, rent.minimum_balance(CLOSED_PARTICIPANT_SIZE));
+
+  let refund = participant_lamports - tombstone.lamports;
+  assert_eq!(get_account(&mut context, &participant.pubkey()).await.unwrap().lamports, wallet_lamports + refund);
+}
+
+#[tokio::test]
+async fn closed_participant_cannot_rejoin() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    end_time: NOW + 100 * MONTH,
+    ..default_args()
+  }).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+
+  warp_to(&mut context, TGE + 10 * MONTH).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+
+  let instruction = client::close_participant(&program_id(), &participant.pubkey(), &test_sell.pubkey());
+  process(&mut context, &[instruction], &[&participant.wallet]).await.unwrap();
+
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokensellError::ParticipantClosed);
+
+  warp_to(&mut context, TGE + 11 * MONTH).await;
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::ParticipantClosed);
+}
+
+#[tokio::test]
+async fn close_requires_everything_claimed() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+
+  warp_to(&mut context, TGE + 3 * MONTH).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+
+  let instruction = client::close_participant(&program_id(), &participant.pubkey(), &test_sell.pubkey());
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::UnclaimedTokens,
+  );
+}
+
+#[tokio::test]
+async fn close_requires_owner_signature() {
+  let mut context = start().await;
+  let (test_sell, participant) = claimed_sale(&mut context).await;
+
+  let mut instruction = client::close_participant(&program_id(), &participant.pubkey(), &test_sell.pubkey());
+  instruction.accounts[0].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn close_rejects_other_participant() {
+  let mut context = start().await;
+  let (test_sell, participant) = claimed_sale(&mut context).await;
+  let payer = context.payer.pubkey();
+
+  // the payer tries to close the participant's account into its own wallet
+  let mut instruction = client::close_participant(&program_id(), &payer, &test_sell.pubkey());
+  instruction.accounts[2].pubkey = participant_address(&test_sell, &participant);
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidParticipantAddress);
+}
@@ -0,0 +1,249 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{signature::{Keypair, Signer}, transport::TransportError};
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{ComplianceGate, Key},
+};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn compliance_authority(context: &mut ProgramTestContext) -> Keypair {
+  let authority = Keypair::new();
+  fund(context, &authority.pubkey(), 1_000_000_000).await;
+  authority
+}
+
+async fn gated_sale(context: &mut ProgramTestContext, authority: &Keypair, gate_claims: bool) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create(context, InitSellArgs {
+    compliance: Some(ComplianceGate {
+      authority: authority.pubkey(),
+      gate_claims,
+    }),
+    ..default_args()
+  }).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+
+  (test_sell, participant)
+}
+
+async fn attest(
+  context: &mut ProgramTestContext,
+  authority: &Keypair,
+  wallet: &Pubkey,
+  expires_at: i64,
+) -> Result<(), TransportError> {
+  let instruction = client::issue_attestation(&program_id(), &authority.pubkey(), wallet, expires_at, Some(*b"DE"));
+  process(context, &[instruction], &[authority]).await
+}
+
+async fn revoke(context: &mut ProgramTestContext, authority: &Keypair, wallet: &Pubkey) -> Result<(), TransportError> {
+  let instruction = client::revoke_attestation(&program_id(), &authority.pubkey(), wallet);
+  process(context, &[instruction], &[authority]).await
+}
+
+fn distribute_instruction(context: &ProgramTestContext, test_sell: &TestSell, participant: &TestParticipant) -> Instruction {
+  client::batch_distribute(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    &test_sell.target_program,
+    &[(participant.pubkey(), participant.target_token)],
+    test_sell.claim_compliance_authority(),
+  )
+}
+
+#[tokio::test]
+async fn attested_wallet_deposits() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 1_000).await.unwrap();
+
+  let (address, _) = client::find_attestation_address(&program_id(), &authority.pubkey(), &participant.pubkey());
+  let account = get_account(&mut context, &address).await.unwrap();
+  let attestation = client::decode_attestation(&account.data).unwrap();
+  assert_eq!(attestation.key, Key::Attestation);
+  assert_eq!(attestation.wallet, participant.pubkey());
+  assert_eq!(attestation.jurisdiction, Some(*b"DE"));
+
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 2_000);
+}
+
+#[tokio::test]
+async fn deposit_needs_attestation() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+
+  // the attestation account doesn't exist yet
+  assert_error(
+    participant.deposit(&mut context, &test_sell, 1_000).await,
+    TokensellError::AttestationRequired,
+  );
+
+  // nor can it be left out
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts.pop();
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::AttestationRequired,
+  );
+}
+
+#[tokio::test]
+async fn attestation_of_other_authority_is_rejected() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let other = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+
+  attest(&mut context, &other, &participant.pubkey(), NOW + 1_000).await.unwrap();
+
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  let last = instruction.accounts.len() - 1;
+  instruction.accounts[last].pubkey = client::find_attestation_address(&program_id(), &other.pubkey(), &participant.pubkey()).0;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidAttestation,
+  );
+}
+
+#[tokio::test]
+async fn expired_attestation_is_rejected() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 300).await.unwrap();
+
+  warp_to(&mut context, NOW + 301).await;
+  assert_error(
+    participant.deposit(&mut context, &test_sell, 1_000).await,
+    TokensellError::AttestationExpired,
+  );
+}
+
+#[tokio::test]
+async fn revoked_attestation_is_rejected_until_reissued() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 1_000).await.unwrap();
+  revoke(&mut context, &authority, &participant.pubkey()).await.unwrap();
+
+  assert_error(
+    participant.deposit(&mut context, &test_sell, 1_000).await,
+    TokensellError::AttestationRevoked,
+  );
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 2_000).await.unwrap();
+  warp_to(&mut context, NOW + 300).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+}
+
+#[tokio::test]
+async fn only_issuer_revokes() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let other = compliance_authority(&mut context).await;
+  let wallet = Pubkey::new_unique();
+
+  attest(&mut context, &authority, &wallet, NOW + 1_000).await.unwrap();
+
+  let mut instruction = client::revoke_attestation(&program_id(), &other.pubkey(), &wallet);
+  instruction.accounts[1].pubkey = client::find_attestation_address(&program_id(), &authority.pubkey(), &wallet).0;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&other]).await,
+    TokensellError::InvalidAttestation,
+  );
+}
+
+#[tokio::test]
+async fn claims_are_gated_when_configured() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, true).await;
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 1_000).await.unwrap();
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+
+  warp_to(&mut context, TGE + 1).await;
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::AttestationExpired);
+
+  let instruction = distribute_instruction(&context, &test_sell, &participant);
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::AttestationExpired);
+
+  attest(&mut context, &authority, &participant.pubkey(), TGE + 1_000).await.unwrap();
+  warp_to(&mut context, TGE + 2).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 100);
+}
+
+#[tokio::test]
+async fn claims_stay_open_without_claim_gate() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 1_000).await.unwrap();
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+  revoke(&mut context, &authority, &participant.pubkey()).await.unwrap();
+
+  warp_to(&mut context, TGE + 1).await;
+  let instruction = distribute_instruction(&context, &test_sell, &participant);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 100);
+}
+
+#[tokio::test]
+async fn transfer_needs_recipient_attestation() {
+  let mut context = start().await;
+  let authority = compliance_authority(&mut context).await;
+  let (test_sell, participant) = gated_sale(&mut context, &authority, false).await;
+  let recipient = TestParticipant::create(&mut context, &test_sell, 0).await;
+
+  attest(&mut context, &authority, &participant.pubkey(), NOW + 1_000).await.unwrap();
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  let transfer = || client::transfer_position(
+    &program_id(),
+    &participant.pubkey(),
+    &recipient.pubkey(),
+    &test_sell.pubkey(),
+    Some(&authority.pubkey()),
+    None,
+  );
+  assert_error(
+    process(&mut context, &[transfer()], &[&participant.wallet]).await,
+    TokensellError::AttestationRequired,
+  );
+
+  attest(&mut context, &authority, &recipient.pubkey(), NOW + 1_000).await.unwrap();
+  warp_to(&mut context, NOW + 300).await;
+  process(&mut context, &[transfer()], &[&participant.wallet]).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &recipient.pubkey()).await;
+  assert_eq!(data.amount, 1_000);
+}
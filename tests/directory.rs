@@ -0,0 +1,161 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use borsh::BorshSerialize;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{
+  account::{Account, AccountSharedData},
+  signature::Signer,
+};
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{Directory, DirectoryPage, Key, SaleStatus, DIRECTORY_PAGE_CAPACITY, DIRECTORY_SIZE},
+};
+
+use utils::*;
+
+async fn get_page(context: &mut ProgramTestContext, page: u64) -> DirectoryPage {
+  let (address, _) = client::find_directory_page_address(&program_id(), page);
+  let account = get_account(context, &address).await.expect("directory page");
+  client::decode_directory_page(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn sales_are_numbered_per_owner() {
+  let mut context = start().await;
+  let payer = context.payer.pubkey();
+
+  let first = TestSell::create(&mut context, default_args()).await;
+  let second = TestSell::create(&mut context, default_args()).await;
+
+  assert_eq!(owner_sales(&mut context, &payer).await, 2);
+  assert_eq!(client::find_sell_address(&program_id(), &payer, 0).0, first.pubkey());
+  assert_eq!(client::find_sell_address(&program_id(), &payer, 1).0, second.pubkey());
+}
+
+#[tokio::test]
+async fn init_sell_lists_sale_in_directory() {
+  let mut context = start().await;
+  let first = TestSell::create(&mut context, default_args()).await;
+  let second = TestSell::create(&mut context, default_args()).await;
+  let args = default_args();
+
+  assert_eq!(directory_sales(&mut context).await, 2);
+  assert_eq!(first.directory_index(&mut context).await, Some(0));
+  assert_eq!(second.directory_index(&mut context).await, Some(1));
+
+  let page = get_page(&mut context, 0).await;
+  assert_eq!(page.key, Key::DirectoryPage);
+  assert_eq!(page.page, 0);
+  assert_eq!(page.entries.len(), 2);
+
+  let entry = &page.entries[1];
+  assert_eq!(entry.sell, second.pubkey());
+  assert_eq!(entry.owner, context.payer.pubkey());
+  assert_eq!(entry.status, SaleStatus::Pending);
+  assert_eq!(entry.start_time, args.start_time);
+  assert_eq!(entry.end_time, args.end_time);
+}
+
+#[tokio::test]
+async fn full_page_rolls_over_to_next_page() {
+  let mut context = start().await;
+  let sales = DIRECTORY_PAGE_CAPACITY as u64;
+
+  let (directory, _) = client::find_directory_address(&program_id());
+  let rent = context.banks_client.get_rent().await.unwrap();
+  let mut data = Directory { key: Key::Directory, sales }.try_to_vec().unwrap();
+  data.resize(DIRECTORY_SIZE, 0);
+  context.set_account(&directory, &AccountSharedData::from(Account {
+    lamports: rent.minimum_balance(DIRECTORY_SIZE),
+    data,
+    owner: program_id(),
+    executable: false,
+    rent_epoch: 0,
+  }));
+
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  assert_eq!(test_sell.directory_index(&mut context).await, Some(sales));
+  assert_eq!(directory_sales(&mut context).await, sales + 1);
+
+  let page = get_page(&mut context, 1).await;
+  assert_eq!(page.page, 1);
+  assert_eq!(page.entries.len(), 1);
+  assert_eq!(page.entries[0].sell, test_sell.pubkey());
+}
+
+#[tokio::test]
+async fn init_sell_rejects_skipped_sale_id() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let instruction = client::init_sell(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.source_mint,
+    &test_sell.target_acc,
+    &test_sell.target_program,
+    &test_sell.source_program,
+    0,
+    InitSellArgs {
+      sale_id: 1,
+      ..default_args()
+    },
+  );
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidSaleId);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_other_directory_page() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let instruction = client::init_sell(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.source_mint,
+    &test_sell.target_acc,
+    &test_sell.target_program,
+    &test_sell.source_program,
+    DIRECTORY_PAGE_CAPACITY as u64,
+    InitSellArgs {
+      sale_id: test_sell.sale_id,
+      ..default_args()
+    },
+  );
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidDirectoryAccount);
+}
+
+#[tokio::test]
+async fn applied_tge_updates_directory_status() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  test_sell.queue_tge(&mut context, NOW + 20_000).await.unwrap();
+  assert_eq!(get_page(&mut context, 0).await.entries[0].status, SaleStatus::Pending);
+
+  test_sell.set_tge(&mut context, NOW + 20_000).await.unwrap();
+  assert_eq!(get_page(&mut context, 0).await.entries[0].status, SaleStatus::TgeSet);
+}
+
+#[tokio::test]
+async fn apply_tge_rejects_other_directory_page() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  test_sell.queue_tge(&mut context, NOW + 20_000).await.unwrap();
+  warp_to(&mut context, NOW + TGE_POLICY.delay).await;
+
+  let instruction = client::apply_tge(&program_id(), &test_sell.pubkey(), Some(DIRECTORY_PAGE_CAPACITY as u64));
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidDirectoryAccount);
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "client")]
+
+use solana_program::program_error::ProgramError;
+
+use live_tokensell::{client, error::TokensellError};
+
+#[test]
+fn codes_are_stable() {
+  assert_eq!(TokensellError::TokenTransferFailed as u32, 1);
+  assert_eq!(TokensellError::InvalidAccount as u32, 4);
+  assert_eq!(TokensellError::PriceUncertain as u32, 28);
+  assert_eq!(TokensellError::SellNotOwnedByProgram as u32, 29);
+  assert_eq!(ProgramError::from(TokensellError::InvalidVault), ProgramError::Custom(36));
+}
+
+#[test]
+fn custom_codes_decode_to_errors() {
+  assert_eq!(client::decode_error(29), Some(TokensellError::SellNotOwnedByProgram));
+  assert_eq!(client::decode_error(TokensellError::InvalidTargetMint as u32), Some(TokensellError::InvalidTargetMint));
+  assert_eq!(client::decode_error(10_000), None);
+}
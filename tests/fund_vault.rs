@@ -0,0 +1,102 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+
+use live_tokensell::{client, error::TokensellError};
+
+use utils::*;
+
+async fn funding_account(context: &mut ProgramTestContext, test_sell: &TestSell, owner: &Pubkey, amount: u64) -> Pubkey {
+  let funding = create_token_account(context, &test_sell.target_mint, owner).await;
+  mint_to(context, &test_sell.target_mint, &funding, amount).await;
+  funding
+}
+
+fn fund_instruction(owner: &Pubkey, test_sell: &TestSell, funding: &Pubkey, amount: u64) -> Instruction {
+  client::fund_vault(
+    &program_id(),
+    owner,
+    &test_sell.pubkey(),
+    funding,
+    &test_sell.target_mint,
+    &test_sell.target_program,
+    amount,
+  )
+}
+
+#[tokio::test]
+async fn sale_is_created_and_funded_in_one_transaction() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+  let payer = context.payer.pubkey();
+  let funding = funding_account(&mut context, &test_sell, &payer, 1_000_000).await;
+
+  let instructions = [
+    test_sell.init_instruction(&mut context, default_args()).await,
+    fund_instruction(&payer, &test_sell, &funding, 1_000_000),
+  ];
+  process(&mut context, &instructions, &[]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &test_sell.vault).await, 1_000_000);
+  assert_eq!(token_balance(&mut context, &funding).await, 0);
+
+  test_sell.set_tge(&mut context, NOW + 20_000).await.unwrap();
+}
+
+#[tokio::test]
+async fn owner_tops_up_vault() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let payer = context.payer.pubkey();
+  let funding = funding_account(&mut context, &test_sell, &payer, 500).await;
+
+  let instruction = fund_instruction(&payer, &test_sell, &funding, 500);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &test_sell.vault).await, 1_000_500);
+}
+
+#[tokio::test]
+async fn fund_vault_requires_sale_owner() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let stranger = Keypair::new();
+  fund(&mut context, &stranger.pubkey(), 1_000_000_000).await;
+  let funding = funding_account(&mut context, &test_sell, &stranger.pubkey(), 500).await;
+
+  let instruction = fund_instruction(&stranger.pubkey(), &test_sell, &funding, 500);
+  assert_error(process(&mut context, &[instruction], &[&stranger]).await, TokensellError::NotSellOwner);
+
+  let mut instruction = fund_instruction(&stranger.pubkey(), &test_sell, &funding, 500);
+  instruction.accounts[0].is_signer = false;
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn fund_vault_rejects_other_vault() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let payer = context.payer.pubkey();
+  let funding = funding_account(&mut context, &test_sell, &payer, 500).await;
+  let other = create_token_account(&mut context, &test_sell.target_mint, &payer).await;
+
+  let mut instruction = fund_instruction(&payer, &test_sell, &funding, 500);
+  instruction.accounts[3].pubkey = other;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidVault);
+}
+
+#[tokio::test]
+async fn fund_vault_rejects_funding_of_other_mint() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let payer = context.payer.pubkey();
+
+  let instruction = fund_instruction(&payer, &test_sell, &test_sell.target_acc, 500);
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SourceMintMismatch);
+}
@@ -0,0 +1,230 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::{
+  instruction::{AccountMeta, Instruction},
+  program_pack::Pack,
+};
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+use borsh::BorshSerialize;
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{Key, TOKENSELL_SELL_SIZE},
+};
+
+use utils::*;
+
+#[tokio::test]
+async fn init_sell_records_sale() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  let args = default_args();
+
+  assert!(sell.key == Key::SellDataV2);
+  assert_eq!(sell.owner, context.payer.pubkey());
+  assert_eq!(sell.source_mint, test_sell.source_mint);
+  assert_eq!(sell.target_mint, Some(test_sell.target_mint));
+  assert_eq!(sell.source_vault, Some(test_sell.vault));
+  assert_eq!(sell.target_acc, test_sell.target_acc);
+  assert_eq!(sell.price_numerator, args.price_numerator);
+  assert_eq!(sell.price_denominator, args.price_denominator);
+  assert_eq!(sell.source_decimals, 6);
+  assert_eq!(sell.target_decimals, 0);
+  assert_eq!(sell.start_time, args.start_time);
+  assert_eq!(sell.end_time, args.end_time);
+  assert_eq!(sell.tge, None);
+  assert_eq!(sell.initial_perc, args.initial_perc);
+  assert_eq!(sell.total_months, args.total_months);
+  assert_eq!(sell.min_deposit, args.min_deposit);
+  assert_eq!(sell.amount_total, args.total_amount);
+  assert_eq!(sell.amount_left, args.total_amount);
+  assert!(!sell.transfers_disabled);
+}
+
+#[tokio::test]
+async fn init_sell_twice_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  let instruction = test_sell.init_instruction(&mut context, InitSellArgs {
+    min_deposit: 1,
+    ..default_args()
+  }).await;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::AccountInitialized);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_end_in_the_past() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    start_time: NOW - 200,
+    end_time: NOW - 100,
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidEndTimestamp);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_end_before_start() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    start_time: NOW + 500,
+    end_time: NOW + 400,
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidEndTimestamp);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_zero_price() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    price_denominator: 0,
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidPrice);
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    price_numerator: 0,
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidPrice);
+}
+
+#[tokio::test]
+async fn init_sell_creates_empty_vault() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+  let (sell_authority, _) = client::find_sell_authority(&program_id(), &test_sell.pubkey());
+
+  test_sell.init(&mut context, default_args()).await.unwrap();
+
+  let account = get_account(&mut context, &test_sell.vault).await.unwrap();
+  let vault = spl_token::state::Account::unpack(&account.data).unwrap();
+
+  assert_eq!(account.owner, spl_token::id());
+  assert_eq!(vault.owner, sell_authority);
+  assert_eq!(vault.mint, test_sell.target_mint);
+  assert_eq!(vault.amount, 0);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_vault_outside_pda() {
+  let mut context = start().await;
+  let mut test_sell = TestSell::prepare(&mut context, 0).await;
+
+  test_sell.vault = Keypair::new().pubkey();
+
+  let mut instruction = test_sell.init_instruction(&mut context, default_args()).await;
+  instruction.accounts[2].pubkey = test_sell.vault;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidVault);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_other_token_program() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let mut instruction = test_sell.init_instruction(&mut context, default_args()).await;
+  instruction.accounts[9].pubkey = spl_token_2022::id();
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidTokenProgram);
+}
+
+#[tokio::test]
+async fn init_sell_requires_payer_signature() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+  let stranger = Keypair::new().pubkey();
+
+  let mut instruction = test_sell.init_instruction(&mut context, default_args()).await;
+  instruction.accounts[0] = AccountMeta::new(stranger, false);
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_target_account_of_other_mint() {
+  let mut context = start().await;
+  let mut test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+  let payer = context.payer.pubkey();
+
+  test_sell.target_acc = create_token_account(&mut context, &test_sell.target_mint, &payer).await;
+
+  assert_error(test_sell.init(&mut context, default_args()).await, TokensellError::WrongMint);
+}
+
+#[tokio::test]
+async fn init_sell_creates_sell_account_at_pda() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+  let payer = context.payer.pubkey();
+
+  // lamports sent to the address beforehand don't block the sale
+  fund(&mut context, &test_sell.pubkey(), 1_000_000).await;
+  test_sell.init(&mut context, default_args()).await.unwrap();
+
+  let (address, _) = client::find_sell_address(&program_id(), &payer, test_sell.sale_id);
+  let account = get_account(&mut context, &address).await.unwrap();
+  let rent = context.banks_client.get_rent().await.unwrap();
+
+  assert_eq!(address, test_sell.pubkey());
+  assert_eq!(account.owner, program_id());
+  assert_eq!(account.data.len(), TOKENSELL_SELL_SIZE);
+  assert!(rent.is_exempt(account.lamports, account.data.len()));
+}
+
+#[tokio::test]
+async fn init_sell_rejects_sell_outside_pda() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let mut instruction = test_sell.init_instruction(&mut context, default_args()).await;
+  instruction.accounts[1].pubkey = Keypair::new().pubkey();
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidSellAddress);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_other_sale_id() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let mut instruction = test_sell.init_instruction(&mut context, default_args()).await;
+  let mut args = default_args();
+  args.sale_id = test_sell.sale_id + 1;
+  instruction.data = args.try_to_vec().unwrap();
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidSellAddress);
+}
+
+#[tokio::test]
+async fn unknown_instruction_fails() {
+  let mut context = start().await;
+
+  for data in [vec![], vec![42u8]] {
+    let instruction = Instruction::new_with_bytes(program_id(), &data, vec![]);
+    assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::UnknownInstruction);
+    warp_to(&mut context, NOW).await;
+  }
+}
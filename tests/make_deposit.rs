@@ -0,0 +1,280 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::{
+  account::AccountSharedData,
+  signature::{Keypair, Signer},
+};
+
+use spl_token::error::TokenError;
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{Key, TOKENSELL_SELL_SIZE},
+};
+
+use utils::*;
+
+#[tokio::test]
+async fn deposit_creates_participant_and_pays_owner() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert!(data.key == Key::ParticipantDataV2);
+  assert_eq!(data.owner, participant.pubkey());
+  assert_eq!(data.sell, test_sell.pubkey());
+  assert_eq!(data.amount, 1_000);
+  assert_eq!(data.claimed, 0);
+  assert_eq!(data.paid, 2_000);
+
+  assert_eq!(token_balance(&mut context, &participant.source_token).await, 8_000);
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 2_000);
+}
+
+#[tokio::test]
+async fn second_deposit_tops_up_position() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  // top-ups are not bound by min_deposit
+  warp_to(&mut context, NOW + 300).await;
+  participant.deposit(&mut context, &test_sell, 10).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 1_010);
+  assert_eq!(data.paid, 2_020);
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 2_020);
+}
+
+#[tokio::test]
+async fn deposit_payment_rounds_up() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    price_numerator: 1,
+    price_denominator: 3,
+    ..default_args()
+  }).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 1_000_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1).await.unwrap();
+
+  // a third of a 6 decimal token
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 333_334);
+}
+
+#[tokio::test]
+async fn deposit_before_start_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokensellError::SellNotStarted);
+}
+
+#[tokio::test]
+async fn deposit_after_end_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 10_001).await;
+
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokensellError::SellEnded);
+}
+
+#[tokio::test]
+async fn first_deposit_below_minimum_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  // 49 tokens at rate 2 is below the 100 minimum
+  assert_error(participant.deposit(&mut context, &test_sell, 49).await, TokensellError::MinimalDeposit);
+  participant.deposit(&mut context, &test_sell, 50).await.unwrap();
+}
+
+#[tokio::test]
+async fn deposit_without_funds_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 100).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  assert_token_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokenError::InsufficientFunds);
+}
+
+#[tokio::test]
+async fn deposit_from_other_mint_fails() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  // the participant's target token account holds the sold mint, not the payment one
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts[1].pubkey = participant.target_token;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::SourceMintMismatch,
+  );
+}
+
+#[tokio::test]
+async fn deposit_beyond_tokens_left_fails() {
+  let mut context = start().await;
+  // one source base unit per token, the participant can pay for any amount
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    price_numerator: 1,
+    min_deposit: 0,
+    ..default_args()
+  }).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, u64::MAX).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(participant.deposit(&mut context, &test_sell, u64::MAX).await, TokensellError::SoldOut);
+
+  participant.deposit(&mut context, &test_sell, 1).await.unwrap();
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.amount_left, 999_999);
+
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000_000).await, TokensellError::SoldOut);
+
+  participant.deposit(&mut context, &test_sell, 999_999).await.unwrap();
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.amount_left, 0);
+}
+
+#[tokio::test]
+async fn deposit_requires_payer_signature() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let stranger = Pubkey::new_unique();
+
+  warp_to(&mut context, NOW + 200).await;
+
+  let mut instruction = client::make_deposit(
+    &program_id(),
+    &stranger,
+    &test_sell.pubkey(),
+    &participant.source_token,
+    &test_sell.target_acc,
+    &participant.pubkey(),
+    &test_sell.source_mint,
+    &test_sell.source_program,
+    None,
+    None,
+    None,
+    None,
+    1_000,
+  );
+  instruction.accounts[0].is_signer = false;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::SignatureRequired,
+  );
+}
+
+#[tokio::test]
+async fn deposit_rejects_wrong_token_program() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts[5].pubkey = Pubkey::new_unique();
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidTokenProgram,
+  );
+}
+
+#[tokio::test]
+async fn deposit_rejects_sell_not_owned_by_program() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts[2].pubkey = Keypair::new().pubkey();
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::SellNotOwnedByProgram,
+  );
+}
+
+#[tokio::test]
+async fn deposit_rejects_uninitialized_sell() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let rent = context.banks_client.get_rent().await.unwrap();
+
+  context.set_account(
+    &test_sell.pubkey(),
+    &AccountSharedData::new(rent.minimum_balance(TOKENSELL_SELL_SIZE), TOKENSELL_SELL_SIZE, &program_id()),
+  );
+
+  warp_to(&mut context, NOW + 200).await;
+
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokensellError::NotSellAccount);
+}
+
+#[tokio::test]
+async fn deposit_rejects_wrong_target_account() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts[3].pubkey = participant.source_token;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidUSDTTargetAccount,
+  );
+}
+
+#[tokio::test]
+async fn deposit_rejects_wrong_participant_account() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts[6].pubkey = Pubkey::new_unique();
+
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidParticipantAddress,
+  );
+}
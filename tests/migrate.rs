@@ -0,0 +1,357 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{
+  account::{Account, AccountSharedData},
+  signature::{Keypair, Signer},
+};
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  state::{
+    Key,
+    ParticipantData,
+    RefundPolicy,
+    SellData,
+    SellDataV1,
+    TgePolicy,
+    DEFAULT_TGE_DELAY,
+    PARTICIPANT_SIZE,
+    TOKENSELL_SELL_SIZE,
+  },
+};
+
+use utils::*;
+
+/// Size of the account sales were created with before versioning.
+const LEGACY_SELL_SIZE: usize = 218;
+const LEGACY_PARTICIPANT_SIZE: usize = 105;
+
+async fn set_program_account(context: &mut ProgramTestContext, address: &Pubkey, mut data: Vec<u8>, size: usize) {
+  let rent = context.banks_client.get_rent().await.unwrap();
+  data.resize(size, 0);
+
+  context.set_account(address, &AccountSharedData::from(Account {
+    lamports: rent.minimum_balance(size),
+    data,
+    owner: program_id(),
+    executable: false,
+    rent_epoch: 0,
+  }));
+}
+
+async fn migrate(context: &mut ProgramTestContext, account: &Pubkey) -> Result<(), solana_sdk::transport::TransportError> {
+  let instruction = client::migrate(&program_id(), &context.payer.pubkey(), account);
+  process(context, &[instruction], &[]).await
+}
+
+/// Sales created before versioning used a token account of the sell authority as vault.
+async fn legacy_test_sell(context: &mut ProgramTestContext) -> TestSell {
+  let mut test_sell = TestSell::prepare(context, 0).await;
+  let (sell_authority, _) = client::find_sell_authority(&program_id(), &test_sell.pubkey());
+
+  test_sell.vault = create_token_account(context, &test_sell.target_mint, &sell_authority).await;
+  mint_to(context, &test_sell.target_mint, &test_sell.vault, 1_000_000).await;
+
+  test_sell
+}
+
+fn legacy_sell(context: &ProgramTestContext, test_sell: &TestSell) -> SellDataV1 {
+  let args = default_args();
+
+  SellDataV1 {
+    key: Key::SellDataV1,
+    owner: context.payer.pubkey(),
+    source_mint: test_sell.source_mint,
+    target_mint: None,
+    source_vault: None,
+    target_acc: test_sell.target_acc,
+    exchange_rate: 2,
+    start_time: args.start_time,
+    end_time: args.end_time,
+    tge: None,
+    initial_perc: args.initial_perc,
+    total_months: args.total_months,
+    min_deposit: args.min_deposit,
+    amount_total: args.total_amount,
+    amount_left: args.total_amount,
+  }
+}
+
+fn legacy_sell_migration(context: &ProgramTestContext, test_sell: &TestSell, vault: &Pubkey) -> solana_program::instruction::Instruction {
+  client::migrate_legacy_sell(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.source_mint,
+    &test_sell.target_mint,
+    vault,
+  )
+}
+
+fn legacy_participant_migration(
+  context: &ProgramTestContext,
+  test_sell: &TestSell,
+  participant: &TestParticipant,
+  claimed: u64,
+) -> solana_program::instruction::Instruction {
+  let (address, _) = client::find_participant_address(&program_id(), &test_sell.pubkey(), &participant.pubkey());
+  client::migrate_legacy_participant(&program_id(), &context.payer.pubkey(), &address, &test_sell.pubkey(), &participant.pubkey(), claimed)
+}
+
+#[tokio::test]
+async fn legacy_sell_is_migrated_by_owner() {
+  let mut context = start().await;
+  let test_sell = legacy_test_sell(&mut context).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  let legacy = legacy_sell(&context, &test_sell).try_to_vec().unwrap();
+  set_program_account(&mut context, &test_sell.pubkey(), legacy, LEGACY_SELL_SIZE).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokensellError::MigrationRequired);
+
+  let instruction = legacy_sell_migration(&context, &test_sell, &test_sell.vault);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  let account = get_account(&mut context, &test_sell.pubkey()).await.unwrap();
+  assert_eq!(account.data.len(), TOKENSELL_SELL_SIZE);
+
+  let args = default_args();
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await, SellData {
+    key: Key::SellDataV2,
+    owner: context.payer.pubkey(),
+    source_mint: test_sell.source_mint,
+    target_mint: Some(test_sell.target_mint),
+    source_vault: Some(test_sell.vault),
+    target_acc: test_sell.target_acc,
+    price_numerator: args.price_numerator,
+    price_denominator: args.price_denominator,
+    source_decimals: 6,
+    target_decimals: 0,
+    start_time: args.start_time,
+    end_time: args.end_time,
+    tge: None,
+    initial_perc: args.initial_perc,
+    total_months: args.total_months,
+    min_deposit: args.min_deposit,
+    amount_total: args.total_amount,
+    amount_left: args.total_amount,
+    transfers_disabled: false,
+    directory_index: None,
+    price_oracle: None,
+    refund_policy: RefundPolicy::None,
+    compliance: None,
+    voucher_signer: None,
+    staking: None,
+    tge_policy: None,
+    pending_tge: None,
+    price_oracle_program: None,
+  });
+
+  // the migrated price charges what the legacy exchange rate did
+  warp_to(&mut context, NOW + 300).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 2_000);
+}
+
+#[tokio::test]
+async fn migrated_sale_takes_policy_around_its_first_tge() {
+  let mut context = start().await;
+  let test_sell = legacy_test_sell(&mut context).await;
+  let tge = NOW + 2 * DEFAULT_TGE_DELAY;
+
+  let legacy = legacy_sell(&context, &test_sell).try_to_vec().unwrap();
+  set_program_account(&mut context, &test_sell.pubkey(), legacy, LEGACY_SELL_SIZE).await;
+
+  let instruction = legacy_sell_migration(&context, &test_sell, &test_sell.vault);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  test_sell.queue_tge(&mut context, tge).await.unwrap();
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.tge_policy, Some(TgePolicy::around(tge)));
+  assert_eq!(sell.pending_tge.unwrap().eta, NOW + DEFAULT_TGE_DELAY);
+}
+
+#[tokio::test]
+async fn legacy_sell_migration_requires_owner() {
+  let mut context = start().await;
+  let test_sell = legacy_test_sell(&mut context).await;
+  let stranger = Keypair::new();
+  fund(&mut context, &stranger.pubkey(), 1_000_000_000).await;
+
+  let legacy = legacy_sell(&context, &test_sell).try_to_vec().unwrap();
+  set_program_account(&mut context, &test_sell.pubkey(), legacy, LEGACY_SELL_SIZE).await;
+
+  let instruction = client::migrate_legacy_sell(
+    &program_id(),
+    &stranger.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.source_mint,
+    &test_sell.target_mint,
+    &test_sell.vault,
+  );
+
+  assert_error(process(&mut context, &[instruction], &[&stranger]).await, TokensellError::NotSellOwner);
+}
+
+#[tokio::test]
+async fn legacy_sell_migration_checks_vault() {
+  let mut context = start().await;
+  let test_sell = legacy_test_sell(&mut context).await;
+  let payer = context.payer.pubkey();
+
+  let legacy = legacy_sell(&context, &test_sell).try_to_vec().unwrap();
+  set_program_account(&mut context, &test_sell.pubkey(), legacy, LEGACY_SELL_SIZE).await;
+
+  let other_vault = create_token_account(&mut context, &test_sell.target_mint, &payer).await;
+  let instruction = legacy_sell_migration(&context, &test_sell, &other_vault);
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidVaultOwner);
+}
+
+#[tokio::test]
+async fn legacy_participant_is_migrated() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let (address, _) = client::find_participant_address(&program_id(), &test_sell.pubkey(), &participant.pubkey());
+
+  let legacy = ParticipantData {
+    key: Key::ParticipantDataV1,
+    owner: participant.pubkey(),
+    sell: test_sell.pubkey(),
+    amount: 1_000,
+    claimed: 0,
+    paid: 0,
+    last_deposit_at: 0,
+    revocable: false,
+    revoked_at: None,
+    refundable: vec![],
+  };
+  set_program_account(&mut context, &address, legacy.try_to_vec().unwrap(), LEGACY_PARTICIPANT_SIZE).await;
+
+  test_sell.set_tge(&mut context, NOW + 1_000).await.unwrap();
+  warp_to(&mut context, NOW + 2_000).await;
+  assert_error(participant.claim(&mut context, &test_sell).await, TokensellError::MigrationRequired);
+
+  // legacy claims never recorded what they paid out, the owner and participant reconcile it
+  let instruction = legacy_participant_migration(&context, &test_sell, &participant, 40);
+  process(&mut context, &[instruction], &[&participant.wallet]).await.unwrap();
+
+  let account = get_account(&mut context, &address).await.unwrap();
+  assert_eq!(account.data.len(), PARTICIPANT_SIZE);
+  assert_eq!(get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await, ParticipantData {
+    key: Key::ParticipantDataV2,
+    claimed: 40,
+    ..legacy
+  });
+
+  warp_to(&mut context, NOW + 3_000).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 60);
+}
+
+#[tokio::test]
+async fn legacy_participant_migration_requires_owner_participant_and_reconciled_claims() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let (address, _) = client::find_participant_address(&program_id(), &test_sell.pubkey(), &participant.pubkey());
+  let stranger = Keypair::new();
+  fund(&mut context, &stranger.pubkey(), 1_000_000_000).await;
+
+  let legacy = ParticipantData {
+    key: Key::ParticipantDataV1,
+    owner: participant.pubkey(),
+    sell: test_sell.pubkey(),
+    amount: 1_000,
+    claimed: 0,
+    paid: 0,
+    last_deposit_at: 0,
+    revocable: false,
+    revoked_at: None,
+    refundable: vec![],
+  };
+  set_program_account(&mut context, &address, legacy.try_to_vec().unwrap(), LEGACY_PARTICIPANT_SIZE).await;
+
+  assert!(migrate(&mut context, &address).await.is_err());
+
+  let instruction = client::migrate_legacy_participant(
+    &program_id(),
+    &stranger.pubkey(),
+    &address,
+    &test_sell.pubkey(),
+    &participant.pubkey(),
+    0,
+  );
+  assert_error(process(&mut context, &[instruction], &[&stranger, &participant.wallet]).await, TokensellError::NotSellOwner);
+
+  // the owner can't settle claims on the participant's behalf
+  let mut instruction = legacy_participant_migration(&context, &test_sell, &participant, 1_000);
+  instruction.accounts[5].is_signer = false;
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+
+  let instruction = client::migrate_legacy_participant(
+    &program_id(),
+    &context.payer.pubkey(),
+    &address,
+    &test_sell.pubkey(),
+    &stranger.pubkey(),
+    1_000,
+  );
+  assert_error(process(&mut context, &[instruction], &[&stranger]).await, TokensellError::InvalidParticipantAddress);
+
+  let instruction = legacy_participant_migration(&context, &test_sell, &participant, 1_001);
+  assert_error(process(&mut context, &[instruction], &[&participant.wallet]).await, TokensellError::InvalidClaimedAmount);
+}
+
+#[tokio::test]
+async fn short_account_is_grown() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  // a sale written before the trailing fields of the latest layout existed
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  let data = sell.try_to_vec().unwrap();
+  let size = data.len();
+  set_program_account(&mut context, &test_sell.pubkey(), data, size).await;
+
+  assert_error(test_sell.set_tge(&mut context, NOW + 20_000).await, TokensellError::MigrationRequired);
+
+  migrate(&mut context, &test_sell.pubkey()).await.unwrap();
+
+  let account = get_account(&mut context, &test_sell.pubkey()).await.unwrap();
+  assert_eq!(account.data.len(), TOKENSELL_SELL_SIZE);
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await, sell);
+
+  warp_to(&mut context, NOW).await;
+  test_sell.set_tge(&mut context, NOW + 20_000).await.unwrap();
+}
+
+#[tokio::test]
+async fn migrating_latest_layout_is_a_no_op() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let before = get_account(&mut context, &test_sell.pubkey()).await.unwrap();
+
+  migrate(&mut context, &test_sell.pubkey()).await.unwrap();
+
+  assert_eq!(get_account(&mut context, &test_sell.pubkey()).await.unwrap(), before);
+}
+
+#[tokio::test]
+async fn migrate_rejects_foreign_accounts() {
+  let mut context = start().await;
+  let test_sell = legacy_test_sell(&mut context).await;
+  set_program_account(&mut context, &test_sell.pubkey(), vec![0; TOKENSELL_SELL_SIZE], TOKENSELL_SELL_SIZE).await;
+
+  assert_error(migrate(&mut context, &test_sell.vault).await, TokensellError::NotProgramAccount);
+  assert_error(migrate(&mut context, &test_sell.pubkey()).await, TokensellError::NotMigratable);
+}
@@ -0,0 +1,196 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{signature::{Keypair, Signer}, transport::TransportError};
+
+use live_tokensell::{client, error::TokensellError, state::Key};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+/// 2 of 3 multisig owning a sale.
+struct TestMultisig {
+  address: Pubkey,
+  authority: Pubkey,
+  signers: Vec<Keypair>,
+}
+
+async fn multisig_sale(context: &mut ProgramTestContext) -> (TestSell, TestMultisig) {
+  let test_sell = TestSell::create(context, default_args()).await;
+
+  let mut signers = vec![];
+  for _ in 0..3 {
+    let signer = Keypair::new();
+    fund(context, &signer.pubkey(), 1_000_000_000).await;
+    signers.push(signer);
+  }
+
+  let create_key = Keypair::new();
+  let keys: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+  let instruction = client::create_multisig(&program_id(), &context.payer.pubkey(), &create_key.pubkey(), 2, &keys);
+  process(context, &[instruction], &[&create_key]).await.unwrap();
+
+  let (address, _) = client::find_multisig_address(&program_id(), &create_key.pubkey());
+  let (authority, _) = client::find_multisig_authority(&program_id(), &address);
+
+  let instruction = client::set_owner(&program_id(), &context.payer.pubkey(), &test_sell.pubkey(), &authority);
+  process(context, &[instruction], &[]).await.unwrap();
+
+  (test_sell, TestMultisig { address, authority, signers })
+}
+
+fn set_tge_instruction(test_sell: &TestSell, owner: &Pubkey) -> Instruction {
+  client::set_tge(&program_id(), owner, &test_sell.pubkey(), &test_sell.target_mint, &test_sell.vault, TGE)
+}
+
+async fn propose(
+  context: &mut ProgramTestContext,
+  multisig: &TestMultisig,
+  proposer: &Keypair,
+  index: u64,
+  proposed: &Instruction,
+) -> Result<(), TransportError> {
+  let instruction = client::create_proposal(&program_id(), &proposer.pubkey(), &multisig.address, index, proposed);
+  process(context, &[instruction], &[proposer]).await
+}
+
+async fn approve(context: &mut ProgramTestContext, multisig: &TestMultisig, signer: &Keypair, index: u64) -> Result<(), TransportError> {
+  let instruction = client::approve_proposal(&program_id(), &signer.pubkey(), &multisig.address, index);
+  process(context, &[instruction], &[signer]).await
+}
+
+async fn execute(
+  context: &mut ProgramTestContext,
+  multisig: &TestMultisig,
+  index: u64,
+  proposed: &Instruction,
+  signers: &[&Keypair],
+) -> Result<(), TransportError> {
+  let instruction = client::execute_proposal(&program_id(), &multisig.address, index, proposed);
+  process(context, &[instruction], signers).await
+}
+
+#[tokio::test]
+async fn tge_is_set_once_threshold_is_reached() {
+  let mut context = start().await;
+  let (test_sell, multisig) = multisig_sale(&mut context).await;
+  let proposed = set_tge_instruction(&test_sell, &multisig.authority);
+
+  propose(&mut context, &multisig, &multisig.signers[0], 0, &proposed).await.unwrap();
+  assert_error(execute(&mut context, &multisig, 0, &proposed, &[]).await, TokensellError::ThresholdNotReached);
+
+  approve(&mut context, &multisig, &multisig.signers[2], 0).await.unwrap();
+  warp_to(&mut context, NOW + 100).await;
+  execute(&mut context, &multisig, 0, &proposed, &[]).await.unwrap();
+
+  let pending = get_sell(&mut context, &test_sell.pubkey()).await.pending_tge.unwrap();
+  assert_eq!(pending.tge, TGE);
+
+  let (address, _) = client::find_proposal_address(&program_id(), &multisig.address, 0);
+  let proposal = client::decode_proposal(&get_account(&mut context, &address).await.unwrap().data).unwrap();
+  assert_eq!(proposal.key, Key::Proposal);
+  assert_eq!((proposal.approval_count(), proposal.executed), (2, true));
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(execute(&mut context, &multisig, 0, &proposed, &[]).await, TokensellError::ProposalExecuted);
+
+  warp_to(&mut context, pending.eta).await;
+  test_sell.apply_tge(&mut context).await.unwrap();
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.tge, Some(TGE));
+}
+
+#[tokio::test]
+async fn previous_owner_loses_control() {
+  let mut context = start().await;
+  let (test_sell, _) = multisig_sale(&mut context).await;
+
+  assert_error(test_sell.set_tge(&mut context, TGE).await, TokensellError::NotSellOwner);
+}
+
+#[tokio::test]
+async fn only_signers_propose_and_approve_once() {
+  let mut context = start().await;
+  let (test_sell, multisig) = multisig_sale(&mut context).await;
+  let proposed = set_tge_instruction(&test_sell, &multisig.authority);
+  let outsider = Keypair::new();
+  fund(&mut context, &outsider.pubkey(), 1_000_000_000).await;
+
+  assert_error(
+    propose(&mut context, &multisig, &outsider, 0, &proposed).await,
+    TokensellError::NotMultisigSigner,
+  );
+
+  propose(&mut context, &multisig, &multisig.signers[1], 0, &proposed).await.unwrap();
+
+  assert_error(approve(&mut context, &multisig, &outsider, 0).await, TokensellError::NotMultisigSigner);
+  assert_error(approve(&mut context, &multisig, &multisig.signers[1], 0).await, TokensellError::AlreadyApproved);
+}
+
+#[tokio::test]
+async fn executed_accounts_have_to_match_the_proposal() {
+  let mut context = start().await;
+  let (test_sell, multisig) = multisig_sale(&mut context).await;
+  let proposed = set_tge_instruction(&test_sell, &multisig.authority);
+
+  propose(&mut context, &multisig, &multisig.signers[0], 0, &proposed).await.unwrap();
+  approve(&mut context, &multisig, &multisig.signers[1], 0).await.unwrap();
+
+  let mut instruction = client::execute_proposal(&program_id(), &multisig.address, 0, &proposed);
+  let last = instruction.accounts.len() - 1;
+  instruction.accounts[last].pubkey = Pubkey::new_unique();
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidProposalAccounts);
+}
+
+#[tokio::test]
+async fn executor_pays_for_proposed_allocation() {
+  let mut context = start().await;
+  let (test_sell, multisig) = multisig_sale(&mut context).await;
+  let executor = &multisig.signers[2];
+  let wallet = Pubkey::new_unique();
+
+  let proposed = client::add_allocation(
+    &program_id(),
+    &executor.pubkey(),
+    &multisig.authority,
+    &test_sell.pubkey(),
+    &wallet,
+    1_000,
+    false,
+  );
+
+  propose(&mut context, &multisig, &multisig.signers[0], 0, &proposed).await.unwrap();
+  approve(&mut context, &multisig, &multisig.signers[1], 0).await.unwrap();
+  execute(&mut context, &multisig, 0, &proposed, &[executor]).await.unwrap();
+
+  assert_eq!(get_participant(&mut context, &test_sell.pubkey(), &wallet).await.amount, 1_000);
+}
+
+#[tokio::test]
+async fn threshold_has_to_be_reachable() {
+  let mut context = start().await;
+  let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+  for (threshold, signers) in [(0, &signers[..]), (3, &signers[..]), (1, &[signers[0], signers[0]][..])] {
+    let create_key = Keypair::new();
+    let instruction = client::create_multisig(&program_id(), &context.payer.pubkey(), &create_key.pubkey(), threshold, signers);
+    assert_error(process(&mut context, &[instruction], &[&create_key]).await, TokensellError::InvalidMultisig);
+  }
+}
+
+#[tokio::test]
+async fn create_key_has_to_sign() {
+  let mut context = start().await;
+  let create_key = Pubkey::new_unique();
+
+  // anyone watching a pending creation could otherwise take its address first
+  let mut instruction = client::create_multisig(&program_id(), &context.payer.pubkey(), &create_key, 1, &[Pubkey::new_unique()]);
+  instruction.accounts[1].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+  assert!(get_account(&mut context, &client::find_multisig_address(&program_id(), &create_key).0).await.is_none());
+}
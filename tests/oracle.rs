@@ -0,0 +1,315 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+
+use live_tokensell::{
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  oracle::{
+    oracle_price,
+    read_price,
+    OraclePrice,
+    PYTH_AGG_CONF_OFFSET,
+    PYTH_AGG_PRICE_OFFSET,
+    PYTH_AGG_STATUS_OFFSET,
+    PYTH_EXPO_OFFSET,
+    PYTH_MAGIC,
+    PYTH_PRICE_ACCOUNT,
+    PYTH_PROGRAM_IDS,
+    PYTH_STATUS_TRADING,
+    PYTH_TIMESTAMP_OFFSET,
+    PYTH_VERSION,
+  },
+  state::PriceOracle,
+};
+
+use utils::*;
+
+/// Size of a Pyth v2 price account with its 32 publisher components.
+const PYTH_PRICE_SIZE: usize = 3312;
+
+/// Pyth v2 price account holding an aggregate price of `price * 10^expo`.
+fn pyth_price(price: i64, conf: u64, expo: i32, timestamp: i64) -> Vec<u8> {
+  let mut data = vec![0; PYTH_PRICE_SIZE];
+
+  data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+  data[4..8].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+  data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT.to_le_bytes());
+  data[12..16].copy_from_slice(&(PYTH_PRICE_SIZE as u32).to_le_bytes());
+  data[PYTH_EXPO_OFFSET..PYTH_EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+  data[PYTH_TIMESTAMP_OFFSET..PYTH_TIMESTAMP_OFFSET + 8].copy_from_slice(&timestamp.to_le_bytes());
+  data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+  data[PYTH_AGG_CONF_OFFSET..PYTH_AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+  data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4].copy_from_slice(&PYTH_STATUS_TRADING.to_le_bytes());
+
+  data
+}
+
+fn set_feed(context: &mut ProgramTestContext, feed: &Pubkey, data: Vec<u8>) {
+  set_feed_owned_by(context, feed, data, PYTH_PROGRAM_IDS[0]);
+}
+
+fn set_feed_owned_by(context: &mut ProgramTestContext, feed: &Pubkey, data: Vec<u8>, owner: Pubkey) {
+  context.set_account(feed, &AccountSharedData::from(Account {
+    lamports: 1_000_000_000,
+    data,
+    owner,
+    executable: false,
+    rent_epoch: 0,
+  }));
+}
+
+fn oracle(feed: Pubkey) -> PriceOracle {
+  PriceOracle {
+    feed,
+    max_age: 60,
+    max_confidence_bps: 100,
+  }
+}
+
+fn feed_price(price: i64, conf: u64, expo: i32, timestamp: i64) -> OraclePrice {
+  read_price(&pyth_price(price, conf, expo, timestamp)).unwrap()
+}
+
+/// Sale of 0 decimal tokens at 2 USD each, paid with a 6 decimal token quoted at 0.5 USD.
+async fn usd_sale(context: &mut ProgramTestContext) -> TestSell {
+  let feed = Pubkey::new_unique();
+  set_feed(context, &feed, pyth_price(50_000_000, 10_000, -8, NOW));
+
+  let mut test_sell = TestSell::create(context, InitSellArgs {
+    price_numerator: 2,
+    price_denominator: 1,
+    start_time: NOW,
+    price_oracle: Some(oracle(feed)),
+    ..default_args()
+  }).await;
+  test_sell.price_feed = Some(feed);
+
+  test_sell
+}
+
+#[test]
+fn reads_pyth_aggregate_price() {
+  let price = feed_price(15_000_000_000, 5_000_000, -8, NOW);
+
+  assert_eq!(price, OraclePrice {
+    price: 15_000_000_000,
+    conf: 5_000_000,
+    expo: -8,
+    timestamp: NOW,
+    trading: true,
+  });
+}
+
+#[test]
+fn rejects_other_accounts() {
+  let mut data = pyth_price(1, 0, 0, NOW);
+  data[8..12].copy_from_slice(&2u32.to_le_bytes());
+  assert_eq!(read_price(&data), Err(TokensellError::InvalidPriceFeed));
+
+  let mut data = pyth_price(1, 0, 0, NOW);
+  data[0] ^= 1;
+  assert_eq!(read_price(&data), Err(TokensellError::InvalidPriceFeed));
+
+  assert_eq!(read_price(&pyth_price(1, 0, 0, NOW)[..200]), Err(TokensellError::InvalidPriceFeed));
+}
+
+#[test]
+fn usd_price_is_divided_by_feed_price() {
+  // 0.035 USD per token, paid with a token at 150 USD
+  let price = feed_price(15_000_000_000, 0, -8, NOW);
+  assert_eq!(oracle_price(&oracle(Pubkey::new_unique()), &price, NOW, 35, 1_000), Ok((7, 30_000)));
+
+  // positive exponents scale the denominator
+  let price = feed_price(3, 0, 2, NOW);
+  assert_eq!(oracle_price(&oracle(Pubkey::new_unique()), &price, NOW, 600, 1), Ok((2, 1)));
+}
+
+#[test]
+fn scaled_price_is_reduced_before_narrowing() {
+  // 10^-12 USD per token, 10^12 * 0.5 * 10^8 overflows u64 unless reduced
+  let price = feed_price(50_000_000, 0, -8, NOW);
+  assert_eq!(oracle_price(&oracle(Pubkey::new_unique()), &price, NOW, 1, 1_000_000_000_000), Ok((1, 500_000_000_000)));
+
+  let price = feed_price(i64::MAX, 0, -8, NOW);
+  assert_eq!(
+    oracle_price(&oracle(Pubkey::new_unique()), &price, NOW, 3, u64::MAX),
+    Err(TokensellError::Overflow),
+  );
+}
+
+#[test]
+fn stale_or_halted_feeds_are_rejected() {
+  let oracle = oracle(Pubkey::new_unique());
+
+  let price = feed_price(100, 0, 0, NOW - 60);
+  assert!(oracle_price(&oracle, &price, NOW, 1, 1).is_ok());
+  assert_eq!(oracle_price(&oracle, &price, NOW + 1, 1, 1), Err(TokensellError::StalePrice));
+
+  // a feed slightly ahead of the cluster clock is fresh
+  let price = feed_price(100, 0, 0, NOW + 5);
+  assert!(oracle_price(&oracle, &price, NOW, 1, 1).is_ok());
+
+  let price = OraclePrice { trading: false, ..feed_price(100, 0, 0, NOW) };
+  assert_eq!(oracle_price(&oracle, &price, NOW, 1, 1), Err(TokensellError::StalePrice));
+
+  let price = feed_price(0, 0, 0, NOW);
+  assert_eq!(oracle_price(&oracle, &price, NOW, 1, 1), Err(TokensellError::InvalidPriceFeed));
+}
+
+#[test]
+fn wide_confidence_is_rejected() {
+  let oracle = oracle(Pubkey::new_unique());
+
+  assert!(oracle_price(&oracle, &feed_price(10_000, 100, 0, NOW), NOW, 1, 1).is_ok());
+  assert_eq!(
+    oracle_price(&oracle, &feed_price(10_000, 101, 0, NOW), NOW, 1, 1),
+    Err(TokensellError::PriceUncertain),
+  );
+}
+
+#[tokio::test]
+async fn deposit_converts_usd_price() {
+  let mut context = start().await;
+  let test_sell = usd_sale(&mut context).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 100_000_000).await;
+
+  warp_to(&mut context, NOW + 10).await;
+  participant.deposit(&mut context, &test_sell, 10).await.unwrap();
+
+  // 10 tokens at 2 USD, paid at 0.5 USD per payment token
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 10);
+  assert_eq!(data.paid, 40_000_000);
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 40_000_000);
+
+  // the next deposit pays the price of the moment
+  set_feed(&mut context, &test_sell.price_feed.unwrap(), pyth_price(100_000_000, 10_000, -8, NOW + 20));
+  warp_to(&mut context, NOW + 30).await;
+  participant.deposit(&mut context, &test_sell, 10).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 20);
+  assert_eq!(data.paid, 60_000_000);
+}
+
+#[tokio::test]
+async fn deposit_rejects_stale_price() {
+  let mut context = start().await;
+  let test_sell = usd_sale(&mut context).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 100_000_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 10).await, TokensellError::StalePrice);
+}
+
+#[tokio::test]
+async fn deposit_rejects_uncertain_price() {
+  let mut context = start().await;
+  let test_sell = usd_sale(&mut context).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 100_000_000).await;
+
+  set_feed(&mut context, &test_sell.price_feed.unwrap(), pyth_price(50_000_000, 1_000_000, -8, NOW));
+  warp_to(&mut context, NOW + 10).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 10).await, TokensellError::PriceUncertain);
+}
+
+#[tokio::test]
+async fn deposit_rejects_other_feed() {
+  let mut context = start().await;
+  let mut test_sell = usd_sale(&mut context).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 100_000_000).await;
+
+  let other_feed = Pubkey::new_unique();
+  set_feed(&mut context, &other_feed, pyth_price(1, 0, -8, NOW));
+  test_sell.price_feed = Some(other_feed);
+
+  warp_to(&mut context, NOW + 10).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 10).await, TokensellError::InvalidPriceFeed);
+}
+
+#[tokio::test]
+async fn init_sell_rejects_non_pyth_feed() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+  let feed = Pubkey::new_unique();
+  set_feed(&mut context, &feed, vec![0; PYTH_PRICE_SIZE]);
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    price_oracle: Some(oracle(feed)),
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidPriceFeed);
+}
+
+#[tokio::test]
+async fn feed_has_to_be_owned_by_pyth() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+  let feed = Pubkey::new_unique();
+  set_feed_owned_by(&mut context, &feed, pyth_price(50_000_000, 10_000, -8, NOW), Pubkey::new_unique());
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    price_oracle: Some(oracle(feed)),
+    ..default_args()
+  }).await;
+  assert_error(result, TokensellError::InvalidPriceFeed);
+
+  // a feed handed over after init is rejected at deposit
+  let test_sell = usd_sale(&mut context).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 100_000_000).await;
+  set_feed_owned_by(&mut context, &test_sell.price_feed.unwrap(), pyth_price(50_000_000, 10_000, -8, NOW), Pubkey::new_unique());
+
+  warp_to(&mut context, NOW + 10).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 10).await, TokensellError::InvalidPriceFeed);
+}
+
+#[tokio::test]
+async fn feed_can_be_owned_by_the_sale_oracle_program() {
+  let mut context = start().await;
+  let oracle_program = Pubkey::new_unique();
+  let feed = Pubkey::new_unique();
+  set_feed_owned_by(&mut context, &feed, pyth_price(50_000_000, 10_000, -8, NOW), oracle_program);
+
+  let mut test_sell = TestSell::create(&mut context, InitSellArgs {
+    price_numerator: 2,
+    price_denominator: 1,
+    start_time: NOW,
+    price_oracle: Some(oracle(feed)),
+    price_oracle_program: Some(oracle_program),
+    ..default_args()
+  }).await;
+  test_sell.price_feed = Some(feed);
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.price_oracle_program, Some(oracle_program));
+
+  let participant = TestParticipant::create(&mut context, &test_sell, 100_000_000).await;
+
+  warp_to(&mut context, NOW + 10).await;
+  participant.deposit(&mut context, &test_sell, 10).await.unwrap();
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 40_000_000);
+
+  // Pyth feeds no longer qualify once the sale names its own program
+  set_feed(&mut context, &feed, pyth_price(50_000_000, 10_000, -8, NOW));
+  warp_to(&mut context, NOW + 20).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 10).await, TokensellError::InvalidPriceFeed);
+}
+
+#[tokio::test]
+async fn oracle_program_needs_a_price_oracle() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    price_oracle_program: Some(Pubkey::new_unique()),
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidPriceFeed);
+}
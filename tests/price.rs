@@ -0,0 +1,53 @@
+use live_tokensell::{
+  error::TokensellError,
+  price::{payment_amount, purchasable_amount},
+};
+
+#[test]
+fn same_decimals_multiply_by_price() {
+  assert_eq!(payment_amount(1_000, 2, 1, 6, 6).unwrap(), 2_000);
+  assert_eq!(payment_amount(1_000, 35, 1_000, 6, 6).unwrap(), 35);
+}
+
+#[test]
+fn decimals_are_normalized() {
+  // 1.5 tokens with 9 decimals at 0.035 USDT with 6 decimals
+  assert_eq!(payment_amount(1_500_000_000, 35, 1_000, 6, 9).unwrap(), 52_500);
+  // 10 tokens with 0 decimals at 2 tokens with 9 decimals
+  assert_eq!(payment_amount(10, 2, 1, 9, 0).unwrap(), 20_000_000_000);
+}
+
+#[test]
+fn fractions_round_up() {
+  assert_eq!(payment_amount(1, 1, 3, 6, 0).unwrap(), 333_334);
+  assert_eq!(payment_amount(1, 35, 1_000, 6, 9).unwrap(), 1);
+  assert_eq!(payment_amount(0, 35, 1_000, 6, 9).unwrap(), 0);
+}
+
+#[test]
+fn zero_denominator_fails() {
+  assert_eq!(payment_amount(1, 1, 0, 6, 6), Err(TokensellError::InvalidPrice));
+}
+
+#[test]
+fn overflow_fails() {
+  assert_eq!(payment_amount(u64::MAX, 2, 1, 6, 6), Err(TokensellError::Overflow));
+  assert_eq!(payment_amount(u64::MAX, u64::MAX, 1, 255, 0), Err(TokensellError::Overflow));
+  assert_eq!(payment_amount(1, 1, 1, 0, 255), Err(TokensellError::Overflow));
+}
+
+#[test]
+fn purchasable_amount_rounds_down() {
+  assert_eq!(purchasable_amount(2_000, 2, 1, 6, 6).unwrap(), 1_000);
+  assert_eq!(purchasable_amount(52_499, 35, 1_000, 6, 9).unwrap(), 1_499_971_428);
+  assert_eq!(purchasable_amount(333_333, 1, 3, 6, 0).unwrap(), 0);
+  assert_eq!(purchasable_amount(1, 0, 1, 6, 6), Err(TokensellError::InvalidPrice));
+}
+
+#[test]
+fn purchasable_amount_inverts_payment() {
+  for amount in [1, 7, 1_000, 123_456_789] {
+    let payment = payment_amount(amount, 2, 1_000_000, 6, 0).unwrap();
+    assert_eq!(purchasable_amount(payment, 2, 1_000_000, 6, 0).unwrap(), amount);
+  }
+}
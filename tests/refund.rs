@@ -0,0 +1,239 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{program_pack::Pack, transport::TransportError};
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{RefundPolicy, MAX_REFUNDABLE_DEPOSITS},
+};
+
+use utils::*;
+
+const END: i64 = NOW + 10_000;
+
+async fn refundable_sale(context: &mut ProgramTestContext, refund_policy: RefundPolicy) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create(context, InitSellArgs {
+    refund_policy,
+    ..default_args()
+  }).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  (test_sell, participant)
+}
+
+fn refund_instruction(test_sell: &TestSell, participant: &TestParticipant) -> Instruction {
+  client::refund(
+    &program_id(),
+    &participant.pubkey(),
+    &test_sell.pubkey(),
+    &participant.source_token,
+    &test_sell.source_mint,
+    &test_sell.source_program,
+  )
+}
+
+async fn refund(context: &mut ProgramTestContext, test_sell: &TestSell, participant: &TestParticipant) -> Result<(), TransportError> {
+  let instruction = refund_instruction(test_sell, participant);
+  process(context, &[instruction], &[&participant.wallet]).await
+}
+
+async fn release(context: &mut ProgramTestContext, test_sell: &TestSell) -> Result<(), TransportError> {
+  let instruction = client::release_escrow(
+    &program_id(),
+    &test_sell.pubkey(),
+    &test_sell.target_acc,
+    &test_sell.source_mint,
+    &test_sell.source_program,
+  );
+  process(context, &[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn init_sell_creates_escrow() {
+  let mut context = start().await;
+  let (test_sell, _) = refundable_sale(&mut context, RefundPolicy::UntilEnd).await;
+
+  let escrow = test_sell.escrow.unwrap();
+  assert_eq!(escrow, client::find_escrow_address(&program_id(), &test_sell.pubkey()).0);
+
+  let account = get_account(&mut context, &escrow).await.unwrap();
+  let escrow = spl_token::state::Account::unpack(&account.data).unwrap();
+  assert_eq!(escrow.mint, test_sell.source_mint);
+  assert_eq!(escrow.owner, client::find_sell_authority(&program_id(), &test_sell.pubkey()).0);
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.refund_policy, RefundPolicy::UntilEnd);
+}
+
+#[tokio::test]
+async fn refund_returns_payment_and_tokens() {
+  let mut context = start().await;
+  let (test_sell, participant) = refundable_sale(&mut context, RefundPolicy::AfterDeposit(3_600)).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &test_sell.escrow.unwrap()).await, 2_000);
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 0);
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.amount_left, 999_000);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.last_deposit_at, NOW + 200);
+
+  warp_to(&mut context, NOW + 3_800).await;
+  refund(&mut context, &test_sell, &participant).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &participant.source_token).await, 10_000);
+  assert_eq!(token_balance(&mut context, &test_sell.escrow.unwrap()).await, 0);
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.amount_left, 1_000_000);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!((data.amount, data.paid), (0, 0));
+
+  // the refunded position has nothing left to claim and can be closed
+  let instruction = client::close_participant(&program_id(), &participant.pubkey(), &test_sell.pubkey());
+  process(&mut context, &[instruction], &[&participant.wallet]).await.unwrap();
+}
+
+#[tokio::test]
+async fn each_deposit_has_its_own_refund_window() {
+  let mut context = start().await;
+  let (test_sell, participant) = refundable_sale(&mut context, RefundPolicy::AfterDeposit(100)).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  warp_to(&mut context, NOW + 301).await;
+  assert_error(refund(&mut context, &test_sell, &participant).await, TokensellError::RefundWindowClosed);
+
+  // a new deposit doesn't reopen the window of the earlier one
+  participant.deposit(&mut context, &test_sell, 500).await.unwrap();
+  warp_to(&mut context, NOW + 400).await;
+  refund(&mut context, &test_sell, &participant).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &participant.source_token).await, 8_000);
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.amount_left, 999_000);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!((data.amount, data.paid), (1_000, 2_000));
+  assert!(data.refundable.is_empty());
+
+  warp_to(&mut context, NOW + 410).await;
+  assert_error(refund(&mut context, &test_sell, &participant).await, TokensellError::RefundWindowClosed);
+}
+
+#[tokio::test]
+async fn refundable_deposits_are_bounded() {
+  let mut context = start().await;
+  let (test_sell, participant) = refundable_sale(&mut context, RefundPolicy::AfterDeposit(1_000)).await;
+
+  for i in 0..MAX_REFUNDABLE_DEPOSITS as i64 {
+    warp_to(&mut context, NOW + 200 + i).await;
+    participant.deposit(&mut context, &test_sell, 100).await.unwrap();
+  }
+
+  warp_to(&mut context, NOW + 300).await;
+  assert_error(
+    participant.deposit(&mut context, &test_sell, 100).await,
+    TokensellError::TooManyRefundableDeposits,
+  );
+
+  // deposits whose window closed make room
+  warp_to(&mut context, NOW + 1_201).await;
+  participant.deposit(&mut context, &test_sell, 100).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.refundable.len(), MAX_REFUNDABLE_DEPOSITS);
+}
+
+#[tokio::test]
+async fn refund_window_has_to_fit_a_timestamp() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+
+  let result = test_sell.init(&mut context, InitSellArgs {
+    refund_policy: RefundPolicy::AfterDeposit(i64::MAX as u64 + 1),
+    ..default_args()
+  }).await;
+
+  assert_error(result, TokensellError::InvalidRefundPolicy);
+}
+
+#[tokio::test]
+async fn refunds_until_end_close_with_the_sale() {
+  let mut context = start().await;
+  let (test_sell, participant) = refundable_sale(&mut context, RefundPolicy::UntilEnd).await;
+  let other = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  other.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  warp_to(&mut context, END).await;
+  refund(&mut context, &test_sell, &participant).await.unwrap();
+
+  warp_to(&mut context, END + 1).await;
+  assert_error(refund(&mut context, &test_sell, &other).await, TokensellError::RefundWindowClosed);
+}
+
+#[tokio::test]
+async fn claimed_position_cant_be_refunded() {
+  let mut context = start().await;
+  let (test_sell, participant) = refundable_sale(&mut context, RefundPolicy::UntilEnd).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, NOW + 350).await.unwrap();
+
+  warp_to(&mut context, NOW + 400).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+
+  assert_error(refund(&mut context, &test_sell, &participant).await, TokensellError::PositionClaimed);
+}
+
+#[tokio::test]
+async fn sale_without_policy_rejects_refunds() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  assert_error(refund(&mut context, &test_sell, &participant).await, TokensellError::RefundsDisabled);
+}
+
+#[tokio::test]
+async fn deposit_has_to_pay_into_escrow() {
+  let mut context = start().await;
+  let (mut test_sell, participant) = refundable_sale(&mut context, RefundPolicy::UntilEnd).await;
+  test_sell.escrow = None;
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(participant.deposit(&mut context, &test_sell, 1_000).await, TokensellError::InvalidEscrow);
+}
+
+#[tokio::test]
+async fn escrow_is_released_once_refunds_are_over() {
+  let mut context = start().await;
+  let (test_sell, participant) = refundable_sale(&mut context, RefundPolicy::AfterDeposit(600)).await;
+  let other = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  other.deposit(&mut context, &test_sell, 500).await.unwrap();
+  refund(&mut context, &test_sell, &other).await.unwrap();
+
+  warp_to(&mut context, END + 600).await;
+  assert_error(release(&mut context, &test_sell).await, TokensellError::EscrowLocked);
+
+  warp_to(&mut context, END + 601).await;
+  release(&mut context, &test_sell).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 2_000);
+  assert_eq!(token_balance(&mut context, &test_sell.escrow.unwrap()).await, 0);
+}
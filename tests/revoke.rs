@@ -0,0 +1,183 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{signature::{Keypair, Signer}, transport::TransportError};
+
+use live_tokensell::{client, error::TokensellError};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn revocable_sale(context: &mut ProgramTestContext) -> (TestSell, TestParticipant, Pubkey) {
+  let test_sell = TestSell::create(context, default_args()).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+  let payer = context.payer.pubkey();
+  let treasury = create_token_account(context, &test_sell.target_mint, &payer).await;
+
+  warp_to(context, NOW + 200).await;
+  participant.deposit(context, &test_sell, 1_000).await.unwrap();
+  set_revocable(context, &test_sell, &participant, true).await.unwrap();
+
+  (test_sell, participant, treasury)
+}
+
+async fn set_revocable(
+  context: &mut ProgramTestContext,
+  test_sell: &TestSell,
+  participant: &TestParticipant,
+  revocable: bool,
+) -> Result<(), TransportError> {
+  let instruction = client::set_revocable(
+    &program_id(),
+    &context.payer.pubkey(),
+    &participant.pubkey(),
+    &test_sell.pubkey(),
+    revocable,
+  );
+  process(context, &[instruction], &[&participant.wallet]).await
+}
+
+async fn revoke(
+  context: &mut ProgramTestContext,
+  owner: &Keypair,
+  test_sell: &TestSell,
+  participant: &TestParticipant,
+  destination: &Pubkey,
+) -> Result<(), TransportError> {
+  let instruction = client::revoke(
+    &program_id(),
+    &owner.pubkey(),
+    &participant.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    destination,
+    &test_sell.target_program,
+  );
+  process(context, &[instruction], &[owner]).await
+}
+
+fn payer(context: &ProgramTestContext) -> Keypair {
+  Keypair::from_bytes(&context.payer.to_bytes()).unwrap()
+}
+
+#[tokio::test]
+async fn revoke_keeps_vested_and_returns_the_rest() {
+  let mut context = start().await;
+  let (test_sell, participant, treasury) = revocable_sale(&mut context).await;
+  let owner = payer(&context);
+
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+  warp_to(&mut context, TGE + 3 * MONTH).await;
+  revoke(&mut context, &owner, &test_sell, &participant, &treasury).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &treasury).await, 630);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 370);
+  assert_eq!(data.revoked_at, Some(TGE + 3 * MONTH));
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.amount_total, 1_000_000 - 630);
+
+  // vesting stopped, later claims get what had vested
+  warp_to(&mut context, TGE + 9 * MONTH).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 370);
+
+  assert_error(
+    revoke(&mut context, &owner, &test_sell, &participant, &treasury).await,
+    TokensellError::PositionRevoked,
+  );
+}
+
+#[tokio::test]
+async fn revoke_before_tge_takes_the_whole_position() {
+  let mut context = start().await;
+  let (test_sell, participant, treasury) = revocable_sale(&mut context).await;
+  let owner = payer(&context);
+
+  revoke(&mut context, &owner, &test_sell, &participant, &treasury).await.unwrap();
+  assert_eq!(token_balance(&mut context, &treasury).await, 1_000);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 0);
+
+  // the vault still covers the sale
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+
+  warp_to(&mut context, NOW + 300).await;
+  assert_error(
+    participant.deposit(&mut context, &test_sell, 1_000).await,
+    TokensellError::PositionRevoked,
+  );
+  assert_error(
+    set_revocable(&mut context, &test_sell, &participant, false).await,
+    TokensellError::PositionRevoked,
+  );
+}
+
+#[tokio::test]
+async fn only_revocable_positions_are_revoked() {
+  let mut context = start().await;
+  let (test_sell, participant, treasury) = revocable_sale(&mut context).await;
+  let owner = payer(&context);
+
+  set_revocable(&mut context, &test_sell, &participant, false).await.unwrap();
+
+  assert_error(
+    revoke(&mut context, &owner, &test_sell, &participant, &treasury).await,
+    TokensellError::NotRevocable,
+  );
+}
+
+#[tokio::test]
+async fn only_sell_owner_revokes() {
+  let mut context = start().await;
+  let (test_sell, participant, treasury) = revocable_sale(&mut context).await;
+  let other = Keypair::new();
+  fund(&mut context, &other.pubkey(), 1_000_000_000).await;
+
+  assert_error(
+    revoke(&mut context, &other, &test_sell, &participant, &treasury).await,
+    TokensellError::NotSellOwner,
+  );
+}
+
+#[tokio::test]
+async fn participant_has_to_agree() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let participant = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  let mut instruction = client::set_revocable(
+    &program_id(),
+    &context.payer.pubkey(),
+    &participant.pubkey(),
+    &test_sell.pubkey(),
+    true,
+  );
+  instruction.accounts[1].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn revocable_position_cant_be_transferred() {
+  let mut context = start().await;
+  let (test_sell, participant, _) = revocable_sale(&mut context).await;
+  let buyer = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  let instruction = client::transfer_position(&program_id(), &participant.pubkey(), &buyer.pubkey(), &test_sell.pubkey(), None, None);
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::RevocablePosition,
+  );
+}
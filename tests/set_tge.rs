@@ -0,0 +1,261 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use solana_program_test::tokio;
+use solana_sdk::signature::{Keypair, Signer};
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::{init_sell::InitSellArgs, set_tge::SetTgeArgs},
+  state::{PendingTge, TgePolicy, DEFAULT_TGE_DELAY, DEFAULT_TGE_WINDOW},
+};
+
+use utils::*;
+
+/// TGE far enough out to outlast the default delay.
+const DEFAULT_TGE: i64 = NOW + 2 * DEFAULT_TGE_DELAY;
+
+#[tokio::test]
+async fn init_sets_default_policy() {
+  let mut context = start().await;
+  let args = InitSellArgs {
+    tge_policy: None,
+    ..default_args()
+  };
+  let test_sell = TestSell::create(&mut context, args.clone()).await;
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.tge_policy, Some(TgePolicy::after(args.end_time)));
+
+  test_sell.queue_tge(&mut context, DEFAULT_TGE).await.unwrap();
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.tge, None);
+  assert_eq!(sell.pending_tge, Some(PendingTge { tge: DEFAULT_TGE, eta: NOW + DEFAULT_TGE_DELAY }));
+
+  assert_error(test_sell.apply_tge(&mut context).await, TokensellError::TgeTimelocked);
+
+  warp_to(&mut context, NOW + DEFAULT_TGE_DELAY).await;
+  test_sell.apply_tge(&mut context).await.unwrap();
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.tge, Some(DEFAULT_TGE));
+}
+
+#[tokio::test]
+async fn tge_follows_the_sale_by_default() {
+  let mut context = start().await;
+  let args = InitSellArgs {
+    tge_policy: None,
+    ..default_args()
+  };
+  let test_sell = TestSell::create(&mut context, args.clone()).await;
+
+  assert_error(test_sell.queue_tge(&mut context, args.end_time - 1).await, TokensellError::TgeOutOfBounds);
+
+  warp_to(&mut context, NOW + 100).await;
+  assert_error(
+    test_sell.queue_tge(&mut context, args.end_time + DEFAULT_TGE_WINDOW + 1).await,
+    TokensellError::TgeOutOfBounds,
+  );
+  test_sell.queue_tge(&mut context, args.end_time + DEFAULT_TGE_WINDOW).await.unwrap();
+}
+
+#[tokio::test]
+async fn tge_cant_be_cleared() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  test_sell.set_tge(&mut context, NOW + 20_000).await.unwrap();
+
+  let mut instruction = client::set_tge(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    NOW + 20_000,
+  );
+  instruction.data = SetTgeArgs { instruction: 3, tge: None }.try_to_vec().unwrap();
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::TgeOutOfBounds);
+}
+
+#[tokio::test]
+async fn set_tge_requires_owner() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let stranger = Keypair::new();
+
+  let instruction = client::set_tge(
+    &program_id(),
+    &stranger.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    NOW,
+  );
+
+  assert_error(process(&mut context, &[instruction], &[&stranger]).await, TokensellError::NotSellOwner);
+}
+
+#[tokio::test]
+async fn set_tge_requires_signature() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  let mut instruction = client::set_tge(
+    &program_id(),
+    &Pubkey::new_unique(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    NOW,
+  );
+  instruction.accounts[0].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn set_tge_rejects_other_vault() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+  let (sell_authority, _) = client::find_sell_authority(&program_id(), &test_sell.pubkey());
+
+  let other_vault = create_token_account(&mut context, &test_sell.target_mint, &sell_authority).await;
+  mint_to(&mut context, &test_sell.target_mint, &other_vault, 1_000_000).await;
+
+  let instruction = client::set_tge(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &other_vault,
+    NOW,
+  );
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::InvalidVault);
+}
+
+#[tokio::test]
+async fn set_tge_requires_vault_to_cover_sale() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 999_999).await;
+  test_sell.init(&mut context, default_args()).await.unwrap();
+
+  assert_error(test_sell.set_tge(&mut context, NOW + 20_000).await, TokensellError::NoTokensInVault);
+
+  mint_to(&mut context, &test_sell.target_mint, &test_sell.vault, 1).await;
+  warp_to(&mut context, NOW).await;
+  test_sell.set_tge(&mut context, NOW + 20_000).await.unwrap();
+}
+
+#[tokio::test]
+async fn set_tge_rejects_other_mint() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    total_amount: 1,
+    ..default_args()
+  }).await;
+
+  let instruction = client::set_tge(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.source_mint,
+    &test_sell.vault,
+    NOW,
+  );
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::WrongMint);
+}
+
+const POLICY: TgePolicy = TgePolicy {
+  delay: 1_000,
+  earliest: NOW + 20_000,
+  latest: NOW + 30_000,
+};
+
+#[tokio::test]
+async fn tge_change_applies_after_delay() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    tge_policy: Some(POLICY),
+    ..default_args()
+  }).await;
+
+  assert_error(test_sell.apply_tge(&mut context).await, TokensellError::NoPendingTge);
+
+  warp_to(&mut context, NOW + 100).await;
+  test_sell.queue_tge(&mut context, NOW + 25_000).await.unwrap();
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!(sell.tge, None);
+  assert_eq!(sell.pending_tge, Some(PendingTge { tge: NOW + 25_000, eta: NOW + 1_100 }));
+
+  assert_error(test_sell.apply_tge(&mut context).await, TokensellError::TgeTimelocked);
+
+  warp_to(&mut context, NOW + 1_100).await;
+  test_sell.apply_tge(&mut context).await.unwrap();
+
+  let sell = get_sell(&mut context, &test_sell.pubkey()).await;
+  assert_eq!((sell.tge, sell.pending_tge), (Some(NOW + 25_000), None));
+}
+
+#[tokio::test]
+async fn tge_stays_within_policy_bounds() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    tge_policy: Some(POLICY),
+    ..default_args()
+  }).await;
+
+  assert_error(test_sell.queue_tge(&mut context, NOW + 30_001).await, TokensellError::TgeOutOfBounds);
+
+  warp_to(&mut context, NOW + 100).await;
+  assert_error(test_sell.queue_tge(&mut context, NOW + 19_999).await, TokensellError::TgeOutOfBounds);
+}
+
+#[tokio::test]
+async fn tge_is_locked_once_passed() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, default_args()).await;
+
+  test_sell.set_tge(&mut context, NOW + 20_000).await.unwrap();
+
+  warp_to(&mut context, NOW + 20_001).await;
+  assert_error(test_sell.queue_tge(&mut context, NOW + 30_000).await, TokensellError::TgeLocked);
+}
+
+#[tokio::test]
+async fn passed_tge_cant_be_applied() {
+  let mut context = start().await;
+  let test_sell = TestSell::create(&mut context, InitSellArgs {
+    tge_policy: Some(POLICY),
+    ..default_args()
+  }).await;
+
+  // nobody applied the change before the queued TGE came
+  test_sell.queue_tge(&mut context, NOW + 20_000).await.unwrap();
+
+  warp_to(&mut context, NOW + 20_000).await;
+  assert_error(test_sell.apply_tge(&mut context).await, TokensellError::TgeOutOfBounds);
+  assert_eq!(get_sell(&mut context, &test_sell.pubkey()).await.tge, None);
+}
+
+#[tokio::test]
+async fn init_rejects_invalid_tge_policy() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 1_000_000).await;
+
+  for policy in [TgePolicy { earliest: NOW + 30_000, latest: NOW + 20_000, ..POLICY }, TgePolicy { delay: 0, ..POLICY }] {
+    let args = InitSellArgs {
+      tge_policy: Some(policy),
+      ..default_args()
+    };
+    assert_error(test_sell.init(&mut context, args).await, TokensellError::InvalidTgePolicy);
+  }
+}
@@ -0,0 +1,251 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::transport::TransportError;
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{StakingTier, StakingTiers},
+};
+
+use utils::*;
+
+const END: i64 = NOW + 10_000;
+
+/// A participant holding 1_000 staking tokens.
+struct Staker {
+  participant: TestParticipant,
+  staking_token: Pubkey,
+}
+
+fn tiers(mint: Pubkey) -> StakingTiers {
+  StakingTiers {
+    mint,
+    tiers: vec![
+      StakingTier { min_staked: 100, max_amount: 1_000 },
+      StakingTier { min_staked: 500, max_amount: 3_000 },
+    ],
+  }
+}
+
+async fn tiered_sale(context: &mut ProgramTestContext) -> (TestSell, Pubkey, Staker) {
+  let staking_mint = create_mint(context, 0).await;
+  let test_sell = TestSell::create(context, InitSellArgs {
+    staking: Some(tiers(staking_mint)),
+    ..default_args()
+  }).await;
+
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+  let staking_token = create_token_account(context, &staking_mint, &participant.pubkey()).await;
+  mint_to(context, &staking_mint, &staking_token, 1_000).await;
+
+  (test_sell, staking_mint, Staker { participant, staking_token })
+}
+
+async fn stake(
+  context: &mut ProgramTestContext,
+  staker: &Staker,
+  mint: &Pubkey,
+  amount: u64,
+  lock_until: i64,
+) -> Result<(), TransportError> {
+  let instruction = client::stake(
+    &program_id(),
+    &staker.participant.pubkey(),
+    mint,
+    &staker.staking_token,
+    &spl_token::id(),
+    amount,
+    lock_until,
+  );
+  process(context, &[instruction], &[&staker.participant.wallet]).await
+}
+
+async fn unstake(context: &mut ProgramTestContext, staker: &Staker, mint: &Pubkey, amount: u64) -> Result<(), TransportError> {
+  let instruction = client::unstake(
+    &program_id(),
+    &staker.participant.pubkey(),
+    mint,
+    &staker.staking_token,
+    &spl_token::id(),
+    amount,
+  );
+  process(context, &[instruction], &[&staker.participant.wallet]).await
+}
+
+#[tokio::test]
+async fn stake_locks_tokens_in_vault() {
+  let mut context = start().await;
+  let (_, mint, staker) = tiered_sale(&mut context).await;
+
+  stake(&mut context, &staker, &mint, 300, END).await.unwrap();
+  stake(&mut context, &staker, &mint, 200, NOW + 500).await.unwrap();
+
+  let (address, _) = client::find_stake_address(&program_id(), &mint, &staker.participant.pubkey());
+  let data = client::decode_stake(&get_account(&mut context, &address).await.unwrap().data).unwrap();
+  assert_eq!(data.amount, 500);
+  // a shorter lock doesn't shorten the stake
+  assert_eq!(data.unlock_at, END);
+
+  let (vault, _) = client::find_stake_vault_address(&program_id(), &address);
+  assert_eq!(token_balance(&mut context, &vault).await, 500);
+  assert_eq!(token_balance(&mut context, &staker.staking_token).await, 500);
+}
+
+#[tokio::test]
+async fn lock_has_to_end_in_the_future() {
+  let mut context = start().await;
+  let (_, mint, staker) = tiered_sale(&mut context).await;
+
+  assert_error(stake(&mut context, &staker, &mint, 300, NOW).await, TokensellError::InvalidLockPeriod);
+}
+
+#[tokio::test]
+async fn unstake_waits_for_unlock() {
+  let mut context = start().await;
+  let (_, mint, staker) = tiered_sale(&mut context).await;
+
+  stake(&mut context, &staker, &mint, 300, NOW + 500).await.unwrap();
+  assert_error(unstake(&mut context, &staker, &mint, 300).await, TokensellError::StakeLocked);
+
+  warp_to(&mut context, NOW + 500).await;
+  assert_error(unstake(&mut context, &staker, &mint, 301).await, TokensellError::InsufficientStake);
+  unstake(&mut context, &staker, &mint, 300).await.unwrap();
+
+  assert_eq!(token_balance(&mut context, &staker.staking_token).await, 1_000);
+}
+
+#[tokio::test]
+async fn deposits_are_capped_by_tier() {
+  let mut context = start().await;
+  let (test_sell, mint, staker) = tiered_sale(&mut context).await;
+
+  stake(&mut context, &staker, &mint, 100, END).await.unwrap();
+
+  warp_to(&mut context, NOW + 200).await;
+  staker.participant.deposit(&mut context, &test_sell, 600).await.unwrap();
+  staker.participant.deposit(&mut context, &test_sell, 400).await.unwrap();
+
+  assert_error(
+    staker.participant.deposit(&mut context, &test_sell, 1).await,
+    TokensellError::TierCapExceeded,
+  );
+
+  // staking more moves the participant up a tier
+  stake(&mut context, &staker, &mint, 400, END).await.unwrap();
+  staker.participant.deposit(&mut context, &test_sell, 2_000).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &staker.participant.pubkey()).await;
+  assert_eq!(data.amount, 3_000);
+}
+
+#[tokio::test]
+async fn deposit_needs_a_tier() {
+  let mut context = start().await;
+  let (test_sell, mint, staker) = tiered_sale(&mut context).await;
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(
+    staker.participant.deposit(&mut context, &test_sell, 100).await,
+    TokensellError::NoStakingTier,
+  );
+
+  stake(&mut context, &staker, &mint, 99, END).await.unwrap();
+  warp_to(&mut context, NOW + 300).await;
+  assert_error(
+    staker.participant.deposit(&mut context, &test_sell, 100).await,
+    TokensellError::NoStakingTier,
+  );
+}
+
+#[tokio::test]
+async fn stake_unlocking_before_sale_end_doesnt_count() {
+  let mut context = start().await;
+  let (test_sell, mint, staker) = tiered_sale(&mut context).await;
+
+  stake(&mut context, &staker, &mint, 500, END - 1).await.unwrap();
+
+  warp_to(&mut context, NOW + 200).await;
+  assert_error(
+    staker.participant.deposit(&mut context, &test_sell, 100).await,
+    TokensellError::NoStakingTier,
+  );
+}
+
+#[tokio::test]
+async fn stake_of_other_wallet_is_rejected() {
+  let mut context = start().await;
+  let (test_sell, mint, staker) = tiered_sale(&mut context).await;
+  let other = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+
+  stake(&mut context, &staker, &mint, 500, END).await.unwrap();
+
+  warp_to(&mut context, NOW + 200).await;
+  let mut instruction = other.deposit_instruction(&test_sell, 100);
+  let last = instruction.accounts.len() - 1;
+  instruction.accounts[last].pubkey = client::find_stake_address(&program_id(), &mint, &staker.participant.pubkey()).0;
+
+  assert_error(
+    process(&mut context, &[instruction], &[&other.wallet]).await,
+    TokensellError::InvalidStakeAccount,
+  );
+}
+
+#[tokio::test]
+async fn tiers_have_to_ascend() {
+  let mut context = start().await;
+  let test_sell = TestSell::prepare(&mut context, 0).await;
+
+  let mut staking = tiers(Pubkey::new_unique());
+  staking.tiers.reverse();
+
+  assert_error(
+    test_sell.init(&mut context, InitSellArgs {
+      staking: Some(staking),
+      ..default_args()
+    }).await,
+    TokensellError::InvalidStakingTiers,
+  );
+}
+
+#[tokio::test]
+async fn transfer_is_capped_by_recipient_tier() {
+  let mut context = start().await;
+  let (test_sell, mint, staker) = tiered_sale(&mut context).await;
+  let recipient = TestParticipant::create(&mut context, &test_sell, 0).await;
+  let staking_token = create_token_account(&mut context, &mint, &recipient.pubkey()).await;
+  mint_to(&mut context, &mint, &staking_token, 1_000).await;
+  let recipient = Staker { participant: recipient, staking_token };
+
+  stake(&mut context, &staker, &mint, 500, END).await.unwrap();
+  stake(&mut context, &recipient, &mint, 100, END).await.unwrap();
+
+  warp_to(&mut context, NOW + 200).await;
+  staker.participant.deposit(&mut context, &test_sell, 2_000).await.unwrap();
+
+  let transfer = || client::transfer_position(
+    &program_id(),
+    &staker.participant.pubkey(),
+    &recipient.participant.pubkey(),
+    &test_sell.pubkey(),
+    None,
+    Some(&mint),
+  );
+  assert_error(
+    process(&mut context, &[transfer()], &[&staker.participant.wallet]).await,
+    TokensellError::TierCapExceeded,
+  );
+
+  // staking more moves the recipient up a tier
+  stake(&mut context, &recipient, &mint, 400, END).await.unwrap();
+  warp_to(&mut context, NOW + 300).await;
+  process(&mut context, &[transfer()], &[&staker.participant.wallet]).await.unwrap();
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &recipient.participant.pubkey()).await;
+  assert_eq!(data.amount, 2_000);
+}
@@ -0,0 +1,211 @@
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+
+use live_tokensell::state::{
+  Attestation,
+  ComplianceGate,
+  DirectoryEntry,
+  DirectoryPage,
+  Key,
+  Multisig,
+  ParticipantData,
+  PendingTge,
+  PriceOracle,
+  RefundPolicy,
+  RefundableDeposit,
+  SaleStatus,
+  SellData,
+  Stake,
+  StakingTier,
+  StakingTiers,
+  TgePolicy,
+  VoucherReceipt,
+  ATTESTATION_SIZE,
+  DIRECTORY_PAGE_CAPACITY,
+  DIRECTORY_PAGE_SIZE,
+  MAX_MULTISIG_SIGNERS,
+  MAX_REFUNDABLE_DEPOSITS,
+  MAX_STAKING_TIERS,
+  MULTISIG_SIZE,
+  PARTICIPANT_SIZE,
+  STAKE_SIZE,
+  TOKENSELL_SELL_SIZE,
+  VOUCHER_RECEIPT_SIZE,
+};
+
+#[test]
+fn participant_size_matches_layout() {
+  let participant = ParticipantData {
+    key: Key::ParticipantDataV2,
+    owner: Pubkey::new_unique(),
+    sell: Pubkey::new_unique(),
+    amount: 1,
+    claimed: 1,
+    paid: 1,
+    last_deposit_at: 1,
+    revocable: true,
+    revoked_at: Some(1),
+    refundable: vec![RefundableDeposit { amount: 1, paid: 1, deposited_at: 1 }; MAX_REFUNDABLE_DEPOSITS],
+  };
+
+  assert_eq!(participant.try_to_vec().unwrap().len(), PARTICIPANT_SIZE);
+}
+
+#[test]
+fn sell_size_fits_all_optional_fields() {
+  let sell = SellData {
+    key: Key::SellDataV2,
+    owner: Pubkey::new_unique(),
+    source_mint: Pubkey::new_unique(),
+    target_mint: Some(Pubkey::new_unique()),
+    source_vault: Some(Pubkey::new_unique()),
+    target_acc: Pubkey::new_unique(),
+    price_numerator: 1,
+    price_denominator: 1,
+    source_decimals: 6,
+    target_decimals: 9,
+    start_time: 1,
+    end_time: 1,
+    tge: Some(1),
+    initial_perc: 1,
+    total_months: 1,
+    min_deposit: 1,
+    amount_total: 1,
+    amount_left: 1,
+    transfers_disabled: true,
+    directory_index: Some(1),
+    price_oracle: Some(PriceOracle {
+      feed: Pubkey::new_unique(),
+      max_age: 1,
+      max_confidence_bps: 1,
+    }),
+    refund_policy: RefundPolicy::AfterDeposit(1),
+    compliance: Some(ComplianceGate {
+      authority: Pubkey::new_unique(),
+      gate_claims: true,
+    }),
+    voucher_signer: Some(Pubkey::new_unique()),
+    staking: Some(StakingTiers {
+      mint: Pubkey::new_unique(),
+      tiers: vec![StakingTier { min_staked: 1, max_amount: 1 }; MAX_STAKING_TIERS],
+    }),
+    tge_policy: Some(TgePolicy {
+      delay: 1,
+      earliest: 1,
+      latest: 1,
+    }),
+    pending_tge: Some(PendingTge {
+      tge: 1,
+      eta: 1,
+    }),
+    price_oracle_program: Some(Pubkey::new_unique()),
+  };
+
+  assert_eq!(sell.try_to_vec().unwrap().len(), TOKENSELL_SELL_SIZE);
+}
+
+#[test]
+fn attestation_size_matches_layout() {
+  let attestation = Attestation {
+    key: Key::Attestation,
+    authority: Pubkey::new_unique(),
+    wallet: Pubkey::new_unique(),
+    expires_at: 1,
+    jurisdiction: Some(*b"DE"),
+    revoked: true,
+  };
+
+  assert_eq!(attestation.try_to_vec().unwrap().len(), ATTESTATION_SIZE);
+}
+
+#[test]
+fn voucher_receipt_size_matches_layout() {
+  let receipt = VoucherReceipt {
+    key: Key::VoucherReceipt,
+    sell: Pubkey::new_unique(),
+    wallet: Pubkey::new_unique(),
+    nonce: 1,
+    spent: 1,
+  };
+
+  assert_eq!(receipt.try_to_vec().unwrap().len(), VOUCHER_RECEIPT_SIZE);
+}
+
+#[test]
+fn stake_size_matches_layout() {
+  let stake = Stake {
+    key: Key::Stake,
+    owner: Pubkey::new_unique(),
+    mint: Pubkey::new_unique(),
+    amount: 1,
+    unlock_at: 1,
+  };
+
+  assert_eq!(stake.try_to_vec().unwrap().len(), STAKE_SIZE);
+}
+
+#[test]
+fn multisig_size_fits_all_signers() {
+  let multisig = Multisig {
+    key: Key::Multisig,
+    threshold: 1,
+    proposals: 1,
+    signers: (0..MAX_MULTISIG_SIGNERS).map(|_| Pubkey::new_unique()).collect(),
+  };
+
+  assert!(multisig.is_valid());
+  assert_eq!(multisig.try_to_vec().unwrap().len(), MULTISIG_SIZE);
+}
+
+#[test]
+fn staking_tiers_cap_by_highest_tier_reached() {
+  let staking = StakingTiers {
+    mint: Pubkey::new_unique(),
+    tiers: vec![
+      StakingTier { min_staked: 100, max_amount: 1_000 },
+      StakingTier { min_staked: 500, max_amount: 10_000 },
+    ],
+  };
+
+  assert!(staking.is_valid());
+  assert_eq!(staking.cap(99), None);
+  assert_eq!(staking.cap(100), Some(1_000));
+  assert_eq!(staking.cap(499), Some(1_000));
+  assert_eq!(staking.cap(10_000), Some(10_000));
+
+  let unordered = StakingTiers {
+    tiers: staking.tiers.iter().rev().cloned().collect(),
+    ..staking.clone()
+  };
+  assert!(!unordered.is_valid());
+  assert!(!StakingTiers { tiers: vec![], ..staking }.is_valid());
+}
+
+#[test]
+fn directory_page_size_fits_capacity() {
+  let entry = DirectoryEntry {
+    sell: Pubkey::new_unique(),
+    owner: Pubkey::new_unique(),
+    status: SaleStatus::TgeSet,
+    start_time: 1,
+    end_time: 1,
+  };
+  let page = DirectoryPage {
+    key: Key::DirectoryPage,
+    page: 1,
+    entries: vec![entry; DIRECTORY_PAGE_CAPACITY],
+  };
+
+  assert_eq!(page.try_to_vec().unwrap().len(), DIRECTORY_PAGE_SIZE);
+}
+
+#[test]
+fn keys_keep_their_discriminants() {
+  assert_eq!(Key::Uninitialized as u8, 0);
+  assert_eq!(Key::ParticipantDataV1 as u8, 1);
+  assert_eq!(Key::SellDataV1 as u8, 2);
+  assert_eq!(Key::ParticipantDataV2.latest(), Key::ParticipantDataV2);
+  assert_eq!(Key::SellDataV1.latest(), Key::SellDataV2);
+  assert_eq!(Key::from_data(&[4, 0]), Key::SellDataV2);
+  assert_eq!(Key::from_data(&[200]), Key::Uninitialized);
+}
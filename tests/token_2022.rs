@@ -0,0 +1,117 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::signature::Signer;
+
+use live_tokensell::{client, error::TokensellError};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+/// 1% transfer fee, capped well above the amounts used here.
+const FEE: Option<(u16, u64)> = Some((100, 1_000_000));
+
+async fn sale_with(context: &mut ProgramTestContext, target: MintConfig, source: MintConfig) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create_with(context, default_args(), target, source).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+
+  (test_sell, participant)
+}
+
+fn distribute_instruction(context: &ProgramTestContext, test_sell: &TestSell, participant: &TestParticipant) -> Instruction {
+  client::batch_distribute(
+    &program_id(),
+    &context.payer.pubkey(),
+    &test_sell.pubkey(),
+    &test_sell.target_mint,
+    &test_sell.vault,
+    &test_sell.target_program,
+    &[(participant.pubkey(), participant.target_token)],
+    test_sell.claim_compliance_authority(),
+  )
+}
+
+#[tokio::test]
+async fn token_2022_sale_without_fees() {
+  let mut context = start().await;
+  let (test_sell, participant) = sale_with(&mut context, MintConfig::token_2022(None), MintConfig::token_2022(None)).await;
+
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 2_000);
+
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+
+  warp_to(&mut context, TGE + 1).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 100);
+}
+
+#[tokio::test]
+async fn deposit_credits_payment_net_of_fees() {
+  let mut context = start().await;
+  let (test_sell, participant) = sale_with(&mut context, MintConfig::classic(), MintConfig::token_2022(FEE)).await;
+
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+
+  // 2_000 paid, 20 withheld as fee, and 1_980 buys 990 tokens
+  assert_eq!(token_balance(&mut context, &participant.source_token).await, 8_000);
+  assert_eq!(token_balance(&mut context, &test_sell.target_acc).await, 1_980);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 990);
+}
+
+#[tokio::test]
+async fn claims_debit_vault_gross_of_fees() {
+  let mut context = start().await;
+  let (test_sell, participant) = sale_with(&mut context, MintConfig::token_2022(FEE), MintConfig::classic()).await;
+
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+
+  warp_to(&mut context, TGE + 1).await;
+  participant.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 99);
+  assert_eq!(token_balance(&mut context, &test_sell.vault).await, 999_900);
+
+  warp_to(&mut context, TGE + 3 * MONTH).await;
+  let instruction = distribute_instruction(&context, &test_sell, &participant);
+  process(&mut context, &[instruction], &[]).await.unwrap();
+
+  // 270 more, 3 of them withheld
+  assert_eq!(token_balance(&mut context, &participant.target_token).await, 366);
+  assert_eq!(token_balance(&mut context, &test_sell.vault).await, 999_630);
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.claimed, 370);
+}
+
+#[tokio::test]
+async fn token_program_must_own_the_mint() {
+  let mut context = start().await;
+  let (test_sell, participant) = sale_with(&mut context, MintConfig::classic(), MintConfig::token_2022(None)).await;
+
+  let mut instruction = participant.deposit_instruction(&test_sell, 1_000);
+  instruction.accounts[5].pubkey = spl_token::id();
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidTokenProgram,
+  );
+
+  participant.deposit(&mut context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(&mut context, TGE).await.unwrap();
+  warp_to(&mut context, TGE + 1).await;
+
+  let mut instruction = participant.claim_instruction(&test_sell);
+  instruction.accounts[5].pubkey = spl_token_2022::id();
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::InvalidTokenProgram,
+  );
+}
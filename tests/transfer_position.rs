@@ -0,0 +1,161 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::instruction::Instruction;
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::signature::Signer;
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{Key, ParticipantData},
+};
+
+use utils::*;
+
+const TGE: i64 = NOW + 20_000;
+
+async fn vesting_sale(context: &mut ProgramTestContext, args: InitSellArgs) -> (TestSell, TestParticipant, TestParticipant) {
+  let test_sell = TestSell::create(context, args).await;
+  let seller = TestParticipant::create(context, &test_sell, 10_000).await;
+  let buyer = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+  seller.deposit(context, &test_sell, 1_000).await.unwrap();
+  test_sell.set_tge(context, TGE).await.unwrap();
+
+  (test_sell, seller, buyer)
+}
+
+fn transfer_instruction(test_sell: &TestSell, from: &TestParticipant, to: &TestParticipant) -> Instruction {
+  client::transfer_position(&program_id(), &from.pubkey(), &to.pubkey(), &test_sell.pubkey(), None, None)
+}
+
+#[tokio::test]
+async fn position_moves_to_new_wallet() {
+  let mut context = start().await;
+  let (test_sell, seller, buyer) = vesting_sale(&mut context, default_args()).await;
+
+  warp_to(&mut context, TGE + 3 * MONTH).await;
+  seller.claim(&mut context, &test_sell).await.unwrap();
+
+  let instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  process(&mut context, &[instruction], &[&seller.wallet]).await.unwrap();
+
+  assert_eq!(get_participant(&mut context, &test_sell.pubkey(), &buyer.pubkey()).await, ParticipantData {
+    key: Key::ParticipantDataV2,
+    owner: buyer.pubkey(),
+    sell: test_sell.pubkey(),
+    amount: 1_000,
+    claimed: 370,
+    paid: 2_000,
+    last_deposit_at: NOW + 200,
+    revocable: false,
+    revoked_at: None,
+    refundable: vec![],
+  });
+
+  let emptied = get_participant(&mut context, &test_sell.pubkey(), &seller.pubkey()).await;
+  assert_eq!((emptied.amount, emptied.claimed, emptied.paid), (0, 0, 0));
+
+  // the recipient continues the schedule where the seller left it
+  warp_to(&mut context, TGE + 10 * MONTH).await;
+  buyer.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &buyer.target_token).await, 630);
+  assert_error(seller.claim(&mut context, &test_sell).await, TokensellError::NothingToClaim);
+
+  let instruction = client::close_participant(&program_id(), &seller.pubkey(), &test_sell.pubkey());
+  process(&mut context, &[instruction], &[&seller.wallet]).await.unwrap();
+}
+
+#[tokio::test]
+async fn position_merges_into_existing_one() {
+  let mut context = start().await;
+  let (test_sell, seller, buyer) = vesting_sale(&mut context, default_args()).await;
+
+  warp_to(&mut context, NOW + 300).await;
+  buyer.deposit(&mut context, &test_sell, 500).await.unwrap();
+
+  warp_to(&mut context, TGE + 1).await;
+  seller.claim(&mut context, &test_sell).await.unwrap();
+
+  let instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  process(&mut context, &[instruction], &[&seller.wallet]).await.unwrap();
+
+  let merged = get_participant(&mut context, &test_sell.pubkey(), &buyer.pubkey()).await;
+  assert_eq!((merged.amount, merged.claimed, merged.paid), (1_500, 100, 3_000));
+  assert_eq!(merged.last_deposit_at, NOW + 300);
+
+  warp_to(&mut context, TGE + 2).await;
+  buyer.claim(&mut context, &test_sell).await.unwrap();
+  assert_eq!(token_balance(&mut context, &buyer.target_token).await, 50);
+}
+
+#[tokio::test]
+async fn transfer_respects_sale_opt_out() {
+  let mut context = start().await;
+  let (test_sell, seller, buyer) = vesting_sale(&mut context, InitSellArgs {
+    transfers_disabled: true,
+    ..default_args()
+  }).await;
+
+  let instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  assert_error(
+    process(&mut context, &[instruction], &[&seller.wallet]).await,
+    TokensellError::TransfersDisabled,
+  );
+}
+
+#[tokio::test]
+async fn transfer_requires_owner_signature() {
+  let mut context = start().await;
+  let (test_sell, seller, buyer) = vesting_sale(&mut context, default_args()).await;
+
+  let mut instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  instruction.accounts[0].is_signer = false;
+
+  assert_error(process(&mut context, &[instruction], &[]).await, TokensellError::SignatureRequired);
+}
+
+#[tokio::test]
+async fn transfer_rejects_substituted_participants() {
+  let mut context = start().await;
+  let (test_sell, seller, buyer) = vesting_sale(&mut context, default_args()).await;
+  let payer = context.payer.pubkey();
+  let other = client::find_participant_address(&program_id(), &test_sell.pubkey(), &payer).0;
+
+  warp_to(&mut context, NOW + 300).await;
+  buyer.deposit(&mut context, &test_sell, 500).await.unwrap();
+
+  let mut instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  instruction.accounts.swap(3, 4);
+  assert_error(
+    process(&mut context, &[instruction], &[&seller.wallet]).await,
+    TokensellError::InvalidParticipantAddress,
+  );
+
+  let mut instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  instruction.accounts[4].pubkey = other;
+  assert_error(
+    process(&mut context, &[instruction], &[&seller.wallet]).await,
+    TokensellError::InvalidParticipantAddress,
+  );
+}
+
+#[tokio::test]
+async fn empty_position_cannot_be_transferred() {
+  let mut context = start().await;
+  let (test_sell, seller, buyer) = vesting_sale(&mut context, default_args()).await;
+
+  let instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  process(&mut context, &[instruction], &[&seller.wallet]).await.unwrap();
+
+  warp_to(&mut context, NOW + 300).await;
+  let instruction = transfer_instruction(&test_sell, &seller, &buyer);
+  assert_error(
+    process(&mut context, &[instruction], &[&seller.wallet]).await,
+    TokensellError::EmptyPosition,
+  );
+}
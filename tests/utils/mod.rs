@@ -0,0 +1,545 @@
+#![allow(dead_code)]
+
+use std::str::FromStr;
+
+use solana_program::{
+  clock::{Clock, UnixTimestamp},
+  instruction::{Instruction, InstructionError},
+  program_pack::Pack,
+  pubkey::Pubkey,
+  system_instruction,
+};
+use solana_program_test::{processor, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::{
+  account::Account,
+  signature::{Keypair, Signer},
+  transaction::{Transaction, TransactionError},
+  transport::TransportError,
+};
+
+use spl_token::error::TokenError;
+use spl_token_2022::{
+  extension::{transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType, StateWithExtensions},
+  state::Mint,
+};
+
+use live_tokensell::{
+  client,
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  processor::process_instruction,
+  state::{ComplianceGate, ParticipantData, RefundPolicy, SellData, TgePolicy},
+};
+
+pub const NOW: UnixTimestamp = 1_700_000_000;
+pub const MONTH: UnixTimestamp = 30 * 24 * 3600;
+
+/// TGE policy of `default_args`, with a delay short enough to set TGEs during the sale.
+pub const TGE_POLICY: TgePolicy = TgePolicy {
+  delay: 100,
+  earliest: NOW,
+  latest: NOW + 12 * MONTH,
+};
+
+pub fn program_id() -> Pubkey {
+  Pubkey::from_str("8f46h42bspUCJ6wKAegXNjKrXLR64QRosKr3WYznauWv").unwrap()
+}
+
+pub async fn start() -> ProgramTestContext {
+  let mut program_test = ProgramTest::new("live_tokensell", program_id(), processor!(process_instruction));
+  // not bundled with solana-program-test
+  program_test.add_program("spl_token_2022", spl_token_2022::id(), processor!(spl_token_2022::processor::Processor::process));
+  let mut context = program_test.start_with_context().await;
+
+  warp_to(&mut context, NOW).await;
+
+  context
+}
+
+/// Moves the bank to a new slot, with a fresh blockhash, at `unix_timestamp`.
+pub async fn warp_to(context: &mut ProgramTestContext, unix_timestamp: UnixTimestamp) {
+  let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+  context.warp_to_slot(clock.slot + 2).unwrap();
+
+  let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+  clock.unix_timestamp = unix_timestamp;
+  context.set_sysvar(&clock);
+
+  context.last_blockhash = context
+    .banks_client
+    .get_new_latest_blockhash(&context.last_blockhash)
+    .await
+    .unwrap();
+}
+
+pub async fn process(
+  context: &mut ProgramTestContext,
+  instructions: &[Instruction],
+  signers: &[&Keypair],
+) -> Result<(), TransportError> {
+  let mut all_signers = vec![&context.payer];
+  all_signers.extend_from_slice(signers);
+
+  let transaction = Transaction::new_signed_with_payer(
+    instructions,
+    Some(&context.payer.pubkey()),
+    &all_signers,
+    context.last_blockhash,
+  );
+
+  context.banks_client.process_transaction(transaction).await
+}
+
+pub fn assert_error(result: Result<(), TransportError>, expected: TokensellError) {
+  match result {
+    Err(TransportError::TransactionError(TransactionError::InstructionError(
+      _,
+      InstructionError::Custom(code),
+    ))) => assert_eq!(code, expected.clone() as u32, "expected {:?}", expected),
+    other => panic!("expected {:?}, got {:?}", expected, other),
+  }
+}
+
+/// Token program errors reach the caller unchanged through the program's transfers.
+pub fn assert_token_error(result: Result<(), TransportError>, expected: TokenError) {
+  match result {
+    Err(TransportError::TransactionError(TransactionError::InstructionError(
+      _,
+      InstructionError::Custom(code),
+    ))) => assert_eq!(code, expected.clone() as u32, "expected {:?}", expected),
+    other => panic!("expected {:?}, got {:?}", expected, other),
+  }
+}
+
+pub async fn get_account(context: &mut ProgramTestContext, address: &Pubkey) -> Option<Account> {
+  context.banks_client.get_account(*address).await.unwrap()
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+  let account = get_account(context, address).await.unwrap();
+  StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap().base.amount
+}
+
+pub async fn get_sell(context: &mut ProgramTestContext, address: &Pubkey) -> SellData {
+  let account = get_account(context, address).await.unwrap();
+  client::decode_sell(&account.data).unwrap()
+}
+
+pub async fn get_participant(context: &mut ProgramTestContext, sell: &Pubkey, owner: &Pubkey) -> ParticipantData {
+  let (address, _) = client::find_participant_address(&program_id(), sell, owner);
+  let account = get_account(context, &address).await.unwrap();
+  client::decode_participant(&account.data).unwrap()
+}
+
+pub async fn fund(context: &mut ProgramTestContext, address: &Pubkey, lamports: u64) {
+  let payer = context.payer.pubkey();
+  process(context, &[system_instruction::transfer(&payer, address, lamports)], &[]).await.unwrap();
+}
+
+/// Token program of a test mint, and its `(basis points, maximum fee)` transfer fee.
+#[derive(Clone, Copy)]
+pub struct MintConfig {
+  pub token_program: Pubkey,
+  pub transfer_fee: Option<(u16, u64)>,
+}
+
+impl MintConfig {
+  pub fn classic() -> MintConfig {
+    MintConfig {
+      token_program: spl_token::id(),
+      transfer_fee: None,
+    }
+  }
+
+  pub fn token_2022(transfer_fee: Option<(u16, u64)>) -> MintConfig {
+    MintConfig {
+      token_program: spl_token_2022::id(),
+      transfer_fee,
+    }
+  }
+}
+
+pub async fn create_mint(context: &mut ProgramTestContext, decimals: u8) -> Pubkey {
+  create_mint_with(context, decimals, MintConfig::classic()).await
+}
+
+pub async fn create_mint_with(context: &mut ProgramTestContext, decimals: u8, config: MintConfig) -> Pubkey {
+  let mint = Keypair::new();
+  let payer = context.payer.pubkey();
+  let rent = context.banks_client.get_rent().await.unwrap();
+
+  let (size, mut instructions) = match (config.token_program == spl_token_2022::id(), config.transfer_fee) {
+    (true, Some((basis_points, maximum_fee))) => (
+      ExtensionType::get_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]),
+      vec![initialize_transfer_fee_config(
+        &config.token_program,
+        &mint.pubkey(),
+        Some(&payer),
+        Some(&payer),
+        basis_points,
+        maximum_fee,
+      ).unwrap()],
+    ),
+    _ => (Mint::LEN, vec![]),
+  };
+
+  instructions.insert(0, system_instruction::create_account(
+    &payer,
+    &mint.pubkey(),
+    rent.minimum_balance(size),
+    size as u64,
+    &config.token_program,
+  ));
+  instructions.push(if config.token_program == spl_token_2022::id() {
+    spl_token_2022::instruction::initialize_mint(&config.token_program, &mint.pubkey(), &payer, None, decimals).unwrap()
+  } else {
+    spl_token::instruction::initialize_mint(&config.token_program, &mint.pubkey(), &payer, None, decimals).unwrap()
+  });
+
+  process(context, &instructions, &[&mint]).await.unwrap();
+
+  mint.pubkey()
+}
+
+/// Creates a token account of the program owning `mint`, sized for the extensions it requires.
+pub async fn create_token_account(context: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+  let account = Keypair::new();
+  let payer = context.payer.pubkey();
+  let rent = context.banks_client.get_rent().await.unwrap();
+  let mint_account = get_account(context, mint).await.unwrap();
+  let token_program = mint_account.owner;
+
+  let (size, initialize) = if token_program == spl_token_2022::id() {
+    let extensions = StateWithExtensions::<Mint>::unpack(&mint_account.data).unwrap().get_extension_types().unwrap();
+    (
+      ExtensionType::get_account_len::<spl_token_2022::state::Account>(
+        &ExtensionType::get_required_init_account_extensions(&extensions),
+      ),
+      spl_token_2022::instruction::initialize_account(&token_program, &account.pubkey(), mint, owner).unwrap(),
+    )
+  } else {
+    (
+      spl_token::state::Account::LEN,
+      spl_token::instruction::initialize_account(&token_program, &account.pubkey(), mint, owner).unwrap(),
+    )
+  };
+
+  process(
+    context,
+    &[
+      system_instruction::create_account(
+        &payer,
+        &account.pubkey(),
+        rent.minimum_balance(size),
+        size as u64,
+        &token_program,
+      ),
+      initialize,
+    ],
+    &[&account],
+  )
+  .await
+  .unwrap();
+
+  account.pubkey()
+}
+
+pub async fn mint_program(context: &mut ProgramTestContext, mint: &Pubkey) -> Pubkey {
+  get_account(context, mint).await.unwrap().owner
+}
+
+pub async fn mint_to(context: &mut ProgramTestContext, mint: &Pubkey, account: &Pubkey, amount: u64) {
+  let payer = context.payer.pubkey();
+  let token_program = mint_program(context, mint).await;
+
+  let instruction = if token_program == spl_token_2022::id() {
+    spl_token_2022::instruction::mint_to(&token_program, mint, account, &payer, &[], amount).unwrap()
+  } else {
+    spl_token::instruction::mint_to(&token_program, mint, account, &payer, &[], amount).unwrap()
+  };
+
+  process(context, &[instruction], &[]).await.unwrap();
+}
+
+pub fn default_args() -> InitSellArgs {
+  InitSellArgs {
+    instruction: 1,
+    // 2 base units of the 6 decimal source mint per whole target token
+    price_numerator: 2,
+    price_denominator: 1_000_000,
+    start_time: NOW + 100,
+    end_time: NOW + 10_000,
+    initial_perc: 10,
+    total_months: 10,
+    min_deposit: 100,
+    total_amount: 1_000_000,
+    transfers_disabled: false,
+    sale_id: 0,
+    price_oracle: None,
+    refund_policy: RefundPolicy::None,
+    compliance: None,
+    voucher_signer: None,
+    staking: None,
+    tge_policy: Some(TGE_POLICY),
+    price_oracle_program: None,
+  }
+}
+
+/// Sales created by `owner`, which is the sale id of its next sale.
+pub async fn owner_sales(context: &mut ProgramTestContext, owner: &Pubkey) -> u64 {
+  let (counter, _) = client::find_sale_counter_address(&program_id(), owner);
+
+  match get_account(context, &counter).await {
+    Some(account) => client::decode_sale_counter(&account.data).unwrap().sales,
+    None => 0,
+  }
+}
+
+pub async fn directory_sales(context: &mut ProgramTestContext) -> u64 {
+  let (directory, _) = client::find_directory_address(&program_id());
+
+  match get_account(context, &directory).await {
+    Some(account) => client::decode_directory(&account.data).unwrap().sales,
+    None => 0,
+  }
+}
+
+/// A sale owned by the context payer, selling `target_mint` for `source_mint`.
+pub struct TestSell {
+  pub sell: Pubkey,
+  pub sale_id: u64,
+  pub vault: Pubkey,
+  /// minted into the vault once `init` created it
+  pub vault_amount: u64,
+  pub target_mint: Pubkey,
+  pub source_mint: Pubkey,
+  pub target_acc: Pubkey,
+  pub target_program: Pubkey,
+  pub source_program: Pubkey,
+  /// passed to deposits of sales priced in USD
+  pub price_feed: Option<Pubkey>,
+  /// receives deposits of sales with a refund policy
+  pub escrow: Option<Pubkey>,
+  /// whose attestations deposits, and gated claims, pass
+  pub compliance: Option<ComplianceGate>,
+  /// mint of the staking tiers, deposits pass the payer's stake of it
+  pub staking_mint: Option<Pubkey>,
+}
+
+impl TestSell {
+  /// Creates the mints and the proceeds account, without calling init_sell.
+  pub async fn prepare(context: &mut ProgramTestContext, vault_amount: u64) -> TestSell {
+    TestSell::prepare_with(context, vault_amount, MintConfig::classic(), MintConfig::classic()).await
+  }
+
+  pub async fn prepare_with(
+    context: &mut ProgramTestContext,
+    vault_amount: u64,
+    target: MintConfig,
+    source: MintConfig,
+  ) -> TestSell {
+    let payer = context.payer.pubkey();
+    let sale_id = owner_sales(context, &payer).await;
+    let (sell, _) = client::find_sell_address(&program_id(), &payer, sale_id);
+    let (vault, _) = client::find_vault_address(&program_id(), &sell);
+
+    let target_mint = create_mint_with(context, 0, target).await;
+    let source_mint = create_mint_with(context, 6, source).await;
+    let target_acc = create_token_account(context, &source_mint, &payer).await;
+
+    TestSell {
+      sell,
+      sale_id,
+      vault,
+      vault_amount,
+      target_mint,
+      source_mint,
+      target_acc,
+      target_program: target.token_program,
+      source_program: source.token_program,
+      price_feed: None,
+      escrow: None,
+      compliance: None,
+      staking_mint: None,
+    }
+  }
+
+  pub async fn create(context: &mut ProgramTestContext, args: InitSellArgs) -> TestSell {
+    TestSell::create_with(context, args, MintConfig::classic(), MintConfig::classic()).await
+  }
+
+  pub async fn create_with(
+    context: &mut ProgramTestContext,
+    args: InitSellArgs,
+    target: MintConfig,
+    source: MintConfig,
+  ) -> TestSell {
+    let mut test_sell = TestSell::prepare_with(context, args.total_amount, target, source).await;
+
+    if args.refund_policy != RefundPolicy::None {
+      test_sell.escrow = Some(client::find_escrow_address(&program_id(), &test_sell.sell).0);
+    }
+
+    test_sell.compliance = args.compliance.clone();
+    test_sell.staking_mint = args.staking.as_ref().map(|staking| staking.mint);
+
+    test_sell.init(context, args).await.unwrap();
+    test_sell
+  }
+
+  pub fn pubkey(&self) -> Pubkey {
+    self.sell
+  }
+
+  /// Account deposits pay into, the escrow of sales with a refund policy.
+  pub fn payment_account(&self) -> Pubkey {
+    self.escrow.unwrap_or(self.target_acc)
+  }
+
+  /// Compliance authority whose attestations claims pass, when the sale gates claims.
+  pub fn claim_compliance_authority(&self) -> Option<&Pubkey> {
+    self.compliance
+      .as_ref()
+      .filter(|gate| gate.gate_claims)
+      .map(|gate| &gate.authority)
+  }
+
+  /// Directory index recorded on the sale, none for sales outside the directory.
+  pub async fn directory_index(&self, context: &mut ProgramTestContext) -> Option<u64> {
+    let account = get_account(context, &self.sell).await?;
+    client::decode_sell(&account.data).ok()?.directory_index
+  }
+
+  pub async fn init_instruction(&self, context: &mut ProgramTestContext, args: InitSellArgs) -> Instruction {
+    client::init_sell(
+      &program_id(),
+      &context.payer.pubkey(),
+      &self.target_mint,
+      &self.source_mint,
+      &self.target_acc,
+      &self.target_program,
+      &self.source_program,
+      directory_sales(context).await,
+      InitSellArgs {
+        sale_id: self.sale_id,
+        ..args
+      },
+    )
+  }
+
+  /// Initializes the sale, then mints `vault_amount` into the vault it created.
+  pub async fn init(&self, context: &mut ProgramTestContext, args: InitSellArgs) -> Result<(), TransportError> {
+    let instruction = self.init_instruction(context, args).await;
+    process(context, &[instruction], &[]).await?;
+
+    if self.vault_amount > 0 {
+      mint_to(context, &self.target_mint, &self.vault, self.vault_amount).await;
+    }
+
+    Ok(())
+  }
+
+  /// Queues `tge`, the change waits for the delay of the sale's TGE policy.
+  pub async fn queue_tge(&self, context: &mut ProgramTestContext, tge: UnixTimestamp) -> Result<(), TransportError> {
+    let instruction = client::set_tge(
+      &program_id(),
+      &context.payer.pubkey(),
+      &self.pubkey(),
+      &self.target_mint,
+      &self.vault,
+      tge,
+    );
+    process(context, &[instruction], &[]).await
+  }
+
+  pub async fn apply_tge(&self, context: &mut ProgramTestContext) -> Result<(), TransportError> {
+    let instruction = client::apply_tge(&program_id(), &self.pubkey(), self.directory_index(context).await);
+    process(context, &[instruction], &[]).await
+  }
+
+  /// Queues `tge` and applies it once the delay is over, then turns the clock back.
+  pub async fn set_tge(&self, context: &mut ProgramTestContext, tge: UnixTimestamp) -> Result<(), TransportError> {
+    self.queue_tge(context, tge).await?;
+
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let pending = get_sell(context, &self.pubkey()).await.pending_tge.unwrap();
+
+    warp_to(context, pending.eta).await;
+    let result = self.apply_tge(context).await;
+    warp_to(context, now).await;
+
+    result
+  }
+}
+
+/// A buyer holding `source_mint` tokens and lamports to pay for its participant account.
+pub struct TestParticipant {
+  pub wallet: Keypair,
+  pub source_token: Pubkey,
+  pub target_token: Pubkey,
+}
+
+impl TestParticipant {
+  pub async fn create(context: &mut ProgramTestContext, test_sell: &TestSell, balance: u64) -> TestParticipant {
+    let wallet = Keypair::new();
+    fund(context, &wallet.pubkey(), 1_000_000_000).await;
+
+    let source_token = create_token_account(context, &test_sell.source_mint, &wallet.pubkey()).await;
+    let target_token = create_token_account(context, &test_sell.target_mint, &wallet.pubkey()).await;
+
+    if balance > 0 {
+      mint_to(context, &test_sell.source_mint, &source_token, balance).await;
+    }
+
+    TestParticipant {
+      wallet,
+      source_token,
+      target_token,
+    }
+  }
+
+  pub fn pubkey(&self) -> Pubkey {
+    self.wallet.pubkey()
+  }
+
+  pub fn deposit_instruction(&self, test_sell: &TestSell, amount: u64) -> Instruction {
+    client::make_deposit(
+      &program_id(),
+      &self.pubkey(),
+      &test_sell.pubkey(),
+      &self.source_token,
+      &test_sell.payment_account(),
+      &self.pubkey(),
+      &test_sell.source_mint,
+      &test_sell.source_program,
+      test_sell.price_feed.as_ref(),
+      test_sell.compliance.as_ref().map(|gate| &gate.authority),
+      None,
+      test_sell.staking_mint.as_ref(),
+      amount,
+    )
+  }
+
+  pub async fn deposit(&self, context: &mut ProgramTestContext, test_sell: &TestSell, amount: u64) -> Result<(), TransportError> {
+    let instruction = self.deposit_instruction(test_sell, amount);
+    process(context, &[instruction], &[&self.wallet]).await
+  }
+
+  pub fn claim_instruction(&self, test_sell: &TestSell) -> Instruction {
+    client::claim(
+      &program_id(),
+      &self.pubkey(),
+      &test_sell.pubkey(),
+      &test_sell.target_mint,
+      &test_sell.vault,
+      &self.target_token,
+      &test_sell.target_program,
+      test_sell.claim_compliance_authority(),
+    )
+  }
+
+  pub async fn claim(&self, context: &mut ProgramTestContext, test_sell: &TestSell) -> Result<(), TransportError> {
+    let instruction = self.claim_instruction(test_sell);
+    process(context, &[instruction], &[&self.wallet]).await
+  }
+}
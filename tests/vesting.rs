@@ -0,0 +1,82 @@
+use proptest::prelude::*;
+
+use live_tokensell::{state::MONTH_SECONDS, vesting::vested_amount};
+
+const TGE: i64 = 1_700_000_000;
+const MONTH: i64 = MONTH_SECONDS as i64;
+
+#[test]
+fn nothing_vests_before_tge() {
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE - 1).unwrap(), 0);
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE).unwrap(), 0);
+}
+
+#[test]
+fn initial_part_unlocks_after_tge() {
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE + 1).unwrap(), 100);
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE + MONTH - 1).unwrap(), 100);
+}
+
+#[test]
+fn rest_unlocks_monthly() {
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE + MONTH).unwrap(), 190);
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE + 3 * MONTH).unwrap(), 370);
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE + 10 * MONTH).unwrap(), 1_000);
+  assert_eq!(vested_amount(1_000, 10, 10, TGE, TGE + 100 * MONTH).unwrap(), 1_000);
+}
+
+#[test]
+fn zero_months_unlocks_everything() {
+  assert_eq!(vested_amount(1_000, 10, 0, TGE, TGE + 1).unwrap(), 1_000);
+}
+
+#[test]
+fn large_amounts_do_not_overflow() {
+  assert_eq!(vested_amount(u64::MAX, 100, 10, TGE, TGE + 1).unwrap(), u64::MAX);
+  assert_eq!(vested_amount(u64::MAX, 0, u64::MAX, TGE, i64::MAX).unwrap(), 3_558_399_704_920);
+  assert_eq!(vested_amount(u64::MAX, 50, 2, TGE, TGE + MONTH).unwrap(), u64::MAX / 2 + u64::MAX / 4 + 1);
+}
+
+#[test]
+fn extreme_timestamps_fail_instead_of_wrapping() {
+  assert!(vested_amount(1_000, 10, 10, i64::MIN, i64::MAX).is_err());
+}
+
+proptest! {
+  #[test]
+  fn vested_never_exceeds_total(
+    total in any::<u64>(),
+    initial_perc in any::<u64>(),
+    total_months in any::<u64>(),
+    tge in -1_000_000_000_000i64..1_000_000_000_000,
+    now in -1_000_000_000_000i64..1_000_000_000_000,
+  ) {
+    let vested = vested_amount(total, initial_perc, total_months, tge, now).unwrap();
+    prop_assert!(vested <= total);
+  }
+
+  #[test]
+  fn vested_is_monotonic_in_time(
+    total in any::<u64>(),
+    initial_perc in 0u64..=100,
+    total_months in 0u64..240,
+    tge in 0i64..2_000_000_000,
+    offset in 0i64..1_000_000_000,
+    step in 0i64..1_000_000_000,
+  ) {
+    let earlier = vested_amount(total, initial_perc, total_months, tge, tge + offset).unwrap();
+    let later = vested_amount(total, initial_perc, total_months, tge, tge + offset + step).unwrap();
+    prop_assert!(earlier <= later);
+  }
+
+  #[test]
+  fn everything_vests_after_schedule(
+    total in any::<u64>(),
+    initial_perc in 0u64..=100,
+    total_months in 1u64..240,
+    tge in 0i64..2_000_000_000,
+  ) {
+    let end = tge + total_months as i64 * MONTH;
+    prop_assert_eq!(vested_amount(total, initial_perc, total_months, tge, end).unwrap(), total);
+  }
+}
@@ -0,0 +1,264 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use solana_program::{instruction::Instruction, pubkey::Pubkey, system_instruction};
+use solana_program_test::{tokio, ProgramTestContext};
+use solana_sdk::{signature::{Keypair, Signer}, transport::TransportError};
+
+use live_tokensell::{
+  client::{self, Voucher},
+  error::TokensellError,
+  instructions::init_sell::InitSellArgs,
+  state::{Key, VoucherReceipt},
+  voucher::{ed25519_instruction_data, parse_voucher, ED25519_MESSAGE_START},
+};
+
+use borsh::BorshDeserialize;
+
+use utils::*;
+
+async fn voucher_sale(context: &mut ProgramTestContext, signer: &Keypair) -> (TestSell, TestParticipant) {
+  let test_sell = TestSell::create(context, InitSellArgs {
+    voucher_signer: Some(signer.pubkey()),
+    ..default_args()
+  }).await;
+  let participant = TestParticipant::create(context, &test_sell, 10_000).await;
+
+  warp_to(context, NOW + 200).await;
+
+  (test_sell, participant)
+}
+
+fn voucher(test_sell: &TestSell, participant: &TestParticipant, max_amount: u64, nonce: u64) -> Voucher {
+  Voucher {
+    wallet: participant.pubkey(),
+    sell: test_sell.pubkey(),
+    max_amount,
+    expires_at: NOW + 1_000,
+    nonce,
+  }
+}
+
+fn sign(signer: &Keypair, voucher: &Voucher) -> [u8; 64] {
+  let signature = signer.sign_message(&client::voucher_message(voucher));
+  <[u8; 64]>::try_from(signature.as_ref()).unwrap()
+}
+
+fn deposit_instruction(test_sell: &TestSell, participant: &TestParticipant, nonce: u64, amount: u64) -> Instruction {
+  client::make_deposit(
+    &program_id(),
+    &participant.pubkey(),
+    &test_sell.pubkey(),
+    &participant.source_token,
+    &test_sell.target_acc,
+    &participant.pubkey(),
+    &test_sell.source_mint,
+    &test_sell.source_program,
+    None,
+    None,
+    Some(nonce),
+    None,
+    amount,
+  )
+}
+
+async fn deposit_with(
+  context: &mut ProgramTestContext,
+  signer: &Keypair,
+  voucher: &Voucher,
+  test_sell: &TestSell,
+  participant: &TestParticipant,
+  amount: u64,
+) -> Result<(), TransportError> {
+  let instructions = [
+    client::voucher_instruction(&signer.pubkey(), &sign(signer, voucher), voucher),
+    deposit_instruction(test_sell, participant, voucher.nonce, amount),
+  ];
+  process(context, &instructions, &[&participant.wallet]).await
+}
+
+async fn receipt(context: &mut ProgramTestContext, test_sell: &TestSell, participant: &TestParticipant, nonce: u64) -> VoucherReceipt {
+  let (address, _) = client::find_voucher_receipt_address(&program_id(), &test_sell.pubkey(), &participant.pubkey(), nonce);
+  let account = get_account(context, &address).await.unwrap();
+  VoucherReceipt::try_from_slice(&account.data).unwrap()
+}
+
+#[test]
+fn parses_signed_voucher() {
+  let signer = Pubkey::new_unique();
+  let voucher = Voucher {
+    wallet: Pubkey::new_unique(),
+    sell: Pubkey::new_unique(),
+    max_amount: 1_000,
+    expires_at: 1,
+    nonce: 7,
+  };
+  let data = ed25519_instruction_data(&signer, &[0; 64], &client::voucher_message(&voucher));
+
+  assert_eq!(parse_voucher(&data, &signer), Ok(voucher));
+  assert_eq!(parse_voucher(&data, &Pubkey::new_unique()), Err(TokensellError::InvalidVoucher));
+  assert_eq!(parse_voucher(&data[..ED25519_MESSAGE_START], &signer), Err(TokensellError::InvalidVoucher));
+}
+
+#[test]
+fn rejects_offsets_into_other_instructions() {
+  let signer = Pubkey::new_unique();
+  let voucher = Voucher {
+    wallet: Pubkey::new_unique(),
+    sell: Pubkey::new_unique(),
+    max_amount: 1_000,
+    expires_at: 1,
+    nonce: 7,
+  };
+  let mut data = ed25519_instruction_data(&signer, &[0; 64], &client::voucher_message(&voucher));
+  // message taken from the first instruction of the transaction
+  data[14..16].copy_from_slice(&0u16.to_le_bytes());
+
+  assert_eq!(parse_voucher(&data, &signer), Err(TokensellError::InvalidVoucher));
+}
+
+#[tokio::test]
+async fn deposits_are_limited_to_the_voucher_amount() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+  let voucher = voucher(&test_sell, &participant, 1_000, 1);
+
+  deposit_with(&mut context, &signer, &voucher, &test_sell, &participant, 600).await.unwrap();
+  deposit_with(&mut context, &signer, &voucher, &test_sell, &participant, 400).await.unwrap();
+
+  let data = receipt(&mut context, &test_sell, &participant, 1).await;
+  assert_eq!(data.key, Key::VoucherReceipt);
+  assert_eq!(data.spent, 1_000);
+
+  warp_to(&mut context, NOW + 300).await;
+  assert_error(
+    deposit_with(&mut context, &signer, &voucher, &test_sell, &participant, 1).await,
+    TokensellError::VoucherExceeded,
+  );
+
+  let data = get_participant(&mut context, &test_sell.pubkey(), &participant.pubkey()).await;
+  assert_eq!(data.amount, 1_000);
+}
+
+#[tokio::test]
+async fn each_nonce_is_a_separate_allocation() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+
+  let first = voucher(&test_sell, &participant, 1_000, 1);
+  deposit_with(&mut context, &signer, &first, &test_sell, &participant, 1_000).await.unwrap();
+
+  let second = voucher(&test_sell, &participant, 500, 2);
+  deposit_with(&mut context, &signer, &second, &test_sell, &participant, 500).await.unwrap();
+
+  assert_eq!(receipt(&mut context, &test_sell, &participant, 2).await.spent, 500);
+}
+
+#[tokio::test]
+async fn deposit_needs_voucher_instruction() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+
+  let instruction = deposit_instruction(&test_sell, &participant, 1, 1_000);
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::VoucherRequired,
+  );
+
+  // the voucher has to come right before the deposit
+  let voucher = voucher(&test_sell, &participant, 1_000, 1);
+  let instructions = [
+    client::voucher_instruction(&signer.pubkey(), &sign(&signer, &voucher), &voucher),
+    system_instruction::transfer(&participant.pubkey(), &participant.pubkey(), 1),
+    deposit_instruction(&test_sell, &participant, 1, 1_000),
+  ];
+  assert_error(
+    process(&mut context, &instructions, &[&participant.wallet]).await,
+    TokensellError::VoucherRequired,
+  );
+}
+
+#[tokio::test]
+async fn voucher_of_other_signer_is_rejected() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let other = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+  let voucher = voucher(&test_sell, &participant, 1_000, 1);
+
+  assert_error(
+    deposit_with(&mut context, &other, &voucher, &test_sell, &participant, 1_000).await,
+    TokensellError::InvalidVoucher,
+  );
+}
+
+#[tokio::test]
+async fn voucher_of_other_wallet_is_rejected() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+  let other = TestParticipant::create(&mut context, &test_sell, 10_000).await;
+  let voucher = voucher(&test_sell, &other, 1_000, 1);
+
+  assert_error(
+    deposit_with(&mut context, &signer, &voucher, &test_sell, &participant, 1_000).await,
+    TokensellError::InvalidVoucher,
+  );
+}
+
+#[tokio::test]
+async fn expired_voucher_is_rejected() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+  let voucher = voucher(&test_sell, &participant, 1_000, 1);
+
+  warp_to(&mut context, NOW + 1_001).await;
+  assert_error(
+    deposit_with(&mut context, &signer, &voucher, &test_sell, &participant, 1_000).await,
+    TokensellError::VoucherExpired,
+  );
+}
+
+#[tokio::test]
+async fn tampered_voucher_fails_signature_check() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+  let voucher = voucher(&test_sell, &participant, 1_000, 1);
+  let signature = sign(&signer, &voucher);
+
+  let raised = Voucher {
+    max_amount: 1_000_000,
+    ..voucher
+  };
+  let instructions = [
+    client::voucher_instruction(&signer.pubkey(), &signature, &raised),
+    deposit_instruction(&test_sell, &participant, 1, 5_000),
+  ];
+
+  assert!(process(&mut context, &instructions, &[&participant.wallet]).await.is_err());
+  assert!(get_account(&mut context, &client::find_participant_address(&program_id(), &test_sell.pubkey(), &participant.pubkey()).0).await.is_none());
+}
+
+#[tokio::test]
+async fn positions_cant_be_transferred() {
+  let mut context = start().await;
+  let signer = Keypair::new();
+  let (test_sell, participant) = voucher_sale(&mut context, &signer).await;
+  let recipient = TestParticipant::create(&mut context, &test_sell, 0).await;
+  let voucher = voucher(&test_sell, &participant, 1_000, 1);
+
+  deposit_with(&mut context, &signer, &voucher, &test_sell, &participant, 1_000).await.unwrap();
+
+  // the recipient would hold more than its own vouchers allow
+  let instruction = client::transfer_position(&program_id(), &participant.pubkey(), &recipient.pubkey(), &test_sell.pubkey(), None, None);
+  assert_error(
+    process(&mut context, &[instruction], &[&participant.wallet]).await,
+    TokensellError::TransfersDisabled,
+  );
+}